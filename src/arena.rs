@@ -0,0 +1,1329 @@
+use std::str::Chars;
+use std::cmp::Ordering::Less;
+use std::cmp::Ordering::Equal;
+use std::cmp::Ordering::Greater;
+use std::io::Write;
+use std::fmt;
+use std::mem;
+
+use super::Stats;
+use super::DistStat;
+use super::walk::NodeRef;
+use super::walk::Walker;
+use super::walk::CompleteWalker;
+use super::walk::NeighborWalker;
+use super::walk::CrosswordWalker;
+
+
+/// An `ArenaTst` is a ternary tree which stores its nodes in a single `Vec`, and links them with `u32` indices
+/// instead of `Box` pointers. It has the same public API as [`Tst`]( ./struct.Tst.html), but nodes are packed
+/// together in memory, which is friendlier to the cache of large dictionaries and avoids heap fragmentation.
+/// Slots of removed nodes are kept in a free list and reused by later insertions.
+///
+/// ```
+/// # use ternary_tree::ArenaTst;
+/// let mut map = ArenaTst::new();
+///
+/// map.insert("foo", "🍄🍄");
+/// map.insert("bar", "🐟");
+///
+/// assert_eq!(map.get("foo"), Some(&"🍄🍄"));
+/// assert_eq!(map.len(), 2);
+/// ```
+///
+/// An `ArenaTst` can hold up to `u32::MAX - 1` nodes.
+
+pub struct ArenaTst<T> {
+
+    arena: Arena<T>,
+    root: u32,
+    count: usize
+}
+
+
+const NIL: u32 = u32::MAX;
+
+
+struct ArenaNode<T> {
+
+    label: char,
+    value: Option<T>,
+    left: u32,
+    middle: u32,
+    right: u32
+}
+
+
+impl<T> fmt::Debug for ArenaNode<T> {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+
+            let value_box = match self.value {
+
+                None => "☐", Some(_) => "☑"
+            };
+
+        write!(f, "{}-{}", value_box, self.label)
+    }
+}
+
+
+// Free slots are chained through their middle link, starting from `free`.
+
+struct Arena<T> {
+
+    nodes: Vec<ArenaNode<T>>,
+    free: u32
+}
+
+
+impl<T> Arena<T> {
+
+    fn alloc(&mut self, label: char) -> u32 {
+
+        let node = ArenaNode { label, value: None, left: NIL, middle: NIL, right: NIL };
+
+        if self.free != NIL {
+
+            let index = self.free;
+            self.free = self.nodes[index as usize].middle;
+            self.nodes[index as usize] = node;
+
+            index
+
+        } else {
+
+            let index = self.nodes.len();
+            assert!(index < NIL as usize, "too many nodes in ArenaTst");
+
+            self.nodes.push(node);
+
+            index as u32
+        }
+    }
+
+
+    fn release(&mut self, index: u32) {
+
+        let node = &mut self.nodes[index as usize];
+
+        node.value = None;
+        node.left = NIL;
+        node.right = NIL;
+        node.middle = self.free;
+
+        self.free = index;
+    }
+}
+
+
+fn insert_r<T>(arena: &mut Arena<T>, link: u32, label: char, mut key_tail: Chars, value: T) -> (u32, Option<T>) {
+
+    let link = if link == NIL { arena.alloc(label) } else { link };
+
+    let node_label = arena.nodes[link as usize].label;
+
+    let old_value = match label.cmp(&node_label) {
+
+        Less => {
+
+            let child = arena.nodes[link as usize].left;
+            let (child, old_value) = insert_r(arena, child, label, key_tail, value);
+            arena.nodes[link as usize].left = child;
+
+            old_value
+        }
+
+        Greater => {
+
+            let child = arena.nodes[link as usize].right;
+            let (child, old_value) = insert_r(arena, child, label, key_tail, value);
+            arena.nodes[link as usize].right = child;
+
+            old_value
+        }
+
+        Equal => {
+
+            let new_label = key_tail.next();
+
+            match new_label {
+
+                None => arena.nodes[link as usize].value.replace(value),
+
+                Some(label) => {
+
+                    let child = arena.nodes[link as usize].middle;
+                    let (child, old_value) = insert_r(arena, child, label, key_tail, value);
+                    arena.nodes[link as usize].middle = child;
+
+                    old_value
+                }
+            }
+        }
+    };
+
+    (link, old_value)
+}
+
+
+fn get_r<'a, T>(nodes: &'a [ArenaNode<T>], link: u32, label: char, key_tail: &mut Chars) -> Option<&'a T> {
+
+    if link == NIL {
+
+        return None;
+    }
+
+    let node = &nodes[link as usize];
+
+    match label.cmp(&node.label) {
+
+        Less => get_r(nodes, node.left, label, key_tail),
+
+        Equal => {
+
+            let new_label = key_tail.next();
+
+            match new_label {
+
+                None => node.value.as_ref(),
+
+                Some(label) => get_r(nodes, node.middle, label, key_tail)
+            }
+        },
+
+        Greater => get_r(nodes, node.right, label, key_tail),
+    }
+}
+
+
+fn get_r_mut<'a, T>(nodes: &'a mut [ArenaNode<T>], link: u32, label: char, key_tail: &mut Chars) -> Option<&'a mut T> {
+
+    if link == NIL {
+
+        return None;
+    }
+
+    let (node_label, left, middle, right) = {
+
+        let node = &nodes[link as usize];
+        (node.label, node.left, node.middle, node.right)
+    };
+
+    match label.cmp(&node_label) {
+
+        Less => get_r_mut(nodes, left, label, key_tail),
+
+        Equal => {
+
+            let new_label = key_tail.next();
+
+            match new_label {
+
+                None => nodes[link as usize].value.as_mut(),
+
+                Some(label) => get_r_mut(nodes, middle, label, key_tail)
+            }
+        },
+
+        Greater => get_r_mut(nodes, right, label, key_tail),
+    }
+}
+
+
+fn is_prunable<T>(node: &ArenaNode<T>) -> bool {
+
+    node.value.is_none() && node.left == NIL && node.middle == NIL && node.right == NIL
+}
+
+
+fn remove_r<T>(arena: &mut Arena<T>, link: u32, label: char, key_tail: &mut Chars) -> (bool, Option<T>) {
+
+    if link == NIL {
+
+        return (false, None);
+    }
+
+    let node_label = arena.nodes[link as usize].label;
+
+    match label.cmp(&node_label) {
+
+        Less => {
+
+            let child = arena.nodes[link as usize].left;
+            let (prune, old_value) = remove_r(arena, child, label, key_tail);
+
+            if prune {
+
+                arena.release(child);
+                arena.nodes[link as usize].left = NIL;
+            }
+
+            (is_prunable(&arena.nodes[link as usize]), old_value)
+        }
+
+        Equal => {
+
+            let new_label = key_tail.next();
+
+            match new_label {
+
+                None => {
+
+                    let node = &mut arena.nodes[link as usize];
+                    let old_value = node.value.take();
+
+                    (old_value.is_some() && is_prunable(node), old_value)
+                }
+
+                Some(label) => {
+
+                    let child = arena.nodes[link as usize].middle;
+                    let (prune, old_value) = remove_r(arena, child, label, key_tail);
+
+                    if prune {
+
+                        arena.release(child);
+                        arena.nodes[link as usize].middle = NIL;
+                    }
+
+                    (is_prunable(&arena.nodes[link as usize]), old_value)
+                }
+            }
+        }
+
+        Greater => {
+
+            let child = arena.nodes[link as usize].right;
+            let (prune, old_value) = remove_r(arena, child, label, key_tail);
+
+            if prune {
+
+                arena.release(child);
+                arena.nodes[link as usize].right = NIL;
+            }
+
+            (is_prunable(&arena.nodes[link as usize]), old_value)
+        }
+    }
+}
+
+
+fn stat_r<T>(stats: Stats, nodes: &[ArenaNode<T>], link: u32, matches: usize, sides: usize, depth: usize) -> Stats {
+
+    if link == NIL {
+
+        return stats;
+    }
+
+    let node = &nodes[link as usize];
+
+    let mut stats = stat_r(stats, nodes, node.left, matches, sides+1, depth+1);
+
+    stats.count.nodes+=1;
+
+    if node.value.is_some() {
+
+        let matches = matches + 1;
+        let depth = depth + 1;
+
+        while stats.dist.len() <= depth {
+
+            stats.dist.push(DistStat { matches: 0, sides: 0, depth: 0 });
+        }
+
+        stats.dist[matches].matches+=1;
+        stats.dist[sides].sides+=1;
+        stats.dist[depth].depth+=1;
+
+        if stats.key_len.min == 0 || matches < stats.key_len.min {
+
+            stats.key_len.min = matches;
+        }
+
+        if matches > stats.key_len.max {
+
+            stats.key_len.max = matches;
+        }
+
+        stats.count.values+=1;
+    }
+
+    let stats = stat_r(stats, nodes, node.middle, matches+1, sides, depth+1);
+
+    stat_r(stats, nodes, node.right, matches, sides+1, depth+1)
+}
+
+
+fn find_complete_root_r<T>(nodes: &[ArenaNode<T>], link: u32, label: char, mut key_tail: Chars) -> u32 {
+
+    if link == NIL {
+
+        return NIL;
+    }
+
+    let node = &nodes[link as usize];
+
+    match label.cmp(&node.label) {
+
+        Less => find_complete_root_r(nodes, node.left, label, key_tail),
+
+        Greater => find_complete_root_r(nodes, node.right, label, key_tail),
+
+        Equal => {
+
+            let new_label = key_tail.next();
+
+            match new_label {
+
+                None => node.middle,
+
+                Some(label) => find_complete_root_r(nodes, node.middle, label, key_tail)
+            }
+        }
+    }
+}
+
+
+fn visit_values_r<T, C>(nodes: &[ArenaNode<T>], link: u32, callback: &mut C)
+where C: FnMut (&T) {
+
+    if link == NIL {
+
+        return;
+    }
+
+    let node = &nodes[link as usize];
+
+    visit_values_r(nodes, node.left, callback);
+
+    if let Some(ref value) = node.value {
+
+        callback(value);
+    }
+
+    visit_values_r(nodes, node.middle, callback);
+    visit_values_r(nodes, node.right, callback);
+}
+
+
+fn visit_values_r_mut<T, C>(nodes: &mut [ArenaNode<T>], link: u32, callback: &mut C)
+where C: FnMut (&mut T) {
+
+    if link == NIL {
+
+        return;
+    }
+
+    let (left, middle, right) = {
+
+        let node = &nodes[link as usize];
+        (node.left, node.middle, node.right)
+    };
+
+    visit_values_r_mut(nodes, left, callback);
+
+    if let Some(ref mut value) = nodes[link as usize].value {
+
+        callback(value);
+    }
+
+    visit_values_r_mut(nodes, middle, callback);
+    visit_values_r_mut(nodes, right, callback);
+}
+
+
+fn visit_neighbor_values_r<T, C>(nodes: &[ArenaNode<T>], link: u32, label: Option<char>, key_tail: &mut Chars, tail_len: usize, range: usize, callback: &mut C)
+where C: FnMut (&T) {
+
+    if range == 0 {
+
+        if let Some(label) = label {
+
+            if let Some(value) = get_r(nodes, link, label, key_tail) {
+
+                callback(value);
+            }
+        }
+
+    } else if link != NIL {
+
+        let node = &nodes[link as usize];
+
+        visit_neighbor_values_r(nodes, node.left, label, key_tail, tail_len, range, callback);
+
+        let new_range = match label {
+
+            None => range-1,
+
+            Some(label) => if label==node.label { range } else { range-1 }
+        };
+
+        if let Some(ref value) = node.value {
+
+            if tail_len <= new_range {
+
+                callback(value);
+            }
+        }
+
+        {
+            let mut new_tail = key_tail.clone();
+            let new_label = new_tail.next();
+
+            let new_len = if tail_len > 0 { tail_len-1 } else { tail_len };
+
+            visit_neighbor_values_r(nodes, node.middle, new_label, &mut new_tail, new_len, new_range, callback);
+        }
+
+        visit_neighbor_values_r(nodes, node.right, label, key_tail, tail_len, range, callback);
+    }
+}
+
+
+fn visit_neighbor_values_r_mut<T, C>(nodes: &mut [ArenaNode<T>], link: u32, label: Option<char>, key_tail: &mut Chars, tail_len: usize, range: usize, callback: &mut C)
+where C: FnMut (&mut T) {
+
+    if range == 0 {
+
+        if let Some(label) = label {
+
+            if let Some(value) = get_r_mut(nodes, link, label, key_tail) {
+
+                callback(value);
+            }
+        }
+
+    } else if link != NIL {
+
+        let (node_label, left, middle, right) = {
+
+            let node = &nodes[link as usize];
+            (node.label, node.left, node.middle, node.right)
+        };
+
+        visit_neighbor_values_r_mut(nodes, left, label, key_tail, tail_len, range, callback);
+
+        let new_range = match label {
+
+            None => range-1,
+
+            Some(label) => if label==node_label { range } else { range-1 }
+        };
+
+        if let Some(ref mut value) = nodes[link as usize].value {
+
+            if tail_len <= new_range {
+
+                callback(value);
+            }
+        }
+
+        {
+            let mut new_tail = key_tail.clone();
+            let new_label = new_tail.next();
+
+            let new_len = if tail_len > 0 { tail_len-1 } else { tail_len };
+
+            visit_neighbor_values_r_mut(nodes, middle, new_label, &mut new_tail, new_len, new_range, callback);
+        }
+
+        visit_neighbor_values_r_mut(nodes, right, label, key_tail, tail_len, range, callback);
+    }
+}
+
+
+fn visit_crossword_values_r<T, C>(nodes: &[ArenaNode<T>], link: u32, label: char, key_tail: &mut Chars, joker: char, callback: &mut C)
+    where C: FnMut (&T) {
+
+    if link == NIL {
+
+        return;
+    }
+
+    let node = &nodes[link as usize];
+
+    if label == joker || label < node.label {
+
+        visit_crossword_values_r(nodes, node.left, label, key_tail, joker, callback);
+    }
+
+    if label == joker || label == node.label {
+
+        let mut new_tail = key_tail.clone();
+        let new_label = new_tail.next();
+
+        match new_label {
+
+            None =>  if let Some(ref value) = node.value {
+
+                callback(value);
+            },
+
+            Some(label) => visit_crossword_values_r(nodes, node.middle, label, &mut new_tail, joker, callback)
+        }
+    }
+
+    if label == joker || label > node.label {
+
+        visit_crossword_values_r(nodes, node.right, label, key_tail, joker, callback);
+    }
+}
+
+
+fn visit_crossword_values_r_mut<T, C>(nodes: &mut [ArenaNode<T>], link: u32, label: char, key_tail: &mut Chars, joker: char, callback: &mut C)
+    where C: FnMut (&mut T) {
+
+    if link == NIL {
+
+        return;
+    }
+
+    let (node_label, left, middle, right) = {
+
+        let node = &nodes[link as usize];
+        (node.label, node.left, node.middle, node.right)
+    };
+
+    if label == joker || label < node_label {
+
+        visit_crossword_values_r_mut(nodes, left, label, key_tail, joker, callback);
+    }
+
+    if label == joker || label == node_label {
+
+        let mut new_tail = key_tail.clone();
+        let new_label = new_tail.next();
+
+        match new_label {
+
+            None =>  if let Some(ref mut value) = nodes[link as usize].value {
+
+                callback(value);
+            },
+
+            Some(label) => visit_crossword_values_r_mut(nodes, middle, label, &mut new_tail, joker, callback)
+        }
+    }
+
+    if label == joker || label > node_label {
+
+        visit_crossword_values_r_mut(nodes, right, label, key_tail, joker, callback);
+    }
+}
+
+
+fn pretty_print_r<T>(nodes: &[ArenaNode<T>], link: u32, writer: &mut dyn Write) {
+
+    if link == NIL {
+
+        return;
+    }
+
+    let node = &nodes[link as usize];
+
+    let value_box = match node.value {
+
+        None => "☐", Some(_) => "☑"
+    };
+
+    let _ = writeln!(writer, r#"N{} [label=<<TABLE BORDER="0" CELLBORDER="1" CELLSPACING="0"><TR><TD COLSPAN="3">{} {}</TD></TR><TR><TD PORT="l"></TD><TD PORT="m"></TD><TD PORT="r"></TD></TR></TABLE>>]"#, link, value_box, node.label);
+
+    let mut print_edge = |child, start, style| if child != NIL {
+
+        let _ = writeln!(writer, r#"N{}:{} -> N{} [style={}]"#, link, start, child, style);
+    };
+
+    print_edge(node.left, "l", "solid");
+    print_edge(node.middle, "m", "bold");
+    print_edge(node.right, "r", "solid");
+
+    pretty_print_r(nodes, node.left, writer);
+    pretty_print_r(nodes, node.middle, writer);
+    pretty_print_r(nodes, node.right, writer);
+}
+
+
+impl<T> Default for ArenaTst<T> {
+
+    fn default() -> Self {
+
+        ArenaTst::new()
+    }
+}
+
+
+impl<T> ArenaTst<T> {
+
+    /// Create a new, empty `ArenaTst`. No memory is allocated until the first insertion.
+
+    pub fn new() -> Self {
+
+        ArenaTst { arena: Arena { nodes: Vec::new(), free: NIL }, root: NIL, count: 0 }
+    }
+
+
+    /// Create a new, empty `ArenaTst` with room for at least `capacity` nodes before any reallocation. Note that a
+    /// key needs up to one node per character.
+
+    pub fn with_capacity(capacity: usize) -> Self {
+
+        ArenaTst { arena: Arena { nodes: Vec::with_capacity(capacity), free: NIL }, root: NIL, count: 0 }
+    }
+
+
+    /// Inserts `key` and `value` pair in the tree, returning any value previously associated with `key`. An empty
+    /// `key` is meaningless, and `value` is given back. See [`Tst::insert`]( ./struct.Tst.html#method.insert).
+
+    pub fn insert(&mut self, key: &str, value: T) -> Option<T> {
+
+        let mut key_tail = key.chars();
+
+        match key_tail.next() {
+
+            None => Some(value),
+
+            Some(label) => {
+
+                let (root, old_value) = insert_r(&mut self.arena, self.root, label, key_tail, value);
+
+                self.root = root;
+
+                if old_value.is_none() {
+
+                    self.count += 1;
+                }
+
+                old_value
+            }
+        }
+    }
+
+
+    /// Returns an immutable reference to the value associated with `key`, or None.
+
+    pub fn get(&self, key: &str) -> Option<&T> {
+
+        let mut key_tail = key.chars();
+
+        match key_tail.next() {
+
+            None => None,
+
+            Some(label) => get_r(&self.arena.nodes, self.root, label, &mut key_tail)
+        }
+    }
+
+
+    /// Returns an mutable reference to the value associated with `key`, or `None`.
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut T> {
+
+        let mut key_tail = key.chars();
+
+        match key_tail.next() {
+
+            None => None,
+
+            Some(label) => get_r_mut(&mut self.arena.nodes, self.root, label, &mut key_tail)
+        }
+    }
+
+
+    /// Removes the value associated with `key` from the tree, and returns it. Does nothing if no value is
+    /// associated with `key`, and returns `None`. Slots of pruned nodes are reused by later insertions.
+    ///
+    /// ```
+    /// # use ternary_tree::ArenaTst;
+    /// let mut map = ArenaTst::new();
+    ///
+    /// map.insert("foo", "🍄🍄");
+    /// let nodes = map.stat().bytes.total;
+    ///
+    /// assert_eq!(map.remove("foo"), Some("🍄🍄"));
+    /// map.insert("bar", "🐟");
+    ///
+    /// assert_eq!(map.stat().bytes.total, nodes);
+    /// ```
+
+    pub fn remove(&mut self, key: &str) -> Option<T> {
+
+        let mut key_tail = key.chars();
+
+        let (prune, old_value) = match key_tail.next() {
+
+            None => (false, None),
+
+            Some(label) => remove_r(&mut self.arena, self.root, label, &mut key_tail)
+        };
+
+        if prune {
+
+            self.arena.release(self.root);
+            self.root = NIL;
+        }
+
+        if old_value.is_some() {
+
+            self.count -= 1;
+        }
+
+        old_value
+    }
+
+
+    /// Returns the number of values stored in the tree.
+
+    pub fn len(&self) -> usize {
+
+        self.count
+    }
+
+
+    /// Returns `true` if the tree stores no value.
+
+    pub fn is_empty(&self) -> bool {
+
+        self.len() == 0
+    }
+
+
+    /// Walks the tree, gathers various metrics about nodes, keys and values, and returns a [`Stats`](
+    /// ./struct.Stats.html) structure to sum it up. Unlike [`Tst::stat`]( ./struct.Tst.html#method.stat),
+    /// `bytes.total` accounts for the whole node storage, including free slots and spare capacity, which may
+    /// exceed `count.nodes` * `bytes.node`.
+    ///
+    /// ```
+    /// # use ternary_tree::ArenaTst;
+    /// let mut map = ArenaTst::with_capacity(8);
+    ///
+    /// map.insert("foo", "🍄🍄");
+    ///
+    /// let stats = map.stat();
+    /// assert_eq!(stats.count.nodes, 3);
+    /// assert_eq!(stats.bytes.total >= 8 * stats.bytes.node, true);
+    /// ```
+
+    pub fn stat(&self) -> Stats {
+
+        let empty_stats: Stats = Default::default();
+
+        let mut stats = stat_r(empty_stats, &self.arena.nodes, self.root, 0, 0, 0);
+
+        stats.bytes.node = mem::size_of::<ArenaNode<T>>();
+        stats.bytes.total = mem::size_of::<ArenaTst<T>>()+self.arena.nodes.capacity()*stats.bytes.node;
+
+        stats
+    }
+
+
+    /// Deletes every node and value stored in the tree. The node storage is kept for later insertions.
+
+    pub fn clear(&mut self) {
+
+        self.arena.nodes.clear();
+        self.arena.free = NIL;
+        self.root = NIL;
+        self.count = 0;
+    }
+
+
+    /// Recursively walks the tree and calls `callback` closure on each immutable value. See [`Tst::visit_values`](
+    /// ./struct.Tst.html#method.visit_values).
+
+    pub fn visit_values<C>(&self, mut callback: C)
+    where C: FnMut (&T) {
+
+        visit_values_r(&self.arena.nodes, self.root, &mut callback);
+    }
+
+
+    /// Recursively walks the tree and calls `callback` closure on each mutable value. See
+    /// [`Tst::visit_values_mut`]( ./struct.Tst.html#method.visit_values_mut).
+
+    pub fn visit_values_mut<C>(&mut self, mut callback: C)
+    where C: FnMut (&mut T) {
+
+        visit_values_r_mut(&mut self.arena.nodes, self.root, &mut callback);
+    }
+
+
+    /// Recursively walks the tree and calls `callback` closure on each immutable value whose key begins with
+    /// `key_prefix`. See [`Tst::visit_complete_values`]( ./struct.Tst.html#method.visit_complete_values).
+
+    pub fn visit_complete_values<C>(&self, key_prefix: &str, mut callback: C)
+    where C: FnMut (&T) {
+
+        let mut prefix_tail = key_prefix.chars();
+
+        match prefix_tail.next() {
+
+            None => visit_values_r(&self.arena.nodes, self.root, &mut callback),
+
+            Some(label) => {
+
+                let new_root = find_complete_root_r(&self.arena.nodes, self.root, label, prefix_tail);
+                visit_values_r(&self.arena.nodes, new_root, &mut callback)
+            }
+        }
+    }
+
+
+    /// Recursively walks the tree and calls `callback` closure on each mutable value whose key begins with
+    /// `key_prefix`. See [`Tst::visit_complete_values_mut`]( ./struct.Tst.html#method.visit_complete_values_mut).
+
+    pub fn visit_complete_values_mut<C>(&mut self, key_prefix: &str, mut callback: C)
+    where C: FnMut (&mut T) {
+
+        let mut prefix_tail = key_prefix.chars();
+
+        match prefix_tail.next() {
+
+            None => visit_values_r_mut(&mut self.arena.nodes, self.root, &mut callback),
+
+            Some(label) => {
+
+                let new_root = find_complete_root_r(&self.arena.nodes, self.root, label, prefix_tail);
+                visit_values_r_mut(&mut self.arena.nodes, new_root, &mut callback)
+            }
+        }
+    }
+
+
+    /// Recursively walks the tree and calls `callback` closure on each immutable value whose key is _close_ to
+    /// `key` (Hamming distance of `range`). See [`Tst::visit_neighbor_values`](
+    /// ./struct.Tst.html#method.visit_neighbor_values).
+
+    pub fn visit_neighbor_values<C>(&self, key: &str, range: usize, mut callback: C)
+    where C: FnMut (&T) {
+
+        let mut key_tail = key.chars();
+        let key_len = key.chars().count();
+        let label = key_tail.next();
+        let tail_len = if key_len == 0 { 0 } else { key_len-1 };
+
+        visit_neighbor_values_r(&self.arena.nodes, self.root, label, &mut key_tail, tail_len, range, &mut callback);
+    }
+
+
+    /// Recursively walks the tree and calls `callback` closure on each mutable value whose key is _close_ to `key`
+    /// (Hamming distance of `range`). See [`Tst::visit_neighbor_values_mut`](
+    /// ./struct.Tst.html#method.visit_neighbor_values_mut).
+
+    pub fn visit_neighbor_values_mut<C>(&mut self, key: &str, range: usize, mut callback: C)
+    where C: FnMut (&mut T) {
+
+        let mut key_tail = key.chars();
+        let key_len = key.chars().count();
+        let label = key_tail.next();
+        let tail_len = if key_len == 0 { 0 } else { key_len-1 };
+
+        visit_neighbor_values_r_mut(&mut self.arena.nodes, self.root, label, &mut key_tail, tail_len, range, &mut callback);
+    }
+
+
+    /// Recursively walks the tree and calls `callback` closure on each immutable value whose key _matches_
+    /// `pattern` with `joker` characters. See [`Tst::visit_crossword_values`](
+    /// ./struct.Tst.html#method.visit_crossword_values).
+
+    pub fn visit_crossword_values<C>(&self, pattern: &str, joker: char, mut callback: C)
+    where C: FnMut (&T) {
+
+        let mut pattern_tail = pattern.chars();
+
+        if let Some(label) = pattern_tail.next() {
+
+            visit_crossword_values_r(&self.arena.nodes, self.root, label, &mut pattern_tail, joker, &mut callback);
+        }
+    }
+
+
+    /// Recursively walks the tree and calls `callback` closure on each mutable value whose key _matches_ `pattern`
+    /// with `joker` characters. See [`Tst::visit_crossword_values_mut`](
+    /// ./struct.Tst.html#method.visit_crossword_values_mut).
+
+    pub fn visit_crossword_values_mut<C>(&mut self, pattern: &str, joker: char, mut callback: C)
+    where C: FnMut (&mut T) {
+
+        let mut pattern_tail = pattern.chars();
+
+        if let Some(label) = pattern_tail.next() {
+
+            visit_crossword_values_r_mut(&mut self.arena.nodes, self.root, label, &mut pattern_tail, joker, &mut callback);
+        }
+    }
+
+
+    /// Dump the tree in `writer` using the _dot_ language of [Graphviz]( http://www.graphviz.org) tools. Nodes are
+    /// named after their index in the arena. See [`Tst::pretty_print`]( ./struct.Tst.html#method.pretty_print).
+
+    pub fn pretty_print(&self, writer: &mut dyn Write) {
+
+        let _ = writeln!(writer, "digraph {{");
+        let _ = writeln!(writer, "node [shape=plaintext]");
+
+        pretty_print_r(&self.arena.nodes, self.root, writer);
+
+        let _ = writeln!(writer, "}}");
+    }
+
+
+    /// Create a double-ended iterator which successively returns all values of the tree, in alphabetical order of
+    /// keys. See [`Tst::iter`]( ./struct.Tst.html#method.iter).
+    ///
+    /// ```
+    /// # use ternary_tree::ArenaTst;
+    /// let mut map = ArenaTst::new();
+    /// map.insert("foo", "🍄🍄");
+    /// map.insert("bar", "🐟");
+    /// map.insert("baz", "㵅");
+    ///
+    /// let mut it = map.iter();
+    ///
+    /// let first_value = it.next();
+    /// let last_value = it.next_back();
+    ///
+    /// assert_eq!((it.current_key(), first_value), ("bar".to_string(), Some(&"🐟")));
+    /// assert_eq!((it.current_key_back(), last_value), ("foo".to_string(), Some(&"🍄🍄")));
+    /// ```
+
+    pub fn iter(&self) -> ArenaTstIterator<'_, T> {
+
+        ArenaTstIterator::<T>::new(self)
+    }
+
+
+    /// Create a double-ended iterator which successively returns all values whose key begins with `prefix`. See
+    /// [`Tst::iter_complete`]( ./struct.Tst.html#method.iter_complete).
+
+    pub fn iter_complete(&self, prefix: &str) -> ArenaTstCompleteIterator<'_, T> {
+
+        ArenaTstCompleteIterator::<T>::new(self, prefix)
+    }
+
+
+    /// Create a double-ended iterator which successively returns all values whose key is _close_ to `key`. See
+    /// [`Tst::iter_neighbor`]( ./struct.Tst.html#method.iter_neighbor).
+
+    pub fn iter_neighbor<'a, 'b>(&'a self, key: &'b str, range: usize) -> ArenaTstNeighborIterator<'a, 'b, T> {
+
+        ArenaTstNeighborIterator::<T>::new(self, key, range)
+    }
+
+
+    /// Create a double-ended iterator which successively returns all values whose key _matches_ `pattern`. See
+    /// [`Tst::iter_crossword`]( ./struct.Tst.html#method.iter_crossword).
+
+    pub fn iter_crossword<'a, 'b>(&'a self, pattern: &'b str, joker: char) -> ArenaTstCrosswordIterator<'a, 'b, T> {
+
+        ArenaTstCrosswordIterator::<T>::new(self, pattern, joker)
+    }
+}
+
+
+// A node of the arena, as seen by the walkers shared with the other trees.
+
+struct ArenaRef<'a, T: 'a> {
+
+    nodes: &'a [ArenaNode<T>],
+    index: u32
+}
+
+
+impl<'a, T> Clone for ArenaRef<'a, T> {
+
+    fn clone(&self) -> Self {
+
+        *self
+    }
+}
+
+
+impl<'a, T> Copy for ArenaRef<'a, T> {}
+
+
+impl<'a, T> fmt::Debug for ArenaRef<'a, T> {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+
+        self.node().fmt(f)
+    }
+}
+
+
+impl<'a, T> ArenaRef<'a, T> {
+
+    fn new(nodes: &'a [ArenaNode<T>], index: u32) -> Option<Self> {
+
+        if index == NIL { None } else { Some(ArenaRef { nodes, index }) }
+    }
+
+
+    fn node(&self) -> &'a ArenaNode<T> {
+
+        &self.nodes[self.index as usize]
+    }
+}
+
+
+impl<'a, T> NodeRef for ArenaRef<'a, T> {
+
+    type Value = &'a T;
+
+    fn label(&self) -> char {
+
+        self.node().label
+    }
+
+
+    fn run(&self) -> &str {
+
+        ""
+    }
+
+
+    fn value(&self) -> Option<&'a T> {
+
+        self.node().value.as_ref()
+    }
+
+
+    fn left(&self) -> Option<Self> {
+
+        ArenaRef::new(self.nodes, self.node().left)
+    }
+
+
+    fn middle(&self) -> Option<Self> {
+
+        ArenaRef::new(self.nodes, self.node().middle)
+    }
+
+
+    fn right(&self) -> Option<Self> {
+
+        ArenaRef::new(self.nodes, self.node().right)
+    }
+
+
+    fn is(&self, other: &Self) -> bool {
+
+        self.index == other.index
+    }
+}
+
+
+/// A double-ended iterator which successively returns all values of an [`ArenaTst`]( ./struct.ArenaTst.html).
+/// See [`iter`]( struct.ArenaTst.html#method.iter) method.
+
+#[derive(Debug)]
+pub struct ArenaTstIterator<'a, T: 'a> {
+
+    it: Walker<ArenaRef<'a, T>>
+}
+
+
+impl<'a, T> ArenaTstIterator<'a, T> {
+
+    pub fn new(tst: &'a ArenaTst<T>) -> Self {
+
+        ArenaTstIterator { it: Walker::new(ArenaRef::new(&tst.arena.nodes, tst.root), true) }
+    }
+
+
+    pub fn current_key(&self) -> String {
+
+        self.it.current_key()
+    }
+
+
+    pub fn current_key_back(&self) -> String {
+
+        self.it.current_key_back()
+    }
+}
+
+
+impl<'a, T> Iterator for ArenaTstIterator<'a, T> {
+
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+
+        self.it.next()
+    }
+}
+
+
+impl<'a, T> IntoIterator for &'a ArenaTst<T> {
+
+    type Item = &'a T;
+    type IntoIter = ArenaTstIterator<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+
+        self.iter()
+    }
+}
+
+
+impl<'a, T> DoubleEndedIterator for ArenaTstIterator<'a, T> {
+
+    fn next_back(&mut self) -> Option<&'a T> {
+
+        self.it.next_back()
+    }
+}
+
+
+/// A double-ended iterator which successively returns all values of an [`ArenaTst`]( ./struct.ArenaTst.html)
+/// whose key begins with `prefix`. See [`iter_complete`]( struct.ArenaTst.html#method.iter_complete) method.
+
+#[derive(Debug)]
+pub struct ArenaTstCompleteIterator<'a, T: 'a> {
+
+    it: CompleteWalker<ArenaRef<'a, T>>
+}
+
+
+impl<'a, T> ArenaTstCompleteIterator<'a, T> {
+
+    pub fn new(tst: &'a ArenaTst<T>, key_prefix: &str) -> Self {
+
+        ArenaTstCompleteIterator { it: CompleteWalker::new(ArenaRef::new(&tst.arena.nodes, tst.root), key_prefix) }
+    }
+
+
+    pub fn current_key(&self) -> String {
+
+        self.it.current_key()
+    }
+
+
+    pub fn current_key_back(&self) -> String {
+
+        self.it.current_key_back()
+    }
+}
+
+
+impl<'a, T> Iterator for ArenaTstCompleteIterator<'a, T> {
+
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+
+        self.it.next()
+    }
+}
+
+
+impl<'a, T> DoubleEndedIterator for ArenaTstCompleteIterator<'a, T> {
+
+    fn next_back(&mut self) -> Option<&'a T> {
+
+        self.it.next_back()
+    }
+}
+
+
+/// A double-ended iterator which successively returns all values of an [`ArenaTst`]( ./struct.ArenaTst.html)
+/// whose key is _close_ to `key`. See [`iter_neighbor`]( struct.ArenaTst.html#method.iter_neighbor) method.
+
+#[derive(Debug)]
+pub struct ArenaTstNeighborIterator<'a, 'b, T: 'a> {
+
+    it: NeighborWalker<'b, ArenaRef<'a, T>>
+}
+
+
+impl<'a, 'b, T> ArenaTstNeighborIterator<'a, 'b, T> {
+
+    pub fn new(tst: &'a ArenaTst<T>, key: &'b str, range: usize) -> Self {
+
+        ArenaTstNeighborIterator { it: NeighborWalker::new(ArenaRef::new(&tst.arena.nodes, tst.root), key, range) }
+    }
+
+
+    pub fn current_key(&self) -> String {
+
+        self.it.current_key()
+    }
+
+
+    pub fn current_key_back(&self) -> String {
+
+        self.it.current_key_back()
+    }
+}
+
+
+impl<'a, 'b, T> Iterator for ArenaTstNeighborIterator<'a, 'b, T> {
+
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+
+        self.it.next()
+    }
+}
+
+
+impl<'a, 'b, T> DoubleEndedIterator for ArenaTstNeighborIterator<'a, 'b, T> {
+
+    fn next_back(&mut self) -> Option<&'a T> {
+
+        self.it.next_back()
+    }
+}
+
+
+/// A double-ended iterator which successively returns all values of an [`ArenaTst`]( ./struct.ArenaTst.html)
+/// whose key _matches_ `pattern`. See [`iter_crossword`]( struct.ArenaTst.html#method.iter_crossword) method.
+
+#[derive(Debug)]
+pub struct ArenaTstCrosswordIterator<'a, 'b, T: 'a> {
+
+    it: CrosswordWalker<'b, ArenaRef<'a, T>>
+}
+
+
+impl<'a, 'b, T> ArenaTstCrosswordIterator<'a, 'b, T> {
+
+    pub fn new(tst: &'a ArenaTst<T>, key: &'b str, joker: char) -> Self {
+
+        ArenaTstCrosswordIterator { it: CrosswordWalker::new(ArenaRef::new(&tst.arena.nodes, tst.root), key, joker) }
+    }
+
+
+    pub fn current_key(&self) -> String {
+
+        self.it.current_key()
+    }
+
+
+    pub fn current_key_back(&self) -> String {
+
+        self.it.current_key_back()
+    }
+}
+
+
+impl<'a, 'b, T> Iterator for ArenaTstCrosswordIterator<'a, 'b, T> {
+
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+
+        self.it.next()
+    }
+}
+
+
+impl<'a, 'b, T> DoubleEndedIterator for ArenaTstCrosswordIterator<'a, 'b, T> {
+
+    fn next_back(&mut self) -> Option<&'a T> {
+
+        self.it.next_back()
+    }
+}
@@ -0,0 +1,604 @@
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::str;
+
+use super::Tst;
+use super::Link;
+use super::Node;
+use super::update_height;
+use super::update_count;
+
+
+/// A `ValueCodec` turns a value into bytes and back, so that a tree can be saved with [`write_to`](
+/// ./struct.Tst.html#method.write_to) and loaded with [`read_from`]( ./struct.Tst.html#method.read_from).
+///
+/// Each encoded value is stored with its length, so `decode` is always given the exact bytes produced by `encode`.
+/// The crate implements this trait for strings, booleans, chars, integers, floats, byte vectors and the unit type.
+///
+/// ```
+/// # use ternary_tree::ValueCodec;
+/// # use std::io;
+/// struct Point { x: i32, y: i32 }
+///
+/// impl ValueCodec for Point {
+///
+///     fn encode(&self, buffer: &mut Vec<u8>) {
+///
+///         self.x.encode(buffer);
+///         self.y.encode(buffer);
+///     }
+///
+///     fn decode(bytes: &[u8]) -> io::Result<Self> {
+///
+///         if bytes.len() != 8 {
+///
+///             return Err(io::Error::new(io::ErrorKind::InvalidData, "bad point"));
+///         }
+///
+///         Ok(Point { x: i32::decode(&bytes[..4])?, y: i32::decode(&bytes[4..])? })
+///     }
+/// }
+/// ```
+
+pub trait ValueCodec: Sized {
+
+    /// Appends the bytes of the value to `buffer`.
+
+    fn encode(&self, buffer: &mut Vec<u8>);
+
+    /// Builds a value back from the bytes produced by `encode`.
+
+    fn decode(bytes: &[u8]) -> io::Result<Self>;
+}
+
+
+fn invalid_data(message: &str) -> io::Error {
+
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+
+macro_rules! impl_value_codec_for_number {
+
+    ($($t:ty),*) => {
+
+        $(
+            impl ValueCodec for $t {
+
+                fn encode(&self, buffer: &mut Vec<u8>) {
+
+                    buffer.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn decode(bytes: &[u8]) -> io::Result<Self> {
+
+                    let mut array = [0u8; std::mem::size_of::<$t>()];
+
+                    if bytes.len() != array.len() {
+
+                        return Err(invalid_data(concat!("bad length for ", stringify!($t), " value")));
+                    }
+
+                    array.copy_from_slice(bytes);
+
+                    Ok(<$t>::from_le_bytes(array))
+                }
+            }
+        )*
+    }
+}
+
+
+impl_value_codec_for_number!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+
+// usize and isize are stored on 64 bits, so that a file does not depend on the platform which wrote it.
+
+impl ValueCodec for usize {
+
+    fn encode(&self, buffer: &mut Vec<u8>) {
+
+        (*self as u64).encode(buffer);
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<Self> {
+
+        let value = u64::decode(bytes)?;
+
+        if value > usize::MAX as u64 {
+
+            return Err(invalid_data("usize value out of range"));
+        }
+
+        Ok(value as usize)
+    }
+}
+
+
+impl ValueCodec for isize {
+
+    fn encode(&self, buffer: &mut Vec<u8>) {
+
+        (*self as i64).encode(buffer);
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<Self> {
+
+        let value = i64::decode(bytes)?;
+
+        if value > isize::MAX as i64 || value < isize::MIN as i64 {
+
+            return Err(invalid_data("isize value out of range"));
+        }
+
+        Ok(value as isize)
+    }
+}
+
+
+impl ValueCodec for bool {
+
+    fn encode(&self, buffer: &mut Vec<u8>) {
+
+        buffer.push(*self as u8);
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<Self> {
+
+        match bytes {
+
+            [0] => Ok(false),
+            [1] => Ok(true),
+            _ => Err(invalid_data("bad bool value"))
+        }
+    }
+}
+
+
+impl ValueCodec for char {
+
+    fn encode(&self, buffer: &mut Vec<u8>) {
+
+        (*self as u32).encode(buffer);
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<Self> {
+
+        std::char::from_u32(u32::decode(bytes)?).ok_or_else(|| invalid_data("bad char value"))
+    }
+}
+
+
+impl ValueCodec for () {
+
+    fn encode(&self, _buffer: &mut Vec<u8>) {
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<Self> {
+
+        if !bytes.is_empty() {
+
+            return Err(invalid_data("bad unit value"));
+        }
+
+        Ok(())
+    }
+}
+
+
+impl ValueCodec for String {
+
+    fn encode(&self, buffer: &mut Vec<u8>) {
+
+        buffer.extend_from_slice(self.as_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<Self> {
+
+        match str::from_utf8(bytes) {
+
+            Ok(s) => Ok(s.to_string()),
+            Err(_) => Err(invalid_data("bad utf-8 in string value"))
+        }
+    }
+}
+
+
+impl ValueCodec for Vec<u8> {
+
+    fn encode(&self, buffer: &mut Vec<u8>) {
+
+        buffer.extend_from_slice(self);
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<Self> {
+
+        Ok(bytes.to_vec())
+    }
+}
+
+
+// A file starts with a fixed size header, followed by a body made of three sections:
+//
+// * the node table, with one fixed size record per node, in depth-first order (node, left, middle, right)
+// * the runs of compressed nodes, as utf-8 bytes
+// * the values, each one being its encoded length (u32) followed by its encoded bytes
+//
+// All integers are little endian u32. A node record holds its label, the indices of its left, middle and right
+// children, the offset and length of its run, and the offset of its value (or NIL for links and values which are
+// not there). The checksum is computed over the whole body.
+
+pub const MAGIC: &[u8; 4] = b"TST\x01";
+pub const VERSION: u16 = 1;
+pub const FLAG_BALANCED: u16 = 1;
+pub const HEADER_LEN: usize = 28;
+pub const NODE_LEN: usize = 28;
+pub const NIL: u32 = u32::MAX;
+
+
+pub struct Header {
+
+    pub flags: u16,
+    pub nodes: u32,
+    pub values: u32,
+    pub runs_len: u32,
+    pub values_len: u32,
+    pub checksum: u32
+}
+
+
+// FNV-1a, 32 bits: cheap, and good enough to catch a truncated or damaged file.
+
+pub fn checksum(bytes: &[u8]) -> u32 {
+
+    let mut hash: u32 = 0x811c_9dc5;
+
+    for b in bytes {
+
+        hash ^= *b as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+
+    hash
+}
+
+
+pub fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+
+    bytes.get(offset..offset.checked_add(4)?).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+
+pub fn parse_header(bytes: &[u8]) -> io::Result<Header> {
+
+    if bytes.len() < HEADER_LEN {
+
+        return Err(invalid_data("truncated header"));
+    }
+
+    if &bytes[0..4] != MAGIC {
+
+        return Err(invalid_data("not a ternary tree file"));
+    }
+
+    let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+
+    if version != VERSION {
+
+        return Err(invalid_data("unsupported ternary tree file version"));
+    }
+
+    let header = Header {
+
+        flags: u16::from_le_bytes([bytes[6], bytes[7]]),
+        nodes: read_u32(bytes, 8).unwrap(),
+        values: read_u32(bytes, 12).unwrap(),
+        runs_len: read_u32(bytes, 16).unwrap(),
+        values_len: read_u32(bytes, 20).unwrap(),
+        checksum: read_u32(bytes, 24).unwrap()
+    };
+
+    if header.flags & !FLAG_BALANCED != 0 {
+
+        return Err(invalid_data("unknown flags in ternary tree file"));
+    }
+
+    Ok(header)
+}
+
+
+impl Header {
+
+    // Length of the body, or None if it does not fit in memory.
+
+    pub fn body_len(&self) -> Option<usize> {
+
+        (self.nodes as usize).checked_mul(NODE_LEN)?
+            .checked_add(self.runs_len as usize)?
+            .checked_add(self.values_len as usize)
+    }
+}
+
+
+pub struct NodeRecord {
+
+    pub label: char,
+    pub left: u32,
+    pub middle: u32,
+    pub right: u32,
+    pub run: (usize, usize),
+    pub value: u32
+}
+
+
+// Reads and checks the record of node `index`. The runs and values sections are the ones of the body, and the
+// returned run bounds and value offset are known to be inside of them (the value length is checked by the caller).
+
+pub fn read_node(nodes: &[u8], runs: &[u8], index: u32) -> io::Result<NodeRecord> {
+
+    let offset = (index as usize).checked_mul(NODE_LEN).ok_or_else(|| invalid_data("bad node index"))?;
+
+    let field = |i: usize| read_u32(nodes, offset + i * 4).ok_or_else(|| invalid_data("bad node index"));
+
+    let label = std::char::from_u32(field(0)?).ok_or_else(|| invalid_data("bad node label"))?;
+
+    let run_offset = field(4)? as usize;
+    let run_len = field(5)? as usize;
+
+    let run_end = run_offset.checked_add(run_len).ok_or_else(|| invalid_data("bad node run"))?;
+
+    match runs.get(run_offset..run_end) {
+
+        Some(run) if str::from_utf8(run).is_ok() => (),
+        _ => return Err(invalid_data("bad node run"))
+    }
+
+    Ok(NodeRecord {
+
+        label,
+        left: field(1)?,
+        middle: field(2)?,
+        right: field(3)?,
+        run: (run_offset, run_end),
+        value: field(6)?
+    })
+}
+
+
+// Returns the encoded bytes of the value stored at `offset` in the values section.
+
+pub fn read_value(values: &[u8], offset: u32) -> io::Result<&[u8]> {
+
+    let offset = offset as usize;
+
+    let len = read_u32(values, offset).ok_or_else(|| invalid_data("bad value offset"))? as usize;
+
+    match (offset + 4).checked_add(len) {
+
+        Some(end) => values.get(offset + 4..end).ok_or_else(|| invalid_data("bad value length")),
+        None => Err(invalid_data("bad value length"))
+    }
+}
+
+
+// Splits a body into its three sections, once its length and checksum are known to match the header.
+
+pub fn split_body<'a>(header: &Header, body: &'a [u8]) -> io::Result<(&'a [u8], &'a [u8], &'a [u8])> {
+
+    if Some(body.len()) != header.body_len() {
+
+        return Err(invalid_data("truncated ternary tree file"));
+    }
+
+    if checksum(body) != header.checksum {
+
+        return Err(invalid_data("bad checksum in ternary tree file"));
+    }
+
+    let (nodes, rest) = body.split_at(header.nodes as usize * NODE_LEN);
+    let (runs, values) = rest.split_at(header.runs_len as usize);
+
+    Ok((nodes, runs, values))
+}
+
+
+fn write_r<T>(link: &Link<T>, body: &mut Vec<u8>, runs: &mut Vec<u8>, values: &mut Vec<u8>, next: &mut u32) -> u32
+where T: ValueCodec {
+
+    match *link {
+
+        None => NIL,
+
+        Some(ref node) => {
+
+            let index = *next;
+            *next += 1;
+
+            let offset = body.len();
+            body.extend_from_slice(&[0u8; NODE_LEN]);
+
+            let value = match node.value {
+
+                None => NIL,
+
+                Some(ref value) => {
+
+                    let value_offset = values.len();
+                    values.extend_from_slice(&[0u8; 4]);
+                    value.encode(values);
+
+                    let len = (values.len() - value_offset - 4) as u32;
+                    values[value_offset..value_offset + 4].copy_from_slice(&len.to_le_bytes());
+
+                    value_offset as u32
+                }
+            };
+
+            let run_offset = runs.len() as u32;
+            runs.extend_from_slice(node.run.as_bytes());
+
+            let left = write_r(&node.left, body, runs, values, next);
+            let middle = write_r(&node.middle, body, runs, values, next);
+            let right = write_r(&node.right, body, runs, values, next);
+
+            let fields = [node.label as u32, left, middle, right, run_offset, node.run.len() as u32, value];
+
+            for (i, field) in fields.iter().enumerate() {
+
+                body[offset + i * 4..offset + i * 4 + 4].copy_from_slice(&field.to_le_bytes());
+            }
+
+            index
+        }
+    }
+}
+
+
+pub fn write_to<T>(tst: &Tst<T>, writer: &mut dyn Write) -> io::Result<()> where T: ValueCodec {
+
+    let mut body = Vec::new();
+    let mut runs = Vec::new();
+    let mut values = Vec::new();
+    let mut next = 0;
+
+    write_r(&tst.root, &mut body, &mut runs, &mut values, &mut next);
+
+    if body.len() + runs.len() + values.len() > u32::MAX as usize {
+
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "tree too large for a ternary tree file"));
+    }
+
+    let (runs_len, values_len) = (runs.len() as u32, values.len() as u32);
+
+    body.extend_from_slice(&runs);
+    body.extend_from_slice(&values);
+
+    let header = Header {
+
+        flags: if tst.balanced { FLAG_BALANCED } else { 0 },
+        nodes: next,
+        values: tst.count as u32,
+        runs_len,
+        values_len,
+        checksum: checksum(&body)
+    };
+
+    let mut bytes = Vec::with_capacity(HEADER_LEN);
+
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&VERSION.to_le_bytes());
+    bytes.extend_from_slice(&header.flags.to_le_bytes());
+
+    for field in &[header.nodes, header.values, header.runs_len, header.values_len, header.checksum] {
+
+        bytes.extend_from_slice(&field.to_le_bytes());
+    }
+
+    writer.write_all(&bytes)?;
+    writer.write_all(&body)
+}
+
+
+// Nodes are rebuilt in the order they were written. Each child must be the very next node of this order, which
+// guarantees that every record is used once, and that a damaged file can not make the walk loop.
+
+pub fn check_next(index: u32, next: &mut u32, nodes: u32) -> io::Result<()> {
+
+    if index != *next || index >= nodes {
+
+        return Err(invalid_data("bad node layout in ternary tree file"));
+    }
+
+    *next += 1;
+
+    Ok(())
+}
+
+
+struct Sections<'a> {
+
+    nodes: &'a [u8],
+    runs: &'a [u8],
+    values: &'a [u8],
+    count: u32,
+    balanced: bool
+}
+
+
+fn read_r<T>(sections: &Sections, index: u32, next: &mut u32, count: &mut usize) -> io::Result<Link<T>>
+where T: ValueCodec {
+
+    if index == NIL {
+
+        return Ok(None);
+    }
+
+    check_next(index, next, sections.count)?;
+
+    let record = read_node(sections.nodes, sections.runs, index)?;
+
+    let value = if record.value == NIL {
+
+        None
+
+    } else {
+
+        *count += 1;
+
+        Some(T::decode(read_value(sections.values, record.value)?)?)
+    };
+
+    let mut node = Box::new(Node {
+
+        label: record.label,
+        run: str::from_utf8(&sections.runs[record.run.0..record.run.1]).unwrap().into(),
+        value,
+        ..Default::default()
+    });
+
+    node.left = read_r(sections, record.left, next, count)?;
+    node.middle = read_r(sections, record.middle, next, count)?;
+    node.right = read_r(sections, record.right, next, count)?;
+
+    if sections.balanced {
+
+        update_height(&mut node);
+    }
+
+    update_count(&mut node);
+
+    Ok(Some(node))
+}
+
+
+pub fn read_from<T>(reader: &mut dyn Read) -> io::Result<Tst<T>> where T: ValueCodec {
+
+    let mut bytes = [0u8; HEADER_LEN];
+
+    reader.read_exact(&mut bytes)?;
+
+    let header = parse_header(&bytes)?;
+
+    let body_len = header.body_len().ok_or_else(|| invalid_data("ternary tree file too large"))?;
+
+    let mut body = Vec::new();
+
+    reader.take(body_len as u64).read_to_end(&mut body)?;
+
+    let (nodes, runs, values) = split_body(&header, &body)?;
+
+    let sections = Sections { nodes, runs, values, count: header.nodes, balanced: header.flags & FLAG_BALANCED != 0 };
+
+    let mut next = 0;
+    let mut count = 0;
+
+    let root = read_r(&sections, if header.nodes == 0 { NIL } else { 0 }, &mut next, &mut count)?;
+
+    if next != header.nodes || count != header.values as usize {
+
+        return Err(invalid_data("bad node count in ternary tree file"));
+    }
+
+    Ok(Tst { root, count, balanced: sections.balanced })
+}
@@ -0,0 +1,244 @@
+use std::sync::RwLock;
+
+use super::Tst;
+
+
+/// A `ConcurrentTst` is a ternary tree which can be shared between threads, and updated through a shared
+/// reference. Keys are spread over several independent [`Tst`]( ./struct.Tst.html) shards according to their first
+/// character, each shard behind its own `RwLock`. A writer only locks the shard of its key, so that readers of other
+/// shards are not blocked, and searches only lock the shards which may hold a matching key.
+///
+/// ```
+/// # use ternary_tree::ConcurrentTst;
+/// # use std::sync::Arc;
+/// # use std::thread;
+/// let map = Arc::new(ConcurrentTst::new());
+///
+/// let writers: Vec<_> = ["foo", "bar", "baz"].iter().map(|key| {
+///
+///     let map = map.clone();
+///     thread::spawn(move || { map.insert(key, key.len()); })
+///
+/// }).collect();
+///
+/// for writer in writers {
+///
+///     writer.join().unwrap();
+/// }
+///
+/// assert_eq!(map.len(), 3);
+/// assert_eq!(map.get("bar"), Some(3));
+/// ```
+///
+/// Since keys with different first characters live in different shards, visits do not return values in the
+/// alphabetical order of keys (only values of a same shard are visited in order).
+///
+/// Callbacks given to `get_with` and to visits run while a shard is locked for reading. Such a callback must not
+/// update the same `ConcurrentTst` (with `insert`, `remove` or `clear`), which would wait for this very lock, and
+/// deadlock. Locks of a shard are poisoned when a thread panics while holding them (in a callback for instance),
+/// and every method which then tries to lock this shard panics too.
+
+pub struct ConcurrentTst<T> {
+
+    shards: Vec<RwLock<Tst<T>>>
+}
+
+
+const DEFAULT_SHARDS: usize = 16;
+
+
+impl<T> Default for ConcurrentTst<T> {
+
+    fn default() -> Self {
+
+        ConcurrentTst::new()
+    }
+}
+
+
+impl<T> ConcurrentTst<T> {
+
+    /// Create a new, empty `ConcurrentTst`, with a default number of shards.
+
+    pub fn new() -> Self {
+
+        ConcurrentTst::with_shards(DEFAULT_SHARDS)
+    }
+
+
+    /// Create a new, empty `ConcurrentTst` with `shards` shards (at least one). More shards means less contention
+    /// between threads, but more locks to take for searches which touch every shard.
+
+    pub fn with_shards(shards: usize) -> Self {
+
+        ConcurrentTst { shards: (0..shards.max(1)).map(|_| RwLock::new(Tst::new())).collect() }
+    }
+
+
+    // The shard which holds the keys starting with `label`.
+
+    fn shard(&self, label: char) -> &RwLock<Tst<T>> {
+
+        &self.shards[label as usize % self.shards.len()]
+    }
+
+
+    // The shard of `key`, if `key` is not empty.
+
+    fn key_shard(&self, key: &str) -> Option<&RwLock<Tst<T>>> {
+
+        key.chars().next().map(|label| self.shard(label))
+    }
+
+
+    /// Inserts `key` and `value` pair in the tree, returning any value previously associated with `key`. Only the
+    /// shard of `key` is locked for writing. An empty `key` is meaningless, and `value` is given back.
+
+    pub fn insert(&self, key: &str, value: T) -> Option<T> {
+
+        match self.key_shard(key) {
+
+            None => Some(value),
+
+            Some(shard) => shard.write().unwrap().insert(key, value)
+        }
+    }
+
+
+    /// Returns a clone of the value associated with `key`, or `None`.
+
+    pub fn get(&self, key: &str) -> Option<T> where T: Clone {
+
+        self.get_with(key, |value| value.clone())
+    }
+
+
+    /// Calls `callback` on the value associated with `key` while its shard is locked for reading, and returns the
+    /// result, or `None` if no value is associated with `key`. This avoids cloning large values.
+    ///
+    /// ```
+    /// # use ternary_tree::ConcurrentTst;
+    /// let map = ConcurrentTst::new();
+    /// map.insert("foo", vec![1, 2, 3]);
+    ///
+    /// assert_eq!(map.get_with("foo", |v| v.len()), Some(3));
+    /// assert_eq!(map.get_with("bar", |v| v.len()), None);
+    /// ```
+
+    pub fn get_with<R, F>(&self, key: &str, callback: F) -> Option<R> where F: FnOnce(&T) -> R {
+
+        let shard = self.key_shard(key)?.read().unwrap();
+
+        shard.get(key).map(callback)
+    }
+
+
+    /// Removes the value associated with `key` from the tree, and returns it. Does nothing if no value is
+    /// associated with `key`, and returns `None`. Only the shard of `key` is locked for writing.
+
+    pub fn remove(&self, key: &str) -> Option<T> {
+
+        self.key_shard(key)?.write().unwrap().remove(key)
+    }
+
+
+    /// Returns the number of values stored in the tree. Shards are counted one after the other, so the result may
+    /// miss concurrent updates.
+
+    pub fn len(&self) -> usize {
+
+        self.shards.iter().map(|shard| shard.read().unwrap().len()).sum()
+    }
+
+
+    /// Returns `true` if the tree stores no value. Like [`len`]( ./struct.ConcurrentTst.html#method.len), this may
+    /// miss concurrent updates.
+
+    pub fn is_empty(&self) -> bool {
+
+        self.shards.iter().all(|shard| shard.read().unwrap().is_empty())
+    }
+
+
+    /// Deletes every value stored in the tree, one shard after the other.
+
+    pub fn clear(&self) {
+
+        for shard in &self.shards {
+
+            shard.write().unwrap().clear();
+        }
+    }
+
+
+    /// Calls `callback` closure on each value, locking one shard at a time for reading. See
+    /// [`Tst::visit_values`]( ./struct.Tst.html#method.visit_values).
+
+    pub fn visit_values<C>(&self, mut callback: C)
+    where C: FnMut (&T) {
+
+        for shard in &self.shards {
+
+            shard.read().unwrap().visit_values(&mut callback);
+        }
+    }
+
+
+    /// Calls `callback` closure on each value whose key begins with `key_prefix`. Only the shard of the first
+    /// character of `key_prefix` is locked, or all shards one at a time if `key_prefix` is empty. See
+    /// [`Tst::visit_complete_values`]( ./struct.Tst.html#method.visit_complete_values).
+
+    pub fn visit_complete_values<C>(&self, key_prefix: &str, mut callback: C)
+    where C: FnMut (&T) {
+
+        match self.key_shard(key_prefix) {
+
+            None => self.visit_values(callback),
+
+            Some(shard) => shard.read().unwrap().visit_complete_values(key_prefix, &mut callback)
+        }
+    }
+
+
+    /// Calls `callback` closure on each value whose key is _close_ to `key` (Hamming distance of `range`). With a
+    /// `range` of 0, only the shard of `key` is locked, otherwise all shards are, one at a time. See
+    /// [`Tst::visit_neighbor_values`]( ./struct.Tst.html#method.visit_neighbor_values).
+
+    pub fn visit_neighbor_values<C>(&self, key: &str, range: usize, mut callback: C)
+    where C: FnMut (&T) {
+
+        match self.key_shard(key) {
+
+            Some(shard) if range == 0 => shard.read().unwrap().visit_neighbor_values(key, range, &mut callback),
+
+            _ => for shard in &self.shards {
+
+                shard.read().unwrap().visit_neighbor_values(key, range, &mut callback);
+            }
+        }
+    }
+
+
+    /// Calls `callback` closure on each value whose key _matches_ `pattern` with `joker` characters. Only the shard
+    /// of the first character of `pattern` is locked, unless this character is `joker`. See
+    /// [`Tst::visit_crossword_values`]( ./struct.Tst.html#method.visit_crossword_values).
+
+    pub fn visit_crossword_values<C>(&self, pattern: &str, joker: char, mut callback: C)
+    where C: FnMut (&T) {
+
+        match pattern.chars().next() {
+
+            None => (),
+
+            Some(label) if label != joker => {
+
+                self.shard(label).read().unwrap().visit_crossword_values(pattern, joker, &mut callback)
+            }
+
+            _ => for shard in &self.shards {
+
+                shard.read().unwrap().visit_crossword_values(pattern, joker, &mut callback);
+            }
+        }
+    }
+}
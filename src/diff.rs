@@ -0,0 +1,281 @@
+use std::cmp::Ordering::Less;
+use std::cmp::Ordering::Equal;
+use std::cmp::Ordering::Greater;
+
+use super::Tst;
+use super::Node;
+use super::Link;
+
+
+/// A difference between two trees, as returned by [`TstDiffIterator`]( ./struct.TstDiffIterator.html). The first
+/// tree is the old one, and the second tree the new one.
+
+#[derive(Debug, PartialEq)]
+pub enum TstDiff<'a, T: 'a> {
+
+    /// The key is only in the new tree.
+    Added(String, &'a T),
+
+    /// The key is only in the old tree.
+    Removed(String, &'a T),
+
+    /// The key is in both trees, with different values: the old value comes first.
+    Changed(String, &'a T, &'a T)
+}
+
+
+// A place in a tree, below the current key: either a group of siblings (the next character of the key is not known
+// yet), or somewhere in the run of a node, with the characters of the run still to follow (when none is left, the
+// next place is the value of the node, then its middle child).
+
+#[derive(Debug)]
+enum Cursor<'a, T: 'a> {
+
+    Level(&'a Link<T>),
+    Run(&'a Node<T>, &'a str)
+}
+
+
+impl<'a, T> Clone for Cursor<'a, T> {
+
+    fn clone(&self) -> Self {
+
+        *self
+    }
+}
+
+
+impl<'a, T> Copy for Cursor<'a, T> {}
+
+
+impl<'a, T> Cursor<'a, T> {
+
+    fn is_empty(&self) -> bool {
+
+        match *self {
+
+            Cursor::Level(link) => link.is_none(),
+
+            Cursor::Run(..) => false
+        }
+    }
+
+
+    fn value(&self) -> Option<&'a T> {
+
+        match *self {
+
+            Cursor::Run(node, "") => node.value.as_ref(),
+
+            _ => None
+        }
+    }
+
+
+    // The places reached from this one by the next character of the key, in alphabetical order.
+
+    fn branches(&self, branches: &mut Vec<(char, Cursor<'a, T>)>) {
+
+        match *self {
+
+            Cursor::Level(&None) => (),
+
+            Cursor::Level(Some(node)) => {
+
+                Cursor::Level(&node.left).branches(branches);
+                branches.push((node.label, Cursor::Run(node, &node.run)));
+                Cursor::Level(&node.right).branches(branches);
+            }
+
+            Cursor::Run(node, "") => Cursor::Level(&node.middle).branches(branches),
+
+            Cursor::Run(node, run) => {
+
+                if let Some(c) = run.chars().next() {
+
+                    branches.push((c, Cursor::Run(node, &run[c.len_utf8()..])));
+                }
+            }
+        }
+    }
+}
+
+
+#[derive(Debug)]
+enum DiffTask<'a, T: 'a> {
+
+    Both(Cursor<'a, T>, Cursor<'a, T>),
+    Old(Cursor<'a, T>),
+    New(Cursor<'a, T>)
+}
+
+
+/// An iterator which successively returns the differences between two trees, in alphabetical order of keys. See
+/// [`diff`]( struct.Tst.html#method.diff) method for a brief description with a short example.
+
+#[derive(Debug)]
+pub struct TstDiffIterator<'a, T: 'a> {
+
+    // Each task is stacked with the length of the key above it, and the character it adds to this key.
+    todo: Vec<(DiffTask<'a, T>, usize, Option<char>)>,
+    key: String
+}
+
+
+impl<'a, T> TstDiffIterator<'a, T> {
+
+    pub fn new(old: &'a Tst<T>, new: &'a Tst<T>) -> Self {
+
+        let task = DiffTask::Both(Cursor::Level(&old.root), Cursor::Level(&new.root));
+
+        TstDiffIterator { todo: vec![(task, 0, None)], key: String::new() }
+    }
+
+
+    // Stacks the places below two places of the old and new trees, so that they are popped in alphabetical order. A
+    // branch found in both trees is compared later on, while a branch found in one tree only is walked as is.
+
+    fn push_both(&mut self, old: Cursor<'a, T>, new: Cursor<'a, T>) {
+
+        let len = self.key.len();
+
+        if old.is_empty() {
+
+            return self.push_one(new, DiffTask::New);
+        }
+
+        if new.is_empty() {
+
+            return self.push_one(old, DiffTask::Old);
+        }
+
+        let mut old_branches = Vec::new();
+        let mut new_branches = Vec::new();
+
+        old.branches(&mut old_branches);
+        new.branches(&mut new_branches);
+
+        while !old_branches.is_empty() || !new_branches.is_empty() {
+
+            let ordering = match (old_branches.last(), new_branches.last()) {
+
+                (Some(&(old_label, _)), Some(&(new_label, _))) => old_label.cmp(&new_label),
+
+                (Some(_), None) => Greater,
+
+                _ => Less
+            };
+
+            match ordering {
+
+                Greater => if let Some((label, old)) = old_branches.pop() {
+
+                    self.todo.push((DiffTask::Old(old), len, Some(label)));
+                },
+
+                Less => if let Some((label, new)) = new_branches.pop() {
+
+                    self.todo.push((DiffTask::New(new), len, Some(label)));
+                },
+
+                Equal => if let (Some((label, old)), Some((_, new))) = (old_branches.pop(), new_branches.pop()) {
+
+                    self.todo.push((DiffTask::Both(old, new), len, Some(label)));
+                }
+            }
+        }
+    }
+
+
+    // Stacks the places below a place found in one tree only, so that they are popped in alphabetical order.
+
+    fn push_one(&mut self, cursor: Cursor<'a, T>, task: fn(Cursor<'a, T>) -> DiffTask<'a, T>) {
+
+        let len = self.key.len();
+
+        match cursor {
+
+            Cursor::Level(&None) => (),
+
+            Cursor::Level(Some(node)) => {
+
+                self.todo.push((task(Cursor::Level(&node.right)), len, None));
+                self.todo.push((task(Cursor::Run(node, &node.run)), len, Some(node.label)));
+                self.todo.push((task(Cursor::Level(&node.left)), len, None));
+            }
+
+            Cursor::Run(node, "") => self.todo.push((task(Cursor::Level(&node.middle)), len, None)),
+
+            Cursor::Run(node, run) => {
+
+                // The rest of the run is added to the key at once, and the task is popped right away.
+                self.key.push_str(run);
+                self.todo.push((task(Cursor::Run(node, "")), self.key.len(), None));
+            }
+        }
+    }
+}
+
+
+impl<'a, T> Iterator for TstDiffIterator<'a, T>
+where T: PartialEq {
+
+    type Item = TstDiff<'a, T>;
+
+    fn next(&mut self) -> Option<TstDiff<'a, T>> {
+
+        while let Some((task, len, label)) = self.todo.pop() {
+
+            self.key.truncate(len);
+
+            if let Some(label) = label {
+
+                self.key.push(label);
+            }
+
+            match task {
+
+                DiffTask::Both(old, new) => {
+
+                    self.push_both(old, new);
+
+                    match (old.value(), new.value()) {
+
+                        (Some(old_value), Some(new_value)) => if old_value != new_value {
+
+                            return Some(TstDiff::Changed(self.key.clone(), old_value, new_value));
+                        },
+
+                        (Some(old_value), None) => return Some(TstDiff::Removed(self.key.clone(), old_value)),
+
+                        (None, Some(new_value)) => return Some(TstDiff::Added(self.key.clone(), new_value)),
+
+                        (None, None) => ()
+                    }
+                }
+
+                DiffTask::Old(old) => {
+
+                    self.push_one(old, DiffTask::Old);
+
+                    if let Some(value) = old.value() {
+
+                        return Some(TstDiff::Removed(self.key.clone(), value));
+                    }
+                }
+
+                DiffTask::New(new) => {
+
+                    self.push_one(new, DiffTask::New);
+
+                    if let Some(value) = new.value() {
+
+                        return Some(TstDiff::Added(self.key.clone(), value));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
@@ -0,0 +1,897 @@
+use std::str::Chars;
+use std::cmp::Ordering::Less;
+use std::cmp::Ordering::Equal;
+use std::cmp::Ordering::Greater;
+use std::collections::VecDeque;
+use std::fmt;
+use std::mem;
+
+use super::Tst;
+use super::Stats;
+use super::DistStat;
+use super::walk::NodeRef;
+use super::walk::Walker;
+use super::walk::CompleteWalker;
+use super::walk::NeighborWalker;
+use super::walk::CrosswordWalker;
+
+
+/// A `FrozenTst` is a read-only ternary tree, built once from a [`Tst`]( ./struct.Tst.html) by
+/// [`freeze`]( ./struct.Tst.html#method.freeze). Nodes are laid out in a flat array, in breadth-first order, so that
+/// the children of a node are always stored next to each other. A node only needs the offset of its first child and
+/// three bits telling which children are present, which makes it about four times smaller than a node of a `Tst`.
+/// Values are stored apart from nodes, in a second array.
+///
+/// A `FrozenTst` supports the same searches and iterators as a `Tst`, but its keys and values can no longer be
+/// changed. Runs of characters, which a `Tst` compresses into a single node, are expanded back into one frozen node
+/// per character.
+///
+/// ```
+/// # use ternary_tree::Tst;
+/// # use ternary_tree::tst;
+/// let map = tst!["foo" => "🍄🍄", "bar" => "🐟", "baz" => "㵅"];
+/// let frozen = map.freeze();
+///
+/// assert_eq!(frozen.get("foo"), Some(&"🍄🍄"));
+/// assert_eq!(frozen.len(), 3);
+///
+/// let v: Vec<_> = frozen.iter_complete("ba").collect();
+/// assert_eq!(v, [&"🐟", &"㵅"]);
+/// ```
+
+pub struct FrozenTst<T> {
+
+    nodes: Vec<FrozenNode>,
+    values: Vec<T>
+}
+
+
+const NIL: u32 = u32::MAX;
+
+const LABEL_MASK: u32 = 0x1F_FFFF;
+const HAS_LEFT: u32 = 1 << 21;
+const HAS_MIDDLE: u32 = 1 << 22;
+const HAS_RIGHT: u32 = 1 << 23;
+
+
+// A char only needs 21 bits, the upper bits of `label` tell which children are present. Present children are
+// stored in order (left, middle, right), starting at index `children`.
+
+struct FrozenNode {
+
+    label: u32,
+    children: u32,
+    value: u32
+}
+
+
+impl FrozenNode {
+
+    fn label(&self) -> char {
+
+        ::std::char::from_u32(self.label & LABEL_MASK).unwrap_or('\0')
+    }
+
+
+    fn left(&self) -> u32 {
+
+        if self.label & HAS_LEFT != 0 { self.children } else { NIL }
+    }
+
+
+    fn middle(&self) -> u32 {
+
+        if self.label & HAS_MIDDLE == 0 {
+
+            NIL
+
+        } else if self.label & HAS_LEFT != 0 {
+
+            self.children + 1
+
+        } else {
+
+            self.children
+        }
+    }
+
+
+    fn right(&self) -> u32 {
+
+        if self.label & HAS_RIGHT == 0 {
+
+            NIL
+
+        } else {
+
+            self.children + (self.label & HAS_LEFT != 0) as u32 + (self.label & HAS_MIDDLE != 0) as u32
+        }
+    }
+
+
+    fn has_value(&self) -> bool {
+
+        self.value != NIL
+    }
+}
+
+
+impl fmt::Debug for FrozenNode {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+
+            let value_box = if self.has_value() { "☑" } else { "☐" };
+
+        write!(f, "{}-{}", value_box, self.label())
+    }
+}
+
+
+// Compressed nodes of the `Tst` are expanded in chains of frozen nodes. Each item of the queue is a node of the
+// `Tst` with a character of its run: its label (with its siblings) for the first item, then each character of the
+// run. The value and the middle child are only given to the item of the last character.
+
+pub fn freeze<T>(tst: Tst<T>) -> FrozenTst<T> {
+
+    let mut nodes = Vec::new();
+    let mut values = Vec::with_capacity(tst.count);
+
+    let mut queue = VecDeque::new();
+
+    if let Some(root) = tst.root {
+
+        queue.push_back((root.label, root, 0, true));
+    }
+
+    let mut next_index = 1;
+
+    while let Some((node_label, mut node, offset, first)) = queue.pop_front() {
+
+        let children = next_index;
+        let mut label = node_label as u32;
+
+        let right = if first { node.right.take() } else { None };
+
+        if first {
+
+            if let Some(child) = node.left.take() {
+
+                label |= HAS_LEFT;
+                queue.push_back((child.label, child, 0, true));
+                next_index += 1;
+            }
+        }
+
+        let run_label = node.run[offset..].chars().next();
+
+        let value = match run_label {
+
+            Some(_) => NIL,
+
+            None => match node.value.take() {
+
+                None => NIL,
+
+                Some(value) => {
+
+                    values.push(value);
+                    (values.len() - 1) as u32
+                }
+            }
+        };
+
+        match run_label {
+
+            Some(run_label) => {
+
+                label |= HAS_MIDDLE;
+                queue.push_back((run_label, node, offset + run_label.len_utf8(), false));
+                next_index += 1;
+            }
+
+            None => if let Some(child) = node.middle.take() {
+
+                label |= HAS_MIDDLE;
+                queue.push_back((child.label, child, 0, true));
+                next_index += 1;
+            }
+        }
+
+        if let Some(child) = right {
+
+            label |= HAS_RIGHT;
+            queue.push_back((child.label, child, 0, true));
+            next_index += 1;
+        }
+
+        assert!(next_index < NIL, "too many nodes in FrozenTst");
+
+        nodes.push(FrozenNode { label, children, value });
+    }
+
+    FrozenTst { nodes, values }
+}
+
+
+fn get_r<'a, T>(tst: &'a FrozenTst<T>, link: u32, label: char, key_tail: &mut Chars) -> Option<&'a T> {
+
+    if link == NIL {
+
+        return None;
+    }
+
+    let node = &tst.nodes[link as usize];
+
+    match label.cmp(&node.label()) {
+
+        Less => get_r(tst, node.left(), label, key_tail),
+
+        Equal => {
+
+            let new_label = key_tail.next();
+
+            match new_label {
+
+                None => tst.value(node),
+
+                Some(label) => get_r(tst, node.middle(), label, key_tail)
+            }
+        },
+
+        Greater => get_r(tst, node.right(), label, key_tail),
+    }
+}
+
+
+fn stat_r(stats: Stats, nodes: &[FrozenNode], link: u32, matches: usize, sides: usize, depth: usize) -> Stats {
+
+    if link == NIL {
+
+        return stats;
+    }
+
+    let node = &nodes[link as usize];
+
+    let mut stats = stat_r(stats, nodes, node.left(), matches, sides+1, depth+1);
+
+    stats.count.nodes+=1;
+
+    if node.has_value() {
+
+        let matches = matches + 1;
+        let depth = depth + 1;
+
+        while stats.dist.len() <= depth {
+
+            stats.dist.push(DistStat { matches: 0, sides: 0, depth: 0 });
+        }
+
+        stats.dist[matches].matches+=1;
+        stats.dist[sides].sides+=1;
+        stats.dist[depth].depth+=1;
+
+        if stats.key_len.min == 0 || matches < stats.key_len.min {
+
+            stats.key_len.min = matches;
+        }
+
+        if matches > stats.key_len.max {
+
+            stats.key_len.max = matches;
+        }
+
+        stats.count.values+=1;
+    }
+
+    let stats = stat_r(stats, nodes, node.middle(), matches+1, sides, depth+1);
+
+    stat_r(stats, nodes, node.right(), matches, sides+1, depth+1)
+}
+
+
+fn find_complete_root_r<T>(tst: &FrozenTst<T>, link: u32, label: char, mut key_tail: Chars) -> u32 {
+
+    if link == NIL {
+
+        return NIL;
+    }
+
+    let node = &tst.nodes[link as usize];
+
+    match label.cmp(&node.label()) {
+
+        Less => find_complete_root_r(tst, node.left(), label, key_tail),
+
+        Greater => find_complete_root_r(tst, node.right(), label, key_tail),
+
+        Equal => {
+
+            let new_label = key_tail.next();
+
+            match new_label {
+
+                None => node.middle(),
+
+                Some(label) => find_complete_root_r(tst, node.middle(), label, key_tail)
+            }
+        }
+    }
+}
+
+
+fn visit_values_r<T, C>(tst: &FrozenTst<T>, link: u32, callback: &mut C)
+where C: FnMut (&T) {
+
+    if link == NIL {
+
+        return;
+    }
+
+    let node = &tst.nodes[link as usize];
+
+    visit_values_r(tst, node.left(), callback);
+
+    if let Some(value) = tst.value(node) {
+
+        callback(value);
+    }
+
+    visit_values_r(tst, node.middle(), callback);
+    visit_values_r(tst, node.right(), callback);
+}
+
+
+fn visit_neighbor_values_r<T, C>(tst: &FrozenTst<T>, link: u32, label: Option<char>, key_tail: &mut Chars, tail_len: usize, range: usize, callback: &mut C)
+where C: FnMut (&T) {
+
+    if range == 0 {
+
+        if let Some(label) = label {
+
+            if let Some(value) = get_r(tst, link, label, key_tail) {
+
+                callback(value);
+            }
+        }
+
+    } else if link != NIL {
+
+        let node = &tst.nodes[link as usize];
+
+        visit_neighbor_values_r(tst, node.left(), label, key_tail, tail_len, range, callback);
+
+        let new_range = match label {
+
+            None => range-1,
+
+            Some(label) => if label==node.label() { range } else { range-1 }
+        };
+
+        if let Some(value) = tst.value(node) {
+
+            if tail_len <= new_range {
+
+                callback(value);
+            }
+        }
+
+        {
+            let mut new_tail = key_tail.clone();
+            let new_label = new_tail.next();
+
+            let new_len = if tail_len > 0 { tail_len-1 } else { tail_len };
+
+            visit_neighbor_values_r(tst, node.middle(), new_label, &mut new_tail, new_len, new_range, callback);
+        }
+
+        visit_neighbor_values_r(tst, node.right(), label, key_tail, tail_len, range, callback);
+    }
+}
+
+
+fn visit_crossword_values_r<T, C>(tst: &FrozenTst<T>, link: u32, label: char, key_tail: &mut Chars, joker: char, callback: &mut C)
+    where C: FnMut (&T) {
+
+    if link == NIL {
+
+        return;
+    }
+
+    let node = &tst.nodes[link as usize];
+    let node_label = node.label();
+
+    if label == joker || label < node_label {
+
+        visit_crossword_values_r(tst, node.left(), label, key_tail, joker, callback);
+    }
+
+    if label == joker || label == node_label {
+
+        let mut new_tail = key_tail.clone();
+        let new_label = new_tail.next();
+
+        match new_label {
+
+            None =>  if let Some(value) = tst.value(node) {
+
+                callback(value);
+            },
+
+            Some(label) => visit_crossword_values_r(tst, node.middle(), label, &mut new_tail, joker, callback)
+        }
+    }
+
+    if label == joker || label > node_label {
+
+        visit_crossword_values_r(tst, node.right(), label, key_tail, joker, callback);
+    }
+}
+
+
+impl<T> FrozenTst<T> {
+
+    fn root(&self) -> u32 {
+
+        if self.nodes.is_empty() { NIL } else { 0 }
+    }
+
+
+    fn value(&self, node: &FrozenNode) -> Option<&T> {
+
+        if node.has_value() { Some(&self.values[node.value as usize]) } else { None }
+    }
+
+
+    /// Returns an immutable reference to the value associated with `key`, or None.
+
+    pub fn get(&self, key: &str) -> Option<&T> {
+
+        let mut key_tail = key.chars();
+
+        match key_tail.next() {
+
+            None => None,
+
+            Some(label) => get_r(self, self.root(), label, &mut key_tail)
+        }
+    }
+
+
+    /// Returns the number of values stored in the tree.
+
+    pub fn len(&self) -> usize {
+
+        self.values.len()
+    }
+
+
+    /// Returns `true` if the tree stores no value.
+
+    pub fn is_empty(&self) -> bool {
+
+        self.len() == 0
+    }
+
+
+    /// Walks the tree, gathers various metrics about nodes, keys and values, and returns a [`Stats`](
+    /// ./struct.Stats.html) structure to sum it up. As values are stored apart from nodes, `bytes.node` is the
+    /// size of a node plus the size of a value, and `bytes.total` is the size of all nodes plus the size of all
+    /// values.
+    ///
+    /// ```
+    /// # use ternary_tree::Tst;
+    /// # use ternary_tree::tst;
+    /// let map = tst!["foo" => "🍄🍄", "bar" => "🐟", "baz" => "㵅"];
+    /// let bytes = map.stat().bytes.total;
+    ///
+    /// let frozen = map.freeze();
+    /// assert_eq!(frozen.stat().count.nodes, 7);
+    /// assert_eq!(frozen.stat().bytes.total < bytes, true);
+    /// ```
+
+    pub fn stat(&self) -> Stats {
+
+        let empty_stats: Stats = Default::default();
+
+        let mut stats = stat_r(empty_stats, &self.nodes, self.root(), 0, 0, 0);
+
+        stats.bytes.node = mem::size_of::<FrozenNode>() + mem::size_of::<T>();
+        stats.bytes.total = mem::size_of::<FrozenTst<T>>()
+            + self.nodes.len()*mem::size_of::<FrozenNode>() + self.values.len()*mem::size_of::<T>();
+
+        stats
+    }
+
+
+    /// Recursively walks the tree and calls `callback` closure on each value. See [`Tst::visit_values`](
+    /// ./struct.Tst.html#method.visit_values).
+
+    pub fn visit_values<C>(&self, mut callback: C)
+    where C: FnMut (&T) {
+
+        visit_values_r(self, self.root(), &mut callback);
+    }
+
+
+    /// Recursively walks the tree and calls `callback` closure on each value whose key begins with `key_prefix`. See
+    /// [`Tst::visit_complete_values`]( ./struct.Tst.html#method.visit_complete_values).
+
+    pub fn visit_complete_values<C>(&self, key_prefix: &str, mut callback: C)
+    where C: FnMut (&T) {
+
+        let mut prefix_tail = key_prefix.chars();
+
+        match prefix_tail.next() {
+
+            None => visit_values_r(self, self.root(), &mut callback),
+
+            Some(label) => {
+
+                let new_root = find_complete_root_r(self, self.root(), label, prefix_tail);
+                visit_values_r(self, new_root, &mut callback)
+            }
+        }
+    }
+
+
+    /// Recursively walks the tree and calls `callback` closure on each value whose key is _close_ to `key`
+    /// (Hamming distance of `range`). See [`Tst::visit_neighbor_values`](
+    /// ./struct.Tst.html#method.visit_neighbor_values).
+
+    pub fn visit_neighbor_values<C>(&self, key: &str, range: usize, mut callback: C)
+    where C: FnMut (&T) {
+
+        let mut key_tail = key.chars();
+        let key_len = key.chars().count();
+        let label = key_tail.next();
+        let tail_len = if key_len == 0 { 0 } else { key_len-1 };
+
+        visit_neighbor_values_r(self, self.root(), label, &mut key_tail, tail_len, range, &mut callback);
+    }
+
+
+    /// Recursively walks the tree and calls `callback` closure on each value whose key _matches_ `pattern` with
+    /// `joker` characters. See [`Tst::visit_crossword_values`]( ./struct.Tst.html#method.visit_crossword_values).
+
+    pub fn visit_crossword_values<C>(&self, pattern: &str, joker: char, mut callback: C)
+    where C: FnMut (&T) {
+
+        let mut pattern_tail = pattern.chars();
+
+        if let Some(label) = pattern_tail.next() {
+
+            visit_crossword_values_r(self, self.root(), label, &mut pattern_tail, joker, &mut callback);
+        }
+    }
+
+
+    /// Create a double-ended iterator which successively returns all values of the tree, in alphabetical order of
+    /// keys. See [`Tst::iter`]( ./struct.Tst.html#method.iter).
+
+    pub fn iter(&self) -> FrozenTstIterator<'_, T> {
+
+        FrozenTstIterator::<T>::new(self)
+    }
+
+
+    /// Create a double-ended iterator which successively returns all values whose key begins with `prefix`. See
+    /// [`Tst::iter_complete`]( ./struct.Tst.html#method.iter_complete).
+
+    pub fn iter_complete(&self, prefix: &str) -> FrozenTstCompleteIterator<'_, T> {
+
+        FrozenTstCompleteIterator::<T>::new(self, prefix)
+    }
+
+
+    /// Create a double-ended iterator which successively returns all values whose key is _close_ to `key`. See
+    /// [`Tst::iter_neighbor`]( ./struct.Tst.html#method.iter_neighbor).
+
+    pub fn iter_neighbor<'a, 'b>(&'a self, key: &'b str, range: usize) -> FrozenTstNeighborIterator<'a, 'b, T> {
+
+        FrozenTstNeighborIterator::<T>::new(self, key, range)
+    }
+
+
+    /// Create a double-ended iterator which successively returns all values whose key _matches_ `pattern`. See
+    /// [`Tst::iter_crossword`]( ./struct.Tst.html#method.iter_crossword).
+
+    pub fn iter_crossword<'a, 'b>(&'a self, pattern: &'b str, joker: char) -> FrozenTstCrosswordIterator<'a, 'b, T> {
+
+        FrozenTstCrosswordIterator::<T>::new(self, pattern, joker)
+    }
+}
+
+
+// A node of the frozen tree, as seen by the walkers shared with the other trees.
+
+struct FrozenRef<'a, T: 'a> {
+
+    tst: &'a FrozenTst<T>,
+    index: u32
+}
+
+
+impl<'a, T> Clone for FrozenRef<'a, T> {
+
+    fn clone(&self) -> Self {
+
+        *self
+    }
+}
+
+
+impl<'a, T> Copy for FrozenRef<'a, T> {}
+
+
+impl<'a, T> FrozenRef<'a, T> {
+
+    fn new(tst: &'a FrozenTst<T>, index: u32) -> Option<Self> {
+
+        if index == NIL { None } else { Some(FrozenRef { tst, index }) }
+    }
+
+
+    fn node(&self) -> &'a FrozenNode {
+
+        &self.tst.nodes[self.index as usize]
+    }
+}
+
+
+impl<'a, T> NodeRef for FrozenRef<'a, T> {
+
+    type Value = &'a T;
+
+    fn label(&self) -> char {
+
+        self.node().label()
+    }
+
+
+    fn run(&self) -> &str {
+
+        ""
+    }
+
+
+    fn value(&self) -> Option<&'a T> {
+
+        self.tst.value(self.node())
+    }
+
+
+    fn left(&self) -> Option<Self> {
+
+        FrozenRef::new(self.tst, self.node().left())
+    }
+
+
+    fn middle(&self) -> Option<Self> {
+
+        FrozenRef::new(self.tst, self.node().middle())
+    }
+
+
+    fn right(&self) -> Option<Self> {
+
+        FrozenRef::new(self.tst, self.node().right())
+    }
+
+
+    fn is(&self, other: &Self) -> bool {
+
+        self.index == other.index
+    }
+}
+
+
+/// A double-ended iterator which successively returns all values of a [`FrozenTst`]( ./struct.FrozenTst.html).
+/// See [`iter`]( struct.FrozenTst.html#method.iter) method.
+
+pub struct FrozenTstIterator<'a, T: 'a> {
+
+    it: Walker<FrozenRef<'a, T>>
+}
+
+
+impl<'a, T> FrozenTstIterator<'a, T> {
+
+    pub fn new(tst: &'a FrozenTst<T>) -> Self {
+
+        FrozenTstIterator { it: Walker::new(FrozenRef::new(tst, tst.root()), true) }
+    }
+
+
+    pub fn current_key(&self) -> String {
+
+        self.it.current_key()
+    }
+
+
+    pub fn current_key_back(&self) -> String {
+
+        self.it.current_key_back()
+    }
+}
+
+
+impl<'a, T> Iterator for FrozenTstIterator<'a, T> {
+
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+
+        self.it.next()
+    }
+}
+
+
+impl<'a, T> IntoIterator for &'a FrozenTst<T> {
+
+    type Item = &'a T;
+    type IntoIter = FrozenTstIterator<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+
+        self.iter()
+    }
+}
+
+
+impl<'a, T> DoubleEndedIterator for FrozenTstIterator<'a, T> {
+
+    fn next_back(&mut self) -> Option<&'a T> {
+
+        self.it.next_back()
+    }
+}
+
+
+/// A double-ended iterator which successively returns all values of a [`FrozenTst`]( ./struct.FrozenTst.html)
+/// whose key begins with `prefix`. See [`iter_complete`]( struct.FrozenTst.html#method.iter_complete) method.
+
+pub struct FrozenTstCompleteIterator<'a, T: 'a> {
+
+    it: CompleteWalker<FrozenRef<'a, T>>
+}
+
+
+impl<'a, T> FrozenTstCompleteIterator<'a, T> {
+
+    pub fn new(tst: &'a FrozenTst<T>, key_prefix: &str) -> Self {
+
+        FrozenTstCompleteIterator { it: CompleteWalker::new(FrozenRef::new(tst, tst.root()), key_prefix) }
+    }
+
+
+    pub fn current_key(&self) -> String {
+
+        self.it.current_key()
+    }
+
+
+    pub fn current_key_back(&self) -> String {
+
+        self.it.current_key_back()
+    }
+}
+
+
+impl<'a, T> Iterator for FrozenTstCompleteIterator<'a, T> {
+
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+
+        self.it.next()
+    }
+}
+
+
+impl<'a, T> DoubleEndedIterator for FrozenTstCompleteIterator<'a, T> {
+
+    fn next_back(&mut self) -> Option<&'a T> {
+
+        self.it.next_back()
+    }
+}
+
+
+/// A double-ended iterator which successively returns all values of a [`FrozenTst`]( ./struct.FrozenTst.html)
+/// whose key is _close_ to `key`. See [`iter_neighbor`]( struct.FrozenTst.html#method.iter_neighbor) method.
+
+pub struct FrozenTstNeighborIterator<'a, 'b, T: 'a> {
+
+    it: NeighborWalker<'b, FrozenRef<'a, T>>
+}
+
+
+impl<'a, 'b, T> FrozenTstNeighborIterator<'a, 'b, T> {
+
+    pub fn new(tst: &'a FrozenTst<T>, key: &'b str, range: usize) -> Self {
+
+        FrozenTstNeighborIterator { it: NeighborWalker::new(FrozenRef::new(tst, tst.root()), key, range) }
+    }
+
+
+    pub fn current_key(&self) -> String {
+
+        self.it.current_key()
+    }
+
+
+    pub fn current_key_back(&self) -> String {
+
+        self.it.current_key_back()
+    }
+}
+
+
+impl<'a, 'b, T> Iterator for FrozenTstNeighborIterator<'a, 'b, T> {
+
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+
+        self.it.next()
+    }
+}
+
+
+impl<'a, 'b, T> DoubleEndedIterator for FrozenTstNeighborIterator<'a, 'b, T> {
+
+    fn next_back(&mut self) -> Option<&'a T> {
+
+        self.it.next_back()
+    }
+}
+
+
+/// A double-ended iterator which successively returns all values of a [`FrozenTst`]( ./struct.FrozenTst.html)
+/// whose key _matches_ `pattern`. See [`iter_crossword`]( struct.FrozenTst.html#method.iter_crossword) method.
+
+pub struct FrozenTstCrosswordIterator<'a, 'b, T: 'a> {
+
+    it: CrosswordWalker<'b, FrozenRef<'a, T>>
+}
+
+
+impl<'a, 'b, T> FrozenTstCrosswordIterator<'a, 'b, T> {
+
+    pub fn new(tst: &'a FrozenTst<T>, key: &'b str, joker: char) -> Self {
+
+        FrozenTstCrosswordIterator { it: CrosswordWalker::new(FrozenRef::new(tst, tst.root()), key, joker) }
+    }
+
+
+    pub fn current_key(&self) -> String {
+
+        self.it.current_key()
+    }
+
+
+    pub fn current_key_back(&self) -> String {
+
+        self.it.current_key_back()
+    }
+}
+
+
+impl<'a, 'b, T> Iterator for FrozenTstCrosswordIterator<'a, 'b, T> {
+
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+
+        self.it.next()
+    }
+}
+
+
+impl<'a, 'b, T> DoubleEndedIterator for FrozenTstCrosswordIterator<'a, 'b, T> {
+
+    fn next_back(&mut self) -> Option<&'a T> {
+
+        self.it.next_back()
+    }
+}
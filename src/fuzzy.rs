@@ -0,0 +1,221 @@
+use super::Tst;
+use super::Node;
+
+
+// A step of the walk: the node to look at, whether its siblings were already pushed (`visit`), and what is known
+// of the path leading to it. `row` is the last row of the edit distance matrix between the prefix and this path, or
+// `None` when no descendant could do better than `best`. `best` is the smallest distance between the prefix and
+// any part of the path which begins it, if within budget. `key_len` is the byte length of the path.
+
+#[derive(Debug)]
+struct FuzzyStep<'a, T: 'a> {
+
+    node: &'a Node<T>,
+    visit: bool,
+    row: Option<Vec<usize>>,
+    best: Option<usize>,
+    key_len: usize
+}
+
+
+/// An iterator which successively returns all values whose key begins with a string _close_ to `prefix`. See
+/// [`iter_fuzzy_complete`]( struct.Tst.html#method.iter_fuzzy_complete) method for a brief description with a short
+/// example.
+
+#[derive(Debug)]
+pub struct TstFuzzyCompleteIterator<'a, T: 'a> {
+
+    todo: Vec<FuzzyStep<'a, T>>,
+    prefix: Vec<char>,
+    max_edits: usize,
+    key: String,
+    distance: usize
+}
+
+
+impl<'a, T> TstFuzzyCompleteIterator<'a, T> {
+
+    pub fn new(tst: &'a Tst<T>, prefix: &str, max_edits: usize) -> Self {
+
+        let prefix: Vec<char> = prefix.chars().collect();
+
+        let mut it = TstFuzzyCompleteIterator {
+
+            todo: Vec::new(),
+            prefix,
+            max_edits,
+            key: String::new(),
+            distance: 0
+        };
+
+        if let Some(ref node) = tst.root {
+
+            let row: Vec<usize> = (0..it.prefix.len()+1).collect();
+            let best = it.within_budget(it.prefix.len());
+
+            it.todo.push(FuzzyStep { node, visit: false, row: Some(row), best, key_len: 0 });
+        }
+
+        it
+    }
+
+
+    /// Returns the key associated with the last value returned by [`next`](
+    /// ./struct.TstFuzzyCompleteIterator.html#method.next).
+
+    pub fn current_key(&self) -> String {
+
+        self.key.clone()
+    }
+
+
+    /// Returns the edit distance (insertions, deletions or substitutions of characters) between `prefix` and the
+    /// closest beginning of the key associated with the last value returned by [`next`](
+    /// ./struct.TstFuzzyCompleteIterator.html#method.next).
+
+    pub fn current_distance(&self) -> usize {
+
+        self.distance
+    }
+
+
+    fn within_budget(&self, distance: usize) -> Option<usize> {
+
+        if distance <= self.max_edits { Some(distance) } else { None }
+    }
+
+
+    // Next row of the edit distance matrix, once `label` is appended to the path.
+
+    fn next_row(&self, row: &[usize], label: char) -> Vec<usize> {
+
+        let mut next = Vec::with_capacity(row.len());
+
+        next.push(row[0] + 1);
+
+        for (j, &c) in self.prefix.iter().enumerate() {
+
+            let substitution = row[j] + if c == label { 0 } else { 1 };
+            let deletion = row[j+1] + 1;
+            let insertion = next[j] + 1;
+
+            next.push(substitution.min(deletion).min(insertion));
+        }
+
+        next
+    }
+
+
+    // Appends the characters of `node` to the path. Returns the updated row and best distance, or `None` when no
+    // key below `node` (nor `node` itself) is close enough to `prefix`.
+
+    fn walk_node(&mut self, node: &Node<T>, mut row: Option<Vec<usize>>, mut best: Option<usize>)
+    -> Option<(Option<Vec<usize>>, Option<usize>)> {
+
+        for label in Some(node.label).into_iter().chain(node.run.chars()) {
+
+            if let Some(current) = row.take() {
+
+                let next = self.next_row(&current, label);
+
+                let last = next[next.len()-1];
+
+                if let Some(distance) = self.within_budget(last) {
+
+                    best = Some(best.map_or(distance, |best| best.min(distance)));
+                }
+
+                let lowest = next.iter().cloned().min().unwrap_or(0);
+
+                let improvable = match best {
+
+                    None => true,
+
+                    Some(best) => lowest < best
+                };
+
+                if lowest <= self.max_edits && improvable {
+
+                    row = Some(next);
+                }
+            }
+
+            if row.is_none() && best.is_none() {
+
+                return None;
+            }
+
+            self.key.push(label);
+        }
+
+        Some((row, best))
+    }
+}
+
+
+impl<'a, T> Iterator for TstFuzzyCompleteIterator<'a, T> {
+
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+
+        while let Some(step) = self.todo.pop() {
+
+            let node = step.node;
+
+            if !step.visit {
+
+                // Keys of siblings share the path which leads to `node`, not `node` itself.
+
+                let alive = step.best.is_some() || match step.row {
+
+                    None => false,
+
+                    Some(ref row) => row.iter().any(|&d| d <= self.max_edits)
+                };
+
+                if !alive {
+
+                    continue;
+                }
+
+                if let Some(ref right) = node.right {
+
+                    self.todo.push(FuzzyStep { node: right, visit: false, row: step.row.clone(), best: step.best,
+                        key_len: step.key_len });
+                }
+
+                let left_row = step.row.clone();
+
+                self.todo.push(FuzzyStep { node, visit: true, .. step });
+
+                if let Some(ref left) = node.left {
+
+                    self.todo.push(FuzzyStep { node: left, visit: false, row: left_row, best: step.best,
+                        key_len: step.key_len });
+                }
+
+                continue;
+            }
+
+            self.key.truncate(step.key_len);
+
+            if let Some((row, best)) = self.walk_node(node, step.row, step.best) {
+
+                if let Some(ref middle) = node.middle {
+
+                    self.todo.push(FuzzyStep { node: middle, visit: false, row, best, key_len: self.key.len() });
+                }
+
+                if let (Some(value), Some(distance)) = (node.value.as_ref(), best) {
+
+                    self.distance = distance;
+
+                    return Some(value);
+                }
+            }
+        }
+
+        None
+    }
+}
@@ -53,6 +53,69 @@ Iterators, on the other hand, save their context in a `Vec` and only work on imm
 double-ended, and support `next` and `next_back` methods to walk the tree from both ends. Moreover, once a value is
 found, they offer the `current_key` and `current_key_back` methods to retrieve the associated key.
 
+Besides `Tst`, the `ArenaTst` type offers the same API with nodes packed in a single `Vec` and linked by `u32`
+indices, which is friendlier to the cache and to the allocator on large dictionaries.
+A `Tst` which is built once and then only queried can also be turned into a compact, read-only `FrozenTst` with
+the `freeze` method.
+
+To save memory on long keys, a `Tst` node does not only hold one character: a chain of characters without any
+sibling nor value (like the tail of a long URL) is compressed into a single node, and split again as soon as another
+key branches off inside of it. This is transparent to the API, but shows in the nodes count returned by `stat`.
+
+With the optional `serde` feature, a `Tst` implements `Serialize` and `Deserialize` as a map of keys to values.
+Keys are written in order, and a deserialized tree is built at once from its sorted keys, the median key of each
+group of siblings first, which gives it a well balanced shape whatever the order of the input entries. Whether a
+tree was created with `new_balanced` is not part of this map: a deserialized tree is always a plain one, as with `new`.
+
+Large trees can also be saved with `write_to` and loaded with `read_from`, in a binary format which keeps the shape
+of the tree, so that loading it back does not need to compare any key. Values are turned into bytes by the
+`ValueCodec` trait, which is already implemented for strings, numbers and a few other common types.
+The bytes of a saved tree (a memory-mapped file for instance) can even be searched in place, through a `TstView`
+which decodes nodes on the fly and returns the encoded bytes of values.
+
+Text dictionaries are handled too: `read_words`, `read_tsv` and `read_json_lines` load word lists, `key<TAB>value`
+files and JSON Lines, and report bad lines with their line and column numbers. The matching `write_words`,
+`write_tsv` and `write_json_lines` methods dump a tree in key order.
+
+When readers need a consistent snapshot of a tree which keeps on changing, `PersistentTst` shares its nodes between
+copies: `clone` is O(1), and `insert` or `remove` only copy the nodes along the path of the changed key.
+Its nodes count values too (for `count_prefix`, `rank` and `select`), but do not compress runs of characters.
+For servers with many threads, `ConcurrentTst` spreads keys over several shards, by their first character, each
+shard behind its own lock, so that writers do not block readers of other shards.
+
+The optional `rayon` feature puts all cores to work on large trees: `par_iter`, `par_visit_values_mut` and
+`par_stat` hand disjoint subtrees to separate threads, and `par_from_sorted` builds a well balanced tree from sorted
+entries, with each group of siblings built in parallel.
+
+For search boxes, `ScoredTst` stores a score with each value, and caches in each node the highest score below it, so
+that `top_k_complete` finds the best completions of a prefix without walking all of them.
+
+Mistyped prefixes can be completed too: `iter_fuzzy_complete` finds keys beginning with a string within a few
+edits (insertions, deletions or substitutions of characters) of the prefix, and tells how far each one is.
+
+Each node also counts the values below it, so that `count_prefix` tells how many keys begin with a prefix in time
+proportional to the prefix length. The same counts let `rank` give the position of a key in alphabetical order,
+and `select` find the key at a given position, which helps to paginate long listings.
+
+Keys between two bounds are found with `range`, which takes the usual Rust ranges (`"a".."c"`, `"b"..` and so on)
+and walks down directly to both bounds. In the same way, `ceiling`, `floor`, `successor` and `predecessor` find the
+nearest keys around a probe which may not be in the tree. An iterator can also be moved to a given key with `seek` or
+`seek_back`, to resume a listing where it stopped.
+The smallest and largest keys are read with `first_key_value` and `last_key_value`, and taken out of the tree with
+`pop_first` and `pop_last`, so that a `Tst` can serve as a simple priority queue.
+Values can be filtered in place with `retain`, or taken out with `extract_matching`, in a single walk which prunes the
+nodes left empty on its way back up.
+A whole namespace is taken out at once with `remove_prefix`, which detaches the subtree below a prefix and returns
+it as a new tree, with the prefix stripped from its keys (or kept, with `remove_prefix_keeping`).
+Trees are cut at a key with `split_off`, and put back together with `append`, which grafts whole subtrees wherever
+the keys of both trees do not overlap.
+In the same way, two trees are combined with `union`, `intersection`, `difference` and `symmetric_difference`, in a
+single walk of both trees, with a closure to combine the values of keys found in both.
+Two versions of a tree are compared with `diff`, which walks both trees together and lists the keys added, removed or
+changed from one to the other, skipping at once the subtrees found in one tree only.
+Finally, `rename_prefix` moves all the keys below a prefix to another one, grafting the whole subtree at once, and
+reports (or resolves, with `rename_prefix_with`) the keys which were already there.
+
 The following lines may give you a foretaste of this crate and TSTs
 
 ```
@@ -115,16 +178,108 @@ assert_eq!(map.get("cca"), Some(&"xxx"));
 
 #![forbid(unsafe_code)]
 
+// The badge links at the top of the crate docs are indented with a tab, as in the README.
+#![allow(clippy::tabs_in_doc_comments)]
+
+// Every doc comment of this crate is followed by a blank line, which this lint would flag on each documented item.
+#![allow(clippy::empty_line_after_doc_comments)]
+
 use std::str::Chars;
 use std::mem::replace;
 use std::cmp::Ordering::Less;
 use std::cmp::Ordering::Equal;
 use std::cmp::Ordering::Greater;
+use std::io;
+use std::io::Read;
+use std::io::BufRead;
 use std::io::Write;
+use std::ops::Bound;
+use std::ops::Deref;
+use std::ops::RangeBounds;
 use std::ptr;
 use std::fmt;
 use std::mem;
 
+#[cfg(feature = "serde")]
+extern crate serde;
+
+#[cfg(feature = "rayon")]
+extern crate rayon;
+
+mod arena;
+
+pub use arena::ArenaTst;
+pub use arena::ArenaTstIterator;
+pub use arena::ArenaTstCompleteIterator;
+pub use arena::ArenaTstNeighborIterator;
+pub use arena::ArenaTstCrosswordIterator;
+
+mod frozen;
+
+pub use frozen::FrozenTst;
+pub use frozen::FrozenTstIterator;
+pub use frozen::FrozenTstCompleteIterator;
+pub use frozen::FrozenTstNeighborIterator;
+pub use frozen::FrozenTstCrosswordIterator;
+
+mod binary;
+
+pub use binary::ValueCodec;
+
+mod view;
+
+pub use view::TstView;
+pub use view::TstViewIterator;
+pub use view::TstViewCompleteIterator;
+pub use view::TstViewNeighborIterator;
+pub use view::TstViewCrosswordIterator;
+
+mod persistent;
+
+pub use persistent::PersistentTst;
+pub use persistent::PersistentTstIterator;
+pub use persistent::PersistentTstCompleteIterator;
+pub use persistent::PersistentTstNeighborIterator;
+pub use persistent::PersistentTstCrosswordIterator;
+
+mod concurrent;
+
+pub use concurrent::ConcurrentTst;
+
+mod scored;
+
+pub use scored::ScoredTst;
+
+mod fuzzy;
+
+pub use fuzzy::TstFuzzyCompleteIterator;
+
+mod diff;
+
+pub use diff::TstDiff;
+pub use diff::TstDiffIterator;
+
+mod text;
+
+pub use text::TextError;
+
+mod walk;
+
+use walk::NodeRef;
+use walk::Walker;
+use walk::CompleteWalker;
+use walk::NeighborWalker;
+use walk::CrosswordWalker;
+
+#[cfg(any(feature = "serde", feature = "rayon"))]
+mod sorted;
+
+#[cfg(feature = "serde")]
+mod serde_impl;
+
+#[cfg(feature = "rayon")]
+mod parallel;
+
 
 /// A `Tst` is a ternary tree structure which stores key value pairs and roughly behave like a map, but allowing
 /// more flexible ways to find and iterate over values.
@@ -134,20 +289,95 @@ use std::mem;
 pub struct Tst<T> {
 
     root: Link<T>,
-    count: usize
+    count: usize,
+    balanced: bool
 }
 
 
 type Link<T> = Option<Box<Node<T>>>;
 
 
+// The run of characters of a node. Most nodes have no run, so that it is kept behind a thin pointer, and only takes
+// 8 bytes in a node (instead of 16 bytes for a `Box<str>`), at the cost of a second allocation for nodes which do
+// have a run.
+
+#[derive(Clone, Default)]
+struct Run(Option<Box<Box<str>>>);
+
+
+impl Run {
+
+    // Bytes allocated for this run, outside of its node.
+
+    fn bytes(&self) -> usize {
+
+        match self.0 {
+
+            None => 0,
+
+            Some(ref run) => mem::size_of::<Box<str>>() + run.len()
+        }
+    }
+}
+
+
+impl Deref for Run {
+
+    type Target = str;
+
+    fn deref(&self) -> &str {
+
+        match self.0 {
+
+            None => "",
+
+            Some(ref run) => run
+        }
+    }
+}
+
+
+impl<'a> From<&'a str> for Run {
+
+    fn from(run: &'a str) -> Run {
+
+        if run.is_empty() { Run(None) } else { Run(Some(Box::new(run.into()))) }
+    }
+}
+
+
+impl From<String> for Run {
+
+    fn from(run: String) -> Run {
+
+        if run.is_empty() { Run(None) } else { Run(Some(Box::new(run.into_boxed_str()))) }
+    }
+}
+
+
+impl fmt::Display for Run {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+
+        f.write_str(self)
+    }
+}
+
+
+// A node may hold a _run_ of characters following its label. Such a compressed node stands for a chain of nodes
+// where each node but the first has no sibling, and each node but the last has no value. Left and right links are
+// the siblings of `label`, while `value` and `middle` belong to the last character of the run.
+
 struct Node<T> {
 
     label: char,
+    run: Run,
     value: Option<T>,
     left: Link<T>,
     middle: Link<T>,
-    right: Link<T>
+    right: Link<T>,
+    height: u8,
+    count: usize
 }
 
 
@@ -158,10 +388,13 @@ impl<T> Default for Node<T> {
         Node {
 
             label: '\0',
+            run: Default::default(),
             value: None,
             left: None,
             middle: None,
-            right: None
+            right: None,
+            height: 1,
+            count: 0
         }
     }
 }
@@ -176,50 +409,223 @@ impl<T> fmt::Debug for Node<T> {
                 None => "☐", Some(_) => "☑"
             };
 
-        write!(f, "{}-{}", value_box, self.label)
+        write!(f, "{}-{}{}", value_box, self.label, self.run)
     }
 }
 
 
-fn insert_r<T>(link: &mut Link<T>, label: char, mut key_tail: Chars, value: T) -> Option<T> {
+impl<'a, T> NodeRef for &'a Node<T> {
+
+    type Value = &'a T;
 
-    let choose_branch_and_do_insert = |node: &mut Box<Node<T>>| match label.cmp(&node.label) {
+    fn label(&self) -> char {
 
-        Less => insert_r(&mut node.left, label, key_tail, value),
+        self.label
+    }
 
-        Greater => insert_r(&mut node.right, label, key_tail, value),
 
-        Equal => {
+    fn run(&self) -> &str {
 
-            let new_label = key_tail.next();
+        &self.run
+    }
 
-            match new_label {
 
-                None => replace(&mut node.value, Some(value)),
+    fn value(&self) -> Option<&'a T> {
 
-                Some(label) => insert_r(&mut node.middle, label, key_tail, value)
-            }
+        self.value.as_ref()
+    }
+
+
+    fn left(&self) -> Option<Self> {
+
+        self.left.as_deref()
+    }
+
+
+    fn middle(&self) -> Option<Self> {
+
+        self.middle.as_deref()
+    }
+
+
+    fn right(&self) -> Option<Self> {
+
+        self.right.as_deref()
+    }
+
+
+    fn is(&self, other: &Self) -> bool {
+
+        ptr::eq(*self, *other)
+    }
+}
+
+
+// Consumes the characters of `key_tail` which match the beginning of `run`, and returns how many of them matched.
+
+fn match_run(run: &str, key_tail: &mut Chars) -> usize {
+
+    let mut matched = 0;
+
+    for c in run.chars() {
+
+        let mut new_tail = key_tail.clone();
+
+        if new_tail.next() != Some(c) {
+
+            break;
+        }
+
+        *key_tail = new_tail;
+        matched += 1;
+    }
+
+    matched
+}
+
+
+// Consumes the characters of `key_tail` which match `run`, and tells if the whole run matched.
+
+fn skip_run(run: &str, key_tail: &mut Chars) -> bool {
+
+    for c in run.chars() {
+
+        if key_tail.next() != Some(c) {
+
+            return false;
         }
+    }
+
+    true
+}
+
+
+// Keeps the first `at` characters of the run in `node`, and moves the remaining ones (with the value and the middle
+// child) in a new middle child.
+
+fn split_run<T>(node: &mut Node<T>, at: usize) {
+
+    let index = match node.run.char_indices().nth(at) {
+
+        None => return,
+
+        Some((index, _)) => index
     };
 
-    match link {
+    let mut tail = node.run[index..].chars();
+
+    if let Some(label) = tail.next() {
+
+        let mut child = Node {
+
+            label,
+            run: tail.as_str().into(),
+            value: node.value.take(),
+            middle: node.middle.take(),
+            .. Default::default()
+        };
+
+        update_count(&mut child);
+
+        node.run = node.run[..index].into();
+        node.middle = Some(Box::new(child));
+    }
+}
+
+
+// Merges a node without value with its middle child, as long as this child has no sibling.
+
+fn merge_run<T>(node: &mut Node<T>) {
+
+    while node.value.is_none() {
+
+        let lonely = match node.middle {
+
+            None => false,
+
+            Some(ref child) => child.left.is_none() && child.right.is_none()
+        };
+
+        if !lonely {
+
+            break;
+        }
+
+        if let Some(mut child) = node.middle.take() {
+
+            let mut run = String::with_capacity(node.run.len() + child.label.len_utf8() + child.run.len());
+
+            run.push_str(&node.run);
+            run.push(child.label);
+            run.push_str(&child.run);
+
+            node.run = run.into();
+            node.value = child.value.take();
+            node.middle = child.middle.take();
+        }
+    }
+}
+
+
+fn insert_r<T>(link: &mut Link<T>, label: char, mut key_tail: Chars, value: T, balanced: bool) -> Option<T> {
+
+    let old_value = match *link {
 
         None => {
 
-            let mut node = Box::new(Node::<T>{label, .. Default::default()});
+            let node = Node::<T>{label, run: key_tail.as_str().into(), value: Some(value), count: 1, .. Default::default()};
+
+            *link = Some(Box::new(node));
+
+            None
+        }
+
+        Some(ref mut node) => match label.cmp(&node.label) {
+
+            Less => insert_r(&mut node.left, label, key_tail, value, balanced),
+
+            Greater => insert_r(&mut node.right, label, key_tail, value, balanced),
+
+            Equal => {
+
+                let matched = match_run(&node.run, &mut key_tail);
+
+                if matched < node.run.chars().count() {
+
+                    split_run(node, matched);
+                }
+
+                let new_label = key_tail.next();
+
+                let old_value = match new_label {
+
+                    None => node.value.replace(value),
 
-            let old_value = choose_branch_and_do_insert(&mut node);
+                    Some(label) => insert_r(&mut node.middle, label, key_tail, value, balanced)
+                };
 
-            *link = Some(node);
+                merge_run(node);
 
-            old_value
+                old_value
+            }
         }
+    };
 
-        Some(ref mut node) => choose_branch_and_do_insert(node)
+    if let Some(ref mut node) = *link {
+
+        update_count(node);
+    }
+
+    if balanced {
+
+        rebalance(link);
     }
+
+    old_value
 }
 
 
+#[allow(clippy::match_as_ref)]
 fn get_r<'a, T>(link: &'a Link<T>, label: char, key_tail: &mut Chars) -> Option<&'a T> {
 
     match *link {
@@ -232,6 +638,11 @@ fn get_r<'a, T>(link: &'a Link<T>, label: char, key_tail: &mut Chars) -> Option<
 
             Equal => {
 
+                if !skip_run(&node.run, key_tail) {
+
+                    return None;
+                }
+
                 let new_label = key_tail.next();
 
                 match new_label {
@@ -253,6 +664,7 @@ fn get_r<'a, T>(link: &'a Link<T>, label: char, key_tail: &mut Chars) -> Option<
 }
 
 
+#[allow(clippy::match_as_ref)]
 fn get_r_mut<'a, T>(link: &'a mut Link<T>, label: char, key_tail: &mut Chars) -> Option<&'a mut T> {
 
     match *link {
@@ -265,6 +677,11 @@ fn get_r_mut<'a, T>(link: &'a mut Link<T>, label: char, key_tail: &mut Chars) ->
 
             Equal => {
 
+                if !skip_run(&node.run, key_tail) {
+
+                    return None;
+                }
+
                 let new_label = key_tail.next();
 
                 match new_label {
@@ -286,9 +703,10 @@ fn get_r_mut<'a, T>(link: &'a mut Link<T>, label: char, key_tail: &mut Chars) ->
 }
 
 
+#[allow(clippy::mem_replace_option_with_none)]
 fn remove_r<T>(link: &mut Link<T>, label: char, key_tail: &mut Chars) -> (bool, Option<T>) {
 
-    match *link {
+    let (prune, old_value) = match *link {
 
         None => (false, None),
 
@@ -309,6 +727,11 @@ fn remove_r<T>(link: &mut Link<T>, label: char, key_tail: &mut Chars) -> (bool,
 
             Equal => {
 
+                if !skip_run(&node.run, key_tail) {
+
+                    return (false, None);
+                }
+
                 let new_label = key_tail.next();
 
                 match new_label {
@@ -317,7 +740,9 @@ fn remove_r<T>(link: &mut Link<T>, label: char, key_tail: &mut Chars) -> (bool,
 
                         let old_value = replace(&mut node.value, None);
 
-                        let prune = old_value.is_some() && node.left.is_none() && node.middle.is_none() && node.right.is_none();
+                        merge_run(node);
+
+                        let prune = old_value.is_some() && node.value.is_none() && node.left.is_none() && node.middle.is_none() && node.right.is_none();
                         (prune, old_value)
                     }
 
@@ -330,6 +755,8 @@ fn remove_r<T>(link: &mut Link<T>, label: char, key_tail: &mut Chars) -> (bool,
                             node.middle = None;
                         }
 
+                        merge_run(node);
+
                         let more_pruning = node.value.is_none() && node.left.is_none() && node.middle.is_none() && node.right.is_none();
                         (more_pruning, old_value)
                     }
@@ -349,496 +776,1494 @@ fn remove_r<T>(link: &mut Link<T>, label: char, key_tail: &mut Chars) -> (bool,
                 (more_pruning, old_value)
             }
         }
+    };
+
+    if let Some(ref mut node) = *link {
+
+        update_count(node);
     }
+
+    (prune, old_value)
 }
 
 
-/// How nodes are distributed. See [Stats]( ./struct.Stats.html) for a brief description.
+fn height<T>(link: &Link<T>) -> u8 {
 
-#[derive(Default,PartialEq,Debug)]
-pub struct DistStat { pub matches: usize, pub sides: usize, pub depth: usize }
+    match *link {
 
+        None => 0,
 
-/// How long are the keys. See [Stats]( ./struct.Stats.html) for a brief description.
+        Some(ref node) => node.height
+    }
+}
 
-#[derive(Default,PartialEq,Debug)]
-pub struct KeyLenStat { pub min: usize, pub max: usize }
 
+fn update_height<T>(node: &mut Node<T>) {
 
-/// How many nodes and values are in the tree. See [Stats]( ./struct.Stats.html) for a brief description.
+    node.height = 1 + height(&node.left).max(height(&node.right));
+}
 
-#[derive(Default,PartialEq,Debug)]
-pub struct CountStat { pub nodes:usize, pub values: usize }
 
+fn count<T>(link: &Link<T>) -> usize {
 
-/// Memory used by the tree. See [Stats]( ./struct.Stats.html) for a brief description.
+    match *link {
 
-#[derive(Default,PartialEq,Debug)]
-pub struct BytesStat { pub node: usize, pub total: usize }
+        None => 0,
 
+        Some(ref node) => node.count
+    }
+}
 
-/// Contains various metrics describing the tree: its nodes, keys and values. Mostly used for tuning and debugging
-/// purpose.
-/// * `dist[n].matches` number of values reached by traversing _n_ `middle` links (the number of keys of length
-/// _n_)
-/// * `dist[n].sides` number of values reached by traversing _n_ `left` or `middle` links (those links may indicate
-/// that the tree is not well balanced)
-/// * `dist[n].depth` number of values whose total depth (`middle`, `left` and `right` links) is _n_
-/// * `key_len.min` length of the shortest key inserted in the tree
-/// * `key_len.max` length of the longest key inserted in the tree
-/// * `count.nodes` total number of nodes in the tree
-/// * `count.values` number of nodes which store a value (same as [len]( ./struct.Tst.html#method.len))
-/// * `bytes.node` byte size of a node (including the fixed size of a value, but excluding heap allocated memory of
-/// this value)
-/// * `bytes.total` total number of bytes allocated for nodes (`count.nodes` * `bytes.node`)
 
-#[derive(Default,PartialEq,Debug)]
-pub struct Stats {
+// Each node counts the values of its subtree, siblings included. This count must be updated, from the bottom up,
+// whenever a value or a child of the node changes.
 
-    pub dist: Vec<DistStat>,
-    pub key_len: KeyLenStat,
-    pub count: CountStat,
-    pub bytes: BytesStat,
+fn update_count<T>(node: &mut Node<T>) {
+
+    node.count = node.value.iter().count() + count(&node.left) + count(&node.middle) + count(&node.right);
 }
 
 
-fn stat_r<T>(stats: Stats, link: &Link<T>, matches: usize, sides: usize, depth: usize) -> Stats {
+fn rotate_left<T>(link: &mut Link<T>) {
 
-    match *link {
+    if let Some(mut node) = link.take() {
 
-        None => stats,
+        match node.right.take() {
 
-        Some(ref node) => {
+            None => *link = Some(node),
 
-            let mut stats = stat_r(stats, &node.left, matches, sides+1, depth+1);
+            Some(mut right) => {
 
-            stats.count.nodes+=1;
+                node.right = right.left.take();
+                update_height(&mut node);
+                update_count(&mut node);
 
-            if node.value.is_some() {
+                right.left = Some(node);
+                update_height(&mut right);
+                update_count(&mut right);
 
-                let matches = matches + 1;
-                let depth = depth + 1;
+                *link = Some(right);
+            }
+        }
+    }
+}
 
-                while stats.dist.len() <= depth {
 
-                    stats.dist.push(DistStat { matches: 0, sides: 0, depth: 0 });
-                }
+fn rotate_right<T>(link: &mut Link<T>) {
 
-                stats.dist[matches].matches+=1;
-                stats.dist[sides].sides+=1;
-                stats.dist[depth].depth+=1;
+    if let Some(mut node) = link.take() {
 
-                if stats.key_len.min == 0 || matches < stats.key_len.min {
+        match node.left.take() {
 
-                    stats.key_len.min = matches;
-                }
+            None => *link = Some(node),
 
-                if matches > stats.key_len.max {
+            Some(mut left) => {
 
-                    stats.key_len.max = matches;
-                }
-
-                stats.count.values+=1;
-            }
+                node.left = left.right.take();
+                update_height(&mut node);
+                update_count(&mut node);
 
-            let stats = stat_r(stats, &node.middle, matches+1, sides, depth+1);
-            let stats = stat_r(stats, &node.right, matches, sides+1, depth+1);
+                left.right = Some(node);
+                update_height(&mut left);
+                update_count(&mut left);
 
-            stats
+                *link = Some(left);
+            }
         }
     }
 }
 
 
-fn find_complete_root_r<'a, T>(link: &'a Link<T>, label: char, mut key_tail: Chars) -> &'a Link<T> {
+// Left and right links of a node form a binary search tree of siblings (all nodes sharing the same key prefix).
+// In a balanced tree, this sibling tree is kept AVL balanced, and middle links are left untouched by rotations.
 
-    match *link {
-
-        None => &link,
-
-        Some(ref node) => match label.cmp(&node.label) {
-
-            Less => find_complete_root_r(&node.left, label, key_tail),
-
-            Greater => find_complete_root_r(&node.right, label, key_tail),
+fn rebalance<T>(link: &mut Link<T>) {
 
-            Equal => {
+    let balance = match *link {
 
-                let new_label = key_tail.next();
+        None => return,
 
-                match new_label {
+        Some(ref mut node) => {
 
-                    None => &node.middle,
+            update_height(node);
+            update_count(node);
 
-                    Some(label) => find_complete_root_r(&node.middle, label, key_tail)
-                }
-            }
+            height(&node.left) as i32 - height(&node.right) as i32
         }
-    }
-}
+    };
 
+    if let Some(ref mut node) = *link {
 
-fn find_complete_root_r_mut<'a, T>(link: &'a mut Link<T>, label: char, mut key_tail: Chars) -> &'a mut Link<T> {
+        if balance > 1 {
 
-    match *link {
+            let inner_heavy = match node.left {
 
-        None => { link }
+                None => false,
 
-        Some(ref mut node) => match label.cmp(&node.label) {
+                Some(ref left) => height(&left.left) < height(&left.right)
+            };
 
-            Less => find_complete_root_r_mut(&mut node.left, label, key_tail),
+            if inner_heavy {
 
-            Greater => find_complete_root_r_mut(&mut node.right, label, key_tail),
+                rotate_left(&mut node.left);
+            }
 
-            Equal => {
+        } else if balance < -1 {
 
-                let new_label = key_tail.next();
+            let inner_heavy = match node.right {
 
-                match new_label {
+                None => false,
 
-                    None => &mut node.middle,
+                Some(ref right) => height(&right.right) < height(&right.left)
+            };
 
-                    Some(label) => find_complete_root_r_mut(&mut node.middle, label, key_tail)
-                }
+            if inner_heavy {
+
+                rotate_right(&mut node.right);
             }
         }
     }
-}
 
+    if balance > 1 {
 
-fn visit_values_r<T, C>(link: &Link<T>, callback: &mut C)
-where C: FnMut (&T) {
+        rotate_right(link);
 
-    match *link {
+    } else if balance < -1 {
 
-        None => return,
+        rotate_left(link);
+    }
+}
 
-        Some(ref node) => {
 
-            visit_values_r(&node.left, callback);
+fn take_min_r<T>(link: &mut Link<T>) -> Link<T> {
 
-            if let Some(ref value) = node.value {
+    let has_left = match *link {
 
-                callback(value);
-            }
+        None => return None,
 
-            visit_values_r(&node.middle, callback);
-            visit_values_r(&node.right, callback);
-        }
-    }
-}
+        Some(ref node) => node.left.is_some()
+    };
 
+    if has_left {
 
-fn visit_values_r_mut<T, C>(link: &mut Link<T>, callback: &mut C)
-where C: FnMut (&mut T) {
+        let min = match *link {
 
-    match *link {
+            None => None,
 
-        None => return,
+            Some(ref mut node) => take_min_r(&mut node.left)
+        };
 
-        Some(ref mut node) => {
+        rebalance(link);
 
-            visit_values_r_mut(&mut node.left, callback);
+        min
 
-            if let Some(ref mut value) = node.value {
+    } else {
 
-                callback(value);
-            }
+        let mut node = link.take();
 
-            visit_values_r_mut(&mut node.middle, callback);
-            visit_values_r_mut(&mut node.right, callback);
+        if let Some(ref mut node) = node {
+
+            *link = node.right.take();
         }
+
+        node
     }
 }
 
 
-fn visit_complete_values_r<T, C>(link: &Link<T>, callback: &mut C)
-where C: FnMut (&T) {
-
-    match *link {
-
-        None => return,
-
-        Some(ref node) => {
-
-            visit_values_r(&node.left, callback);
-
-            if let Some(ref value) = node.value {
+fn unlink_sibling<T>(link: &mut Link<T>) {
 
-                callback(value);
-            }
+    if let Some(mut node) = link.take() {
 
-            visit_values_r(&node.middle, callback);
-            visit_values_r(&node.right, callback);
-        }
-    }
-}
+        *link = match (node.left.take(), node.right.take()) {
 
+            (None, None) => None,
 
-fn visit_complete_values_r_mut<T, C>(link: &mut Link<T>, callback: &mut C)
-where C: FnMut (&mut T) {
+            (Some(left), None) => Some(left),
 
-    match *link {
+            (None, Some(right)) => Some(right),
 
-        None => return,
+            (Some(left), Some(right)) => {
 
-        Some(ref mut node) => {
+                let mut right = Some(right);
+                let mut min = take_min_r(&mut right);
 
-            visit_values_r_mut(&mut node.left, callback);
+                if let Some(ref mut min) = min {
 
-            if let Some(ref mut value) = node.value {
+                    min.left = Some(left);
+                    min.right = right;
+                }
 
-                callback(value);
+                min
             }
+        };
 
-            visit_values_r_mut(&mut node.middle, callback);
-            visit_values_r_mut(&mut node.right, callback);
-        }
+        rebalance(link);
     }
 }
 
 
-fn visit_neighbor_values_r<'a, T, C>(link: &'a Link<T>, label: Option<char>, key_tail: &mut Chars, tail_len: usize, range: usize, callback: &mut C)
-where C: FnMut (&T) {
-
-    if range == 0 {
+fn remove_balanced_r<T>(link: &mut Link<T>, label: char, key_tail: &mut Chars) -> Option<T> {
 
-        if let Some(label) = label {
-
-            if let Some(value) = get_r(link, label, key_tail) {
+    let old_value = match *link {
 
-                callback(value);
-            }
-        }
+        None => None,
 
-    } else {
+        Some(ref mut node) => match label.cmp(&node.label) {
 
-        if let Some(ref node) = *link {
+            Less => remove_balanced_r(&mut node.left, label, key_tail),
 
-            visit_neighbor_values_r(&node.left, label, key_tail, tail_len, range, callback);
+            Greater => remove_balanced_r(&mut node.right, label, key_tail),
 
-            if let Some(ref value) = node.value {
+            Equal => {
 
-                let new_range = match label {
+                if !skip_run(&node.run, key_tail) {
 
-                    None => range-1,
+                    return None;
+                }
 
-                    Some(label) => if label==node.label { range } else { range-1 }
-                };
+                match key_tail.next() {
 
-                if tail_len <= new_range {
+                    None => node.value.take(),
 
-                    callback(value);
+                    Some(label) => remove_balanced_r(&mut node.middle, label, key_tail)
                 }
             }
+        }
+    };
 
-            {
-                let new_range = match label {
+    if old_value.is_some() {
 
-                    None => range-1,
+        let is_empty = match *link {
 
-                    Some(label) => if label==node.label { range } else { range-1 }
-                };
+            None => false,
 
-                let mut new_tail = key_tail.clone();
-                let new_label = new_tail.next();
+            Some(ref mut node) => {
 
-                let new_len = if tail_len > 0 { tail_len-1 } else { tail_len };
+                merge_run(node);
 
-                visit_neighbor_values_r(&node.middle, new_label, &mut new_tail, new_len, new_range, callback);
+                node.value.is_none() && node.middle.is_none()
             }
+        };
 
-            visit_neighbor_values_r(&node.right, label, key_tail, tail_len, range, callback);
+        if is_empty {
+
+            unlink_sibling(link);
+
+        } else {
+
+            rebalance(link);
         }
     }
-}
 
+    old_value
+}
 
-fn visit_neighbor_values_r_mut<'a, T, C>(link: &'a mut Link<T>, label: Option<char>, key_tail: &mut Chars, tail_len: usize, range: usize, callback: &mut C)
-where C: FnMut (&mut T) {
 
-    if range == 0 {
+// Walks the tree in key order, and hands each value for which `keep` returns false to `removed`. Nodes are then
+// pruned from the bottom up, the same way `remove_r` (or `remove_balanced_r`) would do.
 
-        if let Some(label) = label {
+fn retain_r<T, F, C>(link: &mut Link<T>, key: &mut String, keep: &mut F, removed: &mut C, balanced: bool)
+where F: FnMut(&str, &mut T) -> bool, C: FnMut(&str, T) {
 
-            if let Some(value) = get_r_mut(link, label, key_tail) {
+    let is_empty = match *link {
 
-                callback(value);
-            }
-        }
+        None => return,
 
-    } else {
+        Some(ref mut node) => {
 
-        if let Some(ref mut node) = *link {
+            retain_r(&mut node.left, key, keep, removed, balanced);
 
-            let label_tmp = node.label;
+            let key_len = key.len();
 
-            visit_neighbor_values_r_mut(&mut node.left, label, key_tail, tail_len, range, callback);
+            key.push(node.label);
+            key.push_str(&node.run);
 
-            if let Some(ref mut value) = node.value {
+            let kept = match node.value {
 
-                let new_range = match label {
+                None => true,
 
-                    None => range-1,
+                Some(ref mut value) => keep(key, value)
+            };
 
-                    Some(label) => if label == label_tmp { range } else { range-1 }
-                };
+            if !kept {
 
-                if tail_len <= new_range {
+                if let Some(value) = node.value.take() {
 
-                    callback(value);
+                    removed(key, value);
                 }
             }
 
-            {
-                let new_range = match label {
+            retain_r(&mut node.middle, key, keep, removed, balanced);
 
-                    None => range-1,
+            key.truncate(key_len);
 
-                    Some(label) => if label == node.label { range } else { range-1 }
-                };
+            retain_r(&mut node.right, key, keep, removed, balanced);
 
-                let mut new_tail = key_tail.clone();
-                let new_label = new_tail.next();
+            merge_run(node);
+            update_count(node);
 
-                let new_len = if tail_len > 0 { tail_len-1 } else { tail_len };
+            if balanced {
 
-                visit_neighbor_values_r_mut(&mut node.middle, new_label, &mut new_tail, new_len, new_range, callback);
+                node.value.is_none() && node.middle.is_none()
+
+            } else {
+
+                node.value.is_none() && node.left.is_none() && node.middle.is_none() && node.right.is_none()
             }
+        }
+    };
 
-            visit_neighbor_values_r_mut(&mut node.right, label, key_tail, tail_len, range, callback);
+    if !balanced {
+
+        if is_empty {
+
+            *link = None;
         }
+
+    } else if is_empty {
+
+        unlink_sibling(link);
+
+    } else {
+
+        rebalance(link);
     }
 }
 
 
-fn visit_crossword_values_r<'a, T, C>(link: &'a Link<T>, label: char, key_tail: &mut Chars, joker: char, callback: &mut C)
-    where C: FnMut (&T) {
+// Takes the value of the first key below `link` (or of the last one, if not `first`), in a single descent along the
+// leftmost (or rightmost) path, and builds this key on the way. Nodes left empty are pruned on the way back up, as
+// in `retain_r`.
 
-    match *link {
+fn pop_r<T>(link: &mut Link<T>, key: &mut String, first: bool, balanced: bool) -> Option<T> {
 
-        None => return,
+    let (is_empty, value) = match *link {
 
-        Some(ref node) => {
+        None => return None,
 
-            if label == joker || label < node.label {
+        Some(ref mut node) => {
 
-                visit_crossword_values_r(&node.left, label, key_tail, joker, callback);
-            }
+            let value = if first && node.left.is_some() {
 
-            if label == joker || label == node.label {
+                pop_r(&mut node.left, key, first, balanced)
 
-                let mut new_tail = key_tail.clone();
-                let new_label = new_tail.next();
+            } else if !first && node.right.is_some() {
 
-                match new_label {
+                pop_r(&mut node.right, key, first, balanced)
 
-                    None =>  if let Some(ref value) = node.value {
+            } else if node.value.is_some() || node.middle.is_some() {
 
-                        callback(value);
-                    },
+                key.push(node.label);
+                key.push_str(&node.run);
 
-                    Some(label) => visit_crossword_values_r(&node.middle, label, &mut new_tail, joker, callback)
-                }
-            }
+                // The value of a node comes before the keys below its middle child
 
-            if label == joker || label > node.label {
+                let take_value = if first { node.value.is_some() } else { node.middle.is_none() };
 
-                visit_crossword_values_r(&node.right, label, key_tail, joker, callback);
-            }
-        }
-    }
-}
+                if take_value {
 
+                    node.value.take()
 
-fn visit_crossword_values_r_mut<'a, T, C>(link: &'a mut Link<T>, label: char, key_tail: &mut Chars, joker: char, callback: &mut C)
-    where C: FnMut (&mut T) {
+                } else {
 
-    match *link {
+                    pop_r(&mut node.middle, key, first, balanced)
+                }
 
-        None => return,
+            } else if first {
 
-        Some(ref mut node) => {
+                // Only a sibling is left to this node, once its value was removed
 
-            if label == joker || label < node.label {
+                pop_r(&mut node.right, key, first, balanced)
 
-                visit_crossword_values_r_mut(&mut node.left, label, key_tail, joker, callback);
-            }
+            } else {
 
-            if label == joker || label == node.label {
+                pop_r(&mut node.left, key, first, balanced)
+            };
 
-                let mut new_tail = key_tail.clone();
-                let new_label = new_tail.next();
+            merge_run(node);
+            update_count(node);
 
-                match new_label {
+            let is_empty = if balanced {
 
-                    None =>  if let Some(ref mut value) = node.value {
+                node.value.is_none() && node.middle.is_none()
 
-                        callback(value);
-                    },
+            } else {
 
-                    Some(label) => visit_crossword_values_r_mut(&mut node.middle, label, &mut new_tail, joker, callback)
-                }
-            }
+                node.value.is_none() && node.left.is_none() && node.middle.is_none() && node.right.is_none()
+            };
 
-            if label == joker || label > node.label {
+            (is_empty, value)
+        }
+    };
 
-                visit_crossword_values_r_mut(&mut node.right, label, key_tail, joker, callback);
-            }
+    if !balanced {
+
+        if is_empty {
+
+            *link = None;
         }
+
+    } else if is_empty {
+
+        unlink_sibling(link);
+
+    } else {
+
+        rebalance(link);
     }
-}
 
+    value
+}
 
-fn pretty_print_r<'a, T>(link: &'a Link<T>, ids: &mut Tst<usize>, writer: &mut dyn Write) {
 
-    match *link {
+// Detaches the subtree of all keys which begin with `label` and `key_tail` (but not this very key), and returns it
+// without their common prefix. Nodes of the path which are left empty are then pruned, as `remove_r` (or
+// `remove_balanced_r`) would do.
 
-        None => return,
+fn remove_prefix_r<T>(link: &mut Link<T>, label: char, mut key_tail: Chars, balanced: bool) -> Link<T> {
 
-        Some(ref node) => {
+    let (detached, is_empty) = match *link {
 
-            let value_box = match node.value {
+        None => return None,
 
-                None => "☐", Some(_) => "☑"
-            };
+        Some(ref mut node) => {
 
-            {
-                let mut get_id = |node: &Box<Node<T>>| {
+            let detached = match label.cmp(&node.label) {
 
-                    let node_addr = format!("{:p}", node);
+                Less => remove_prefix_r(&mut node.left, label, key_tail, balanced),
 
-                    let prev_id = match ids.get(&node_addr) {
+                Greater => remove_prefix_r(&mut node.right, label, key_tail, balanced),
 
-                        None => None,
+                Equal => {
 
-                        Some(id) => Some(*id)
-                    };
+                    let matched = match_run(&node.run, &mut key_tail);
+                    let new_label = key_tail.next();
 
-                    match prev_id {
+                    match new_label {
 
                         None => {
 
-                            let id = ids.len();
-                            ids.insert(&node_addr, id);
-                            id
+                            // When the prefix ends inside the run, the rest of the run goes with the subtree.
+
+                            split_run(node, matched);
+                            node.middle.take()
                         }
 
-                        Some(id) => id
+                        Some(_) if matched < node.run.chars().count() => None,
+
+                        Some(label) => remove_prefix_r(&mut node.middle, label, key_tail, balanced)
                     }
-                };
+                }
+            };
 
-                let _ = writeln!(writer, r#"N{} [label=<<TABLE BORDER="0" CELLBORDER="1" CELLSPACING="0"><TR><TD COLSPAN="3">{} {}</TD></TR><TR><TD PORT="l"></TD><TD PORT="m"></TD><TD PORT="r"></TD></TR></TABLE>>]"#, get_id(node), value_box, node.label);
+            match detached {
 
-                let mut print_edge = |link, start, style| if let &Some(ref child) = link {
+                None => return None,
 
-                    let _ = writeln!(writer, r#"N{}:{} -> N{} [style={}]"#, get_id(node), start, get_id(child), style);
-                };
+                Some(subtree) => {
 
-                print_edge(&node.left, "l", "solid");
-                print_edge(&node.middle, "m", "bold");
-                print_edge(&node.right, "r", "solid");
-            }
+                    merge_run(node);
+                    update_count(node);
+
+                    let is_empty = if balanced {
+
+                        node.value.is_none() && node.middle.is_none()
+
+                    } else {
+
+                        node.value.is_none() && node.left.is_none() && node.middle.is_none() && node.right.is_none()
+                    };
+
+                    (Some(subtree), is_empty)
+                }
+            }
+        }
+    };
+
+    if !balanced {
+
+        if is_empty {
+
+            *link = None;
+        }
+
+    } else if is_empty {
+
+        unlink_sibling(link);
+
+    } else {
+
+        rebalance(link);
+    }
+
+    detached
+}
+
+
+// Puts a tree below a single node holding `prefix`, so that all its keys begin with `prefix`.
+
+fn prepend_prefix<T>(prefix: &str, link: Link<T>) -> Link<T> {
+
+    let mut prefix_tail = prefix.chars();
+
+    match (prefix_tail.next(), link) {
+
+        (Some(label), Some(root)) => {
+
+            let mut node = Node { label, run: prefix_tail.as_str().into(), middle: Some(root), .. Default::default() };
+
+            merge_run(&mut node);
+            update_count(&mut node);
+
+            Some(Box::new(node))
+        }
+
+        (_, link) => link
+    }
+}
+
+
+// Links `node` between two sibling trees, whose labels are all lower (in `left`) and higher (in `right`). In a
+// balanced tree, `node` goes down the taller tree until heights match, and the path is rebalanced on the way back.
+// A node without value nor middle child is dropped.
+
+fn join<T>(left: Link<T>, mut node: Box<Node<T>>, right: Link<T>, balanced: bool) -> Link<T> {
+
+    merge_run(&mut node);
+
+    if node.value.is_none() && node.middle.is_none() {
+
+        return concat(left, right, balanced);
+    }
+
+    if balanced && height(&left) > height(&right) + 1 {
+
+        if let Some(mut top) = left {
+
+            top.right = join(top.right.take(), node, right, balanced);
+
+            let mut link = Some(top);
+            rebalance(&mut link);
+
+            return link;
+        }
+
+    } else if balanced && height(&right) > height(&left) + 1 {
+
+        if let Some(mut top) = right {
+
+            top.left = join(left, node, top.left.take(), balanced);
+
+            let mut link = Some(top);
+            rebalance(&mut link);
+
+            return link;
+        }
+    }
+
+    node.left = left;
+    node.right = right;
+
+    update_height(&mut node);
+    update_count(&mut node);
+
+    Some(node)
+}
+
+
+// Links two sibling trees, where all labels of `left` are lower than those of `right`.
+
+fn concat<T>(left: Link<T>, right: Link<T>, balanced: bool) -> Link<T> {
+
+    match (left, right) {
+
+        (None, right) => right,
+
+        (left, None) => left,
+
+        (Some(left), Some(right)) => {
+
+            let mut right = Some(right);
+
+            if balanced {
+
+                match take_min_r(&mut right) {
+
+                    None => Some(left),
+
+                    Some(min) => join(Some(left), min, right, balanced)
+                }
+
+            } else {
+
+                let mut left = Some(left);
+                graft_max_r(&mut left, right);
+
+                left
+            }
+        }
+    }
+}
+
+
+fn graft_max_r<T>(link: &mut Link<T>, right: Link<T>) {
+
+    match *link {
+
+        None => *link = right,
+
+        Some(ref mut node) => {
+
+            graft_max_r(&mut node.right, right);
+            update_count(node);
+        }
+    }
+}
+
+
+// Splits a tree in two: keys lower than `label` and `key_tail`, and the other ones.
+
+fn split_r<T>(link: Link<T>, label: char, mut key_tail: Chars, balanced: bool) -> (Link<T>, Link<T>) {
+
+    let mut node = match link {
+
+        None => return (None, None),
+
+        Some(node) => node
+    };
+
+    let left = node.left.take();
+    let right = node.right.take();
+
+    match label.cmp(&node.label) {
+
+        Less => {
+
+            let (low, high) = split_r(left, label, key_tail, balanced);
+            (low, join(high, node, right, balanced))
+        }
+
+        Greater => {
+
+            let (low, high) = split_r(right, label, key_tail, balanced);
+            (join(left, node, low, balanced), high)
+        }
+
+        Equal => match crossing_run(&node.run, &mut key_tail) {
+
+            Less => (left, join(None, node, right, balanced)),
+
+            Greater => (join(left, node, None, balanced), right),
+
+            Equal => match key_tail.next() {
+
+                None => (left, join(None, node, right, balanced)),
+
+                Some(label) => {
+
+                    // The value of `node` is a prefix of the key, thus lower, while its middle child is split.
+
+                    let (low, high) = split_r(node.middle.take(), label, key_tail, balanced);
+
+                    let high_node = Node { label: node.label, run: node.run.clone(), middle: high, .. Default::default() };
+
+                    node.middle = low;
+
+                    (join(left, node, None, balanced), join(None, Box::new(high_node), right, balanced))
+                }
+            }
+        }
+    }
+}
+
+
+// Splits a sibling tree in three: the siblings lower than `label`, the one with this very label, and the higher
+// ones. The node with `label`, if any, is returned without its siblings.
+
+fn split_label<T>(link: Link<T>, label: char, balanced: bool) -> (Link<T>, Link<T>, Link<T>) {
+
+    let mut node = match link {
+
+        None => return (None, None, None),
+
+        Some(node) => node
+    };
+
+    let left = node.left.take();
+    let right = node.right.take();
+
+    match label.cmp(&node.label) {
+
+        Less => {
+
+            let (low, same, high) = split_label(left, label, balanced);
+            (low, same, join(high, node, right, balanced))
+        }
+
+        Equal => (left, Some(node), right),
+
+        Greater => {
+
+            let (low, same, high) = split_label(right, label, balanced);
+            (join(left, node, low, balanced), same, high)
+        }
+    }
+}
+
+
+// Merges two trees in a single walk, grafting whole subtrees wherever keys of one tree do not overlap those of the
+// other. Subtrees found only in `link` (or only in `other`) are kept if `keep.0` (or `keep.1`) is true, and dropped
+// otherwise. When both trees hold a value for the same key, `both` is given this key, the value of `link` and the
+// one of `other`, and returns the value to keep, if any.
+
+fn merge_r<T, F>(link: Link<T>, other: Link<T>, key: &mut String, keep: (bool, bool), both: &mut F, balanced: bool)
+-> Link<T>
+where F: FnMut(&str, T, T) -> Option<T> {
+
+    let (low, same, high, mut node) = match (link, other) {
+
+        (None, other) => return if keep.1 { other } else { None },
+
+        (link, None) => return if keep.0 { link } else { None },
+
+        (Some(node), Some(other_node)) => {
+
+            let (low, same, high) = split_label(Some(node), other_node.label, balanced);
+            (low, same, high, other_node)
+        }
+    };
+
+    let left = merge_r(low, node.left.take(), key, keep, both, balanced);
+
+    match same {
+
+        None => if !keep.1 {
+
+            node.value = None;
+            node.middle = None;
+        },
+
+        Some(mut same) => {
+
+            // Both nodes must have the same run before their values and middle children are merged.
+
+            let common = same.run.chars().zip(node.run.chars()).take_while(|&(a, b)| a == b).count();
+
+            split_run(&mut same, common);
+            split_run(&mut node, common);
+
+            let key_len = key.len();
+
+            key.push(node.label);
+            key.push_str(&node.run);
+
+            node.value = match (same.value.take(), node.value.take()) {
+
+                (Some(value), Some(other_value)) => both(key, value, other_value),
+
+                (value, None) => if keep.0 { value } else { None },
+
+                (None, other_value) => if keep.1 { other_value } else { None }
+            };
+
+            node.middle = merge_r(same.middle.take(), node.middle.take(), key, keep, both, balanced);
+
+            key.truncate(key_len);
+        }
+    }
+
+    let right = merge_r(high, node.right.take(), key, keep, both, balanced);
+
+    join(left, node, right, balanced)
+}
+
+
+// Counts the keys which come before `label` and `key_tail` in alphabetical order, skipping whole subtrees thanks
+// to their value counts.
+
+fn rank_r<T>(link: &Link<T>, label: char, key_tail: &mut Chars) -> usize {
+
+    match *link {
+
+        None => 0,
+
+        Some(ref node) => match label.cmp(&node.label) {
+
+            Less => rank_r(&node.left, label, key_tail),
+
+            Greater => count(&node.left) + node.value.iter().count() + count(&node.middle) + rank_r(&node.right, label, key_tail),
+
+            Equal => {
+
+                let before = count(&node.left);
+
+                for run_label in node.run.chars() {
+
+                    match key_tail.next() {
+
+                        None => return before,
+
+                        Some(label) => match label.cmp(&run_label) {
+
+                            Less => return before,
+
+                            Greater => return before + node.value.iter().count() + count(&node.middle),
+
+                            Equal => ()
+                        }
+                    }
+                }
+
+                match key_tail.next() {
+
+                    None => before,
+
+                    Some(label) => before + node.value.iter().count() + rank_r(&node.middle, label, key_tail)
+                }
+            }
+        }
+    }
+}
+
+
+// Walks down to the value of rank `n`, and builds its key on the way.
+
+fn select_r<'a, T>(link: &'a Link<T>, mut n: usize, key: &mut String) -> Option<&'a T> {
+
+    match *link {
+
+        None => None,
+
+        Some(ref node) => {
+
+            let left = count(&node.left);
+
+            if n < left {
+
+                return select_r(&node.left, n, key);
+            }
+
+            n -= left;
+
+            let key_len = key.len();
+
+            key.push(node.label);
+            key.push_str(&node.run);
+
+            if let Some(ref value) = node.value {
+
+                if n == 0 {
+
+                    return Some(value);
+                }
+
+                n -= 1;
+            }
+
+            let middle = count(&node.middle);
+
+            if n < middle {
+
+                return select_r(&node.middle, n, key);
+            }
+
+            key.truncate(key_len);
+
+            select_r(&node.right, n - middle, key)
+        }
+    }
+}
+
+
+/// How nodes are distributed. See [Stats]( ./struct.Stats.html) for a brief description.
+
+#[derive(Default,PartialEq,Debug)]
+pub struct DistStat { pub matches: usize, pub sides: usize, pub depth: usize }
+
+
+/// How long are the keys. See [Stats]( ./struct.Stats.html) for a brief description.
+
+#[derive(Default,PartialEq,Debug)]
+pub struct KeyLenStat { pub min: usize, pub max: usize }
+
+
+/// How many nodes and values are in the tree. See [Stats]( ./struct.Stats.html) for a brief description.
+
+#[derive(Default,PartialEq,Debug)]
+pub struct CountStat { pub nodes:usize, pub values: usize }
+
+
+/// Memory used by the tree. See [Stats]( ./struct.Stats.html) for a brief description.
+
+#[derive(Default,PartialEq,Debug)]
+pub struct BytesStat { pub node: usize, pub total: usize, pub counts: usize }
+
+
+/// Contains various metrics describing the tree: its nodes, keys and values. Mostly used for tuning and debugging
+/// purpose.
+/// * `dist[n].matches` number of values reached by traversing _n_ `middle` links (the number of keys of length
+/// _n_)
+/// * `dist[n].sides` number of values reached by traversing _n_ `left` or `middle` links (those links may indicate
+/// that the tree is not well balanced)
+/// * `dist[n].depth` number of values whose total depth (`middle`, `left` and `right` links) is _n_
+/// * `key_len.min` length of the shortest key inserted in the tree
+/// * `key_len.max` length of the longest key inserted in the tree
+/// * `count.nodes` total number of nodes in the tree
+/// * `count.values` number of nodes which store a value (same as [len]( ./struct.Tst.html#method.len))
+/// * `bytes.node` byte size of a node (including the fixed size of a value, but excluding heap allocated memory of
+/// this value)
+/// * `bytes.total` total number of bytes allocated for nodes (`count.nodes` * `bytes.node`) and their runs of
+/// characters. A node points to its run through a single pointer (8 bytes on x64, whether it has a run or not), and
+/// a node which has a run owns a second allocation holding it (16 bytes on x64, plus the bytes of the run)
+/// * `bytes.counts` number of bytes used by nodes to count the values of their subtree (already included in
+/// `bytes.total`), which [count_prefix]( ./struct.Tst.html#method.count_prefix) relies on
+
+#[derive(Default,PartialEq,Debug)]
+#[allow(clippy::doc_lazy_continuation)]
+pub struct Stats {
+
+    pub dist: Vec<DistStat>,
+    pub key_len: KeyLenStat,
+    pub count: CountStat,
+    pub bytes: BytesStat,
+}
+
+
+#[allow(clippy::let_and_return)]
+fn stat_r<T>(stats: Stats, link: &Link<T>, matches: usize, sides: usize, depth: usize) -> Stats {
+
+    match *link {
+
+        None => stats,
+
+        Some(ref node) => {
+
+            let mut stats = stat_r(stats, &node.left, matches, sides+1, depth+1);
+
+            stats.count.nodes+=1;
+            stats.bytes.total+=node.run.bytes();
+            stats.bytes.counts+=mem::size_of::<usize>();
+
+            let run_len = node.run.chars().count();
+
+            if node.value.is_some() {
+
+                let matches = matches + 1 + run_len;
+                let depth = depth + 1 + run_len;
+
+                while stats.dist.len() <= depth {
+
+                    stats.dist.push(DistStat { matches: 0, sides: 0, depth: 0 });
+                }
+
+                stats.dist[matches].matches+=1;
+                stats.dist[sides].sides+=1;
+                stats.dist[depth].depth+=1;
+
+                if stats.key_len.min == 0 || matches < stats.key_len.min {
+
+                    stats.key_len.min = matches;
+                }
+
+                if matches > stats.key_len.max {
+
+                    stats.key_len.max = matches;
+                }
+
+                stats.count.values+=1;
+            }
+
+            let stats = stat_r(stats, &node.middle, matches+1+run_len, sides, depth+1+run_len);
+            let stats = stat_r(stats, &node.right, matches, sides+1, depth+1);
+
+            stats
+        }
+    }
+}
+
+
+// Where the values completing a prefix are found: either in all nodes reachable from a link, or, when the prefix
+// ends inside the run of a node, in this very node (its value and its middle child, but not its siblings). In the
+// later case, the node label and the first characters of its run are the last `usize` characters of the prefix.
+
+enum CompletionRoot<'a, T: 'a> {
+
+    Link(&'a Link<T>),
+    Inside(&'a Node<T>),
+    Empty
+}
+
+
+enum CompletionRootMut<'a, T: 'a> {
+
+    Link(&'a mut Link<T>),
+    Inside(&'a mut Node<T>),
+    Empty
+}
+
+
+fn find_complete_root_r<'a, T>(link: &'a Link<T>, label: char, mut key_tail: Chars) -> CompletionRoot<'a, T> {
+
+    match *link {
+
+        None => CompletionRoot::Link(link),
+
+        Some(ref node) => match label.cmp(&node.label) {
+
+            Less => find_complete_root_r(&node.left, label, key_tail),
+
+            Greater => find_complete_root_r(&node.right, label, key_tail),
+
+            Equal => {
+
+                let matched = match_run(&node.run, &mut key_tail);
+                let new_label = key_tail.next();
+
+                if matched < node.run.chars().count() {
+
+                    return match new_label {
+
+                        None => CompletionRoot::Inside(node),
+
+                        Some(_) => CompletionRoot::Empty
+                    };
+                }
+
+                match new_label {
+
+                    None => CompletionRoot::Link(&node.middle),
+
+                    Some(label) => find_complete_root_r(&node.middle, label, key_tail)
+                }
+            }
+        }
+    }
+}
+
+
+fn find_complete_root_r_mut<'a, T>(link: &'a mut Link<T>, label: char, mut key_tail: Chars) -> CompletionRootMut<'a, T> {
+
+    match *link {
+
+        None => CompletionRootMut::Link(link),
+
+        Some(ref mut node) => match label.cmp(&node.label) {
+
+            Less => find_complete_root_r_mut(&mut node.left, label, key_tail),
+
+            Greater => find_complete_root_r_mut(&mut node.right, label, key_tail),
+
+            Equal => {
+
+                let matched = match_run(&node.run, &mut key_tail);
+                let new_label = key_tail.next();
+
+                if matched < node.run.chars().count() {
+
+                    return match new_label {
+
+                        None => CompletionRootMut::Inside(node),
+
+                        Some(_) => CompletionRootMut::Empty
+                    };
+                }
+
+                match new_label {
+
+                    None => CompletionRootMut::Link(&mut node.middle),
+
+                    Some(label) => find_complete_root_r_mut(&mut node.middle, label, key_tail)
+                }
+            }
+        }
+    }
+}
+
+
+#[allow(clippy::needless_return)]
+fn visit_values_r<T, C>(link: &Link<T>, callback: &mut C)
+where C: FnMut (&T) {
+
+    match *link {
+
+        None => return,
+
+        Some(ref node) => {
+
+            visit_values_r(&node.left, callback);
+
+            if let Some(ref value) = node.value {
+
+                callback(value);
+            }
+
+            visit_values_r(&node.middle, callback);
+            visit_values_r(&node.right, callback);
+        }
+    }
+}
+
+
+#[allow(clippy::needless_return)]
+fn visit_values_r_mut<T, C>(link: &mut Link<T>, callback: &mut C)
+where C: FnMut (&mut T) {
+
+    match *link {
+
+        None => return,
+
+        Some(ref mut node) => {
+
+            visit_values_r_mut(&mut node.left, callback);
+
+            if let Some(ref mut value) = node.value {
+
+                callback(value);
+            }
+
+            visit_values_r_mut(&mut node.middle, callback);
+            visit_values_r_mut(&mut node.right, callback);
+        }
+    }
+}
+
+
+fn visit_complete_values_r<T, C>(root: CompletionRoot<T>, callback: &mut C)
+where C: FnMut (&T) {
+
+    match root {
+
+        CompletionRoot::Empty => (),
+
+        CompletionRoot::Link(link) => visit_values_r(link, callback),
+
+        CompletionRoot::Inside(node) => {
+
+            if let Some(ref value) = node.value {
+
+                callback(value);
+            }
+
+            visit_values_r(&node.middle, callback);
+        }
+    }
+}
+
+
+fn visit_complete_values_r_mut<T, C>(root: CompletionRootMut<T>, callback: &mut C)
+where C: FnMut (&mut T) {
+
+    match root {
+
+        CompletionRootMut::Empty => (),
+
+        CompletionRootMut::Link(link) => visit_values_r_mut(link, callback),
+
+        CompletionRootMut::Inside(node) => {
+
+            if let Some(ref mut value) = node.value {
+
+                callback(value);
+            }
+
+            visit_values_r_mut(&mut node.middle, callback);
+        }
+    }
+}
+
+
+// Matches `run` against the next characters of the key, where each mismatch (or missing key character) costs one.
+// Returns the range left after the run, or `None` if the run is out of range.
+
+fn neighbor_run(run: &str, key_tail: &mut Chars, tail_len: &mut usize, range: usize) -> Option<usize> {
+
+    let mut range = range;
+
+    for c in run.chars() {
+
+        let delta = if key_tail.next() == Some(c) { 0 } else { 1 };
+
+        if delta > range {
+
+            return None;
+        }
+
+        range -= delta;
+
+        if *tail_len > 0 {
+
+            *tail_len -= 1;
+        }
+    }
+
+    Some(range)
+}
+
+
+#[allow(clippy::needless_lifetimes)]
+fn visit_neighbor_values_r<'a, T, C>(link: &'a Link<T>, label: Option<char>, key_tail: &mut Chars, tail_len: usize, range: usize, callback: &mut C)
+where C: FnMut (&T) {
+
+    if range == 0 {
+
+        if let Some(label) = label {
+
+            if let Some(value) = get_r(link, label, key_tail) {
+
+                callback(value);
+            }
+        }
+
+    } else {
+
+        if let Some(ref node) = *link {
+
+            visit_neighbor_values_r(&node.left, label, key_tail, tail_len, range, callback);
+
+            let new_range = match label {
+
+                None => range-1,
+
+                Some(label) => if label==node.label { range } else { range-1 }
+            };
+
+            let mut new_tail = key_tail.clone();
+            let mut new_len = tail_len;
+
+            if let Some(new_range) = neighbor_run(&node.run, &mut new_tail, &mut new_len, new_range) {
+
+                if let Some(ref value) = node.value {
+
+                    if new_len <= new_range {
+
+                        callback(value);
+                    }
+                }
+
+                let new_label = new_tail.next();
+
+                let new_len = if new_len > 0 { new_len-1 } else { new_len };
+
+                visit_neighbor_values_r(&node.middle, new_label, &mut new_tail, new_len, new_range, callback);
+            }
+
+            visit_neighbor_values_r(&node.right, label, key_tail, tail_len, range, callback);
+        }
+    }
+}
+
+
+#[allow(clippy::needless_lifetimes)]
+fn visit_neighbor_values_r_mut<'a, T, C>(link: &'a mut Link<T>, label: Option<char>, key_tail: &mut Chars, tail_len: usize, range: usize, callback: &mut C)
+where C: FnMut (&mut T) {
+
+    if range == 0 {
+
+        if let Some(label) = label {
+
+            if let Some(value) = get_r_mut(link, label, key_tail) {
+
+                callback(value);
+            }
+        }
+
+    } else {
+
+        if let Some(ref mut node) = *link {
+
+            visit_neighbor_values_r_mut(&mut node.left, label, key_tail, tail_len, range, callback);
+
+            let new_range = match label {
+
+                None => range-1,
+
+                Some(label) => if label == node.label { range } else { range-1 }
+            };
+
+            let mut new_tail = key_tail.clone();
+            let mut new_len = tail_len;
+
+            if let Some(new_range) = neighbor_run(&node.run, &mut new_tail, &mut new_len, new_range) {
+
+                if let Some(ref mut value) = node.value {
+
+                    if new_len <= new_range {
+
+                        callback(value);
+                    }
+                }
+
+                let new_label = new_tail.next();
+
+                let new_len = if new_len > 0 { new_len-1 } else { new_len };
+
+                visit_neighbor_values_r_mut(&mut node.middle, new_label, &mut new_tail, new_len, new_range, callback);
+            }
+
+            visit_neighbor_values_r_mut(&mut node.right, label, key_tail, tail_len, range, callback);
+        }
+    }
+}
+
+
+// Matches `run` against the next characters of the pattern, and tells if they all matched.
+
+fn crossword_run(run: &str, key_tail: &mut Chars, joker: char) -> bool {
+
+    for c in run.chars() {
+
+        match key_tail.next() {
+
+            Some(label) if label == joker || label == c => continue,
+
+            _ => return false
+        }
+    }
+
+    true
+}
+
+
+#[allow(clippy::needless_lifetimes, clippy::needless_return)]
+fn visit_crossword_values_r<'a, T, C>(link: &'a Link<T>, label: char, key_tail: &mut Chars, joker: char, callback: &mut C)
+    where C: FnMut (&T) {
+
+    match *link {
+
+        None => return,
+
+        Some(ref node) => {
+
+            if label == joker || label < node.label {
+
+                visit_crossword_values_r(&node.left, label, key_tail, joker, callback);
+            }
+
+            if label == joker || label == node.label {
+
+                let mut new_tail = key_tail.clone();
+
+                if crossword_run(&node.run, &mut new_tail, joker) {
+
+                    let new_label = new_tail.next();
+
+                    match new_label {
+
+                        None =>  if let Some(ref value) = node.value {
+
+                            callback(value);
+                        },
+
+                        Some(label) => visit_crossword_values_r(&node.middle, label, &mut new_tail, joker, callback)
+                    }
+                }
+            }
+
+            if label == joker || label > node.label {
+
+                visit_crossword_values_r(&node.right, label, key_tail, joker, callback);
+            }
+        }
+    }
+}
+
+
+#[allow(clippy::needless_lifetimes, clippy::needless_return)]
+fn visit_crossword_values_r_mut<'a, T, C>(link: &'a mut Link<T>, label: char, key_tail: &mut Chars, joker: char, callback: &mut C)
+    where C: FnMut (&mut T) {
+
+    match *link {
+
+        None => return,
+
+        Some(ref mut node) => {
+
+            if label == joker || label < node.label {
+
+                visit_crossword_values_r_mut(&mut node.left, label, key_tail, joker, callback);
+            }
+
+            if label == joker || label == node.label {
+
+                let mut new_tail = key_tail.clone();
+
+                if crossword_run(&node.run, &mut new_tail, joker) {
+
+                    let new_label = new_tail.next();
+
+                    match new_label {
+
+                        None =>  if let Some(ref mut value) = node.value {
+
+                            callback(value);
+                        },
+
+                        Some(label) => visit_crossword_values_r_mut(&mut node.middle, label, &mut new_tail, joker, callback)
+                    }
+                }
+            }
+
+            if label == joker || label > node.label {
+
+                visit_crossword_values_r_mut(&mut node.right, label, key_tail, joker, callback);
+            }
+        }
+    }
+}
+
+
+#[allow(clippy::needless_lifetimes, clippy::needless_return, clippy::borrowed_box, clippy::manual_map,
+        clippy::needless_borrowed_reference)]
+fn pretty_print_r<'a, T>(link: &'a Link<T>, ids: &mut Tst<usize>, writer: &mut dyn Write) {
+
+    match *link {
+
+        None => return,
+
+        Some(ref node) => {
+
+            let value_box = match node.value {
+
+                None => "☐", Some(_) => "☑"
+            };
+
+            {
+                let mut get_id = |node: &Box<Node<T>>| {
+
+                    let node_addr = format!("{:p}", node);
+
+                    let prev_id = match ids.get(&node_addr) {
+
+                        None => None,
+
+                        Some(id) => Some(*id)
+                    };
+
+                    match prev_id {
+
+                        None => {
+
+                            let id = ids.len();
+                            ids.insert(&node_addr, id);
+                            id
+                        }
+
+                        Some(id) => id
+                    }
+                };
+
+                let _ = writeln!(writer, r#"N{} [label=<<TABLE BORDER="0" CELLBORDER="1" CELLSPACING="0"><TR><TD COLSPAN="3">{} {}{}</TD></TR><TR><TD PORT="l"></TD><TD PORT="m"></TD><TD PORT="r"></TD></TR></TABLE>>]"#, get_id(node), value_box, node.label, node.run);
+
+                let mut print_edge = |link, start, style| if let &Some(ref child) = link {
+
+                    let _ = writeln!(writer, r#"N{}:{} -> N{} [style={}]"#, get_id(node), start, get_id(child), style);
+                };
+
+                print_edge(&node.left, "l", "solid");
+                print_edge(&node.middle, "m", "bold");
+                print_edge(&node.right, "r", "solid");
+            }
 
             pretty_print_r(&node.left, ids, writer);
             pretty_print_r(&node.middle, ids, writer);
@@ -848,6 +2273,15 @@ fn pretty_print_r<'a, T>(link: &'a Link<T>, ids: &mut Tst<usize>, writer: &mut d
 }
 
 
+impl<T> Default for Tst<T> {
+
+    fn default() -> Self {
+
+        Tst::new()
+    }
+}
+
+
 impl<T> Tst<T> {
 
     /// Create a new, empty `Tst`. The key is always a string slice and one needs only to provide a value
@@ -869,7 +2303,43 @@ impl<T> Tst<T> {
 
     pub fn new() -> Self {
 
-        Tst { root: None, count: 0 }
+        Tst { root: None, count: 0, balanced: false }
+    }
+
+
+    /// Create a new, empty and self-balancing `Tst`. In such a tree, `left` and `right` links of nodes sharing the
+    /// same key prefix form an [AVL tree]( http://en.wikipedia.org/wiki/AVL_tree), which is kept balanced by
+    /// [`insert`]( ./struct.Tst.html#method.insert) and [`remove`]( ./struct.Tst.html#method.remove) through
+    /// rotations. The search of each key character is then guaranteed to be logarithmic in the number of sibling
+    /// characters, whatever the insertion order of keys (which is often sorted).
+    ///
+    /// ```
+    /// # use ternary_tree::Tst;
+    /// let mut map = Tst::new_balanced();
+    ///
+    /// for key in &["a", "b", "c", "d", "e", "f", "g"] {
+    ///     map.insert(key, *key);
+    /// }
+    ///
+    /// let stats = map.stat();
+    /// assert_eq!(stats.count.nodes, 7);
+    /// assert_eq!(stats.dist.len(), 4);
+    /// ```
+    ///
+    /// Balancing has a small cost on each insertion and removal, but no cost at all on searches and iterations.
+
+    pub fn new_balanced() -> Self {
+
+        Tst { root: None, count: 0, balanced: true }
+    }
+
+
+    /// Returns `true` if the tree was created with [`new_balanced`]( ./struct.Tst.html#method.new_balanced) and
+    /// keeps its sibling links balanced.
+
+    pub fn is_balanced(&self) -> bool {
+
+        self.balanced
     }
 
 
@@ -912,7 +2382,7 @@ impl<T> Tst<T> {
 
             Some(label) => {
 
-                let old_value = insert_r(&mut self.root, label, key_tail, value);
+                let old_value = insert_r(&mut self.root, label, key_tail, value, self.balanced);
 
                 if old_value.is_none() {
 
@@ -997,7 +2467,14 @@ impl<T> Tst<T> {
 
             None => (false, None),
 
-            Some(label) => remove_r(&mut self.root, label, &mut key_tail)
+            Some(label) => if self.balanced {
+
+                (false, remove_balanced_r(&mut self.root, label, &mut key_tail))
+
+            } else {
+
+                remove_r(&mut self.root, label, &mut key_tail)
+            }
         };
 
         if prune {
@@ -1031,6 +2508,104 @@ impl<T> Tst<T> {
     }
 
 
+    /// Returns `true` if the tree stores no value.
+    ///
+    /// ```
+    /// # use ternary_tree::Tst;
+    /// let mut map = Tst::new();
+    /// assert_eq!(map.is_empty(), true);
+    ///
+    /// map.insert("foo", "🍄🍄");
+    /// assert_eq!(map.is_empty(), false);
+    /// ```
+
+    pub fn is_empty(&self) -> bool {
+
+        self.count == 0
+    }
+
+
+    /// Returns the number of values whose key begins with `prefix`, which is the number of values [`iter_complete`](
+    /// ./struct.Tst.html#method.iter_complete) would return. Each node keeps count of the values below it, so this
+    /// takes time proportional to the length of `prefix`, whatever the number of values.
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let map = tst!["foo" => "🍄🍄", "bar" => "🐟", "baz" => "㵅"];
+    ///
+    /// assert_eq!(map.count_prefix("ba"), 2);
+    /// assert_eq!(map.count_prefix("foo"), 0);
+    /// assert_eq!(map.count_prefix(""), 3);
+    /// ```
+
+    pub fn count_prefix(&self, prefix: &str) -> usize {
+
+        let mut prefix_tail = prefix.chars();
+
+        match prefix_tail.next() {
+
+            None => self.count,
+
+            Some(label) => match find_complete_root_r(&self.root, label, prefix_tail) {
+
+                CompletionRoot::Link(link) => count(link),
+
+                CompletionRoot::Inside(node) => node.value.iter().count() + count(&node.middle),
+
+                CompletionRoot::Empty => 0
+            }
+        }
+    }
+
+
+    /// Returns the number of keys which come before `key` in alphabetical order (`key` itself does not need to be
+    /// in the tree). This is the position `key` has, or would have, in the sequence of [`iter`](
+    /// ./struct.Tst.html#method.iter). Subtrees are skipped thanks to the values count of their nodes, so this
+    /// takes time proportional to the length of `key`, not to the number of keys before it.
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let map = tst!["foo" => "🍄🍄", "bar" => "🐟", "baz" => "㵅"];
+    ///
+    /// assert_eq!(map.rank("bar"), 0);
+    /// assert_eq!(map.rank("foo"), 2);
+    /// assert_eq!(map.rank("bat"), 1);
+    /// assert_eq!(map.rank("zoo"), 3);
+    /// ```
+
+    pub fn rank(&self, key: &str) -> usize {
+
+        let mut key_tail = key.chars();
+
+        match key_tail.next() {
+
+            None => 0,
+
+            Some(label) => rank_r(&self.root, label, &mut key_tail)
+        }
+    }
+
+
+    /// Returns the key and the value found at position `n` (starting at 0) in alphabetical order of keys, or `None`
+    /// if the tree holds `n` values or less. Like [`rank`]( ./struct.Tst.html#method.rank), this does not walk
+    /// over the keys before position `n`.
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let map = tst!["foo" => "🍄🍄", "bar" => "🐟", "baz" => "㵅"];
+    ///
+    /// assert_eq!(map.select(1), Some(("baz".to_string(), &"㵅")));
+    /// assert_eq!(map.select(3), None);
+    /// ```
+
+    pub fn select(&self, n: usize) -> Option<(String, &T)> {
+
+        let mut key = String::new();
+
+        select_r(&self.root, n, &mut key).map(|value| (key, value))
+    }
+
+
     /// Walks the tree, gathers various metrics about nodes, keys and values, and returns a [`Stats`](
     /// ./struct.Stats.html) structure to sum it up.
     ///
@@ -1042,8 +2617,9 @@ impl<T> Tst<T> {
     /// map.insert("foo", "🍄🍄");
     /// assert_eq!(map.len(), 1);
     ///
+    /// //"foo" has no branching, so its three chars fit in a single node
     /// let stats = map.stat();
-    /// assert_eq!(stats.count.nodes, 3);
+    /// assert_eq!(stats.count.nodes, 1);
     /// ```
     ///
     /// See [Stats]( ./struct.Stats.html) for a detailed description of available fields.
@@ -1055,7 +2631,7 @@ impl<T> Tst<T> {
         let mut stats = stat_r(empty_stats, &self.root, 0, 0, 0);
 
         stats.bytes.node = mem::size_of::<Node<T>>();
-        stats.bytes.total = mem::size_of::<Tst<T>>()+stats.count.nodes*stats.bytes.node;
+        stats.bytes.total += mem::size_of::<Tst<T>>()+stats.count.nodes*stats.bytes.node;
 
         stats
     }
@@ -1176,8 +2752,8 @@ impl<T> Tst<T> {
 
             Some(label) => {
 
-                let mut new_root = find_complete_root_r_mut(&mut self.root, label, prefix_tail);
-                visit_complete_values_r_mut(&mut new_root, &mut callback)
+                let new_root = find_complete_root_r_mut(&mut self.root, label, prefix_tail);
+                visit_complete_values_r_mut(new_root, &mut callback)
             }
         }
     }
@@ -1272,6 +2848,7 @@ impl<T> Tst<T> {
     ///
     /// An empty `pattern` is meaningless, and does not find any value.
 
+    #[allow(clippy::needless_return)]
     pub fn visit_crossword_values<C>(&self, pattern: &str, joker: char, mut callback: C)
     where C: FnMut (&T) {
 
@@ -1291,6 +2868,7 @@ impl<T> Tst<T> {
     /// ./struct.Tst.html#method.visit_crossword_values), except the `_mut` version works on mutable values, and
     /// does not have an iterator counterpart.
 
+    #[allow(clippy::needless_return)]
     pub fn visit_crossword_values_mut<C>(&mut self, pattern: &str, joker: char, mut callback: C)
     where C: FnMut (&mut T) {
 
@@ -1350,7 +2928,8 @@ impl<T> Tst<T> {
     /// assert_eq!((last_key, last_value), ("foo".to_string(), Some(&"🍄🍄")));
     /// ```
 
-    pub fn iter(&self) -> TstIterator<T> {
+    #[allow(clippy::needless_borrow)]
+    pub fn iter(&self) -> TstIterator<'_, T> {
 
         TstIterator::<T>::new(&self)
     }
@@ -1384,32 +2963,29 @@ impl<T> Tst<T> {
     /// assert_eq!((last_key, last_value), ("baz".to_string(), Some(&"㵅")));
     /// ```
 
-    pub fn iter_complete(&self, prefix: &str) -> TstCompleteIterator<T> {
+    #[allow(clippy::needless_borrow)]
+    pub fn iter_complete(&self, prefix: &str) -> TstCompleteIterator<'_, T> {
 
         TstCompleteIterator::<T>::new(&self, prefix)
     }
 
 
     /// Create a [double-ended]( http://doc.rust-lang.org/std/iter/trait.DoubleEndedIterator.html) iterator which
-    /// successively returns all values whose key is _close_ to `key`. A key is considered _close_ to `key` within
-    /// a [Hamming distance]( http://en.wikipedia.org/wiki/Hamming_distance) of `range` from `key`. An empty `key`
-    /// is allowed, and with a `range` of _n_, it will find all values whose key length is up to _n_. Values are
-    /// immutable, and are found in alphabetical order of keys by [`next`](
-    /// ./struct.TstNeighborIterator.html#method.next), and in the opposite order by [`next_back`](
-    /// ./struct.TstNeighborIterator.html#method.next_back). Methods [`current_key`](
-    /// ./struct.TstNeighborIterator.html#method.current_key) and [`current_key_back`](
-    /// ./struct.TstNeighborIterator.html#method.current_key_back) regenerate the key associated with the last
-    /// value returned by [`next`]( ./struct.TstNeighborIterator.html#method.next) or [`next_back`](
-    /// struct.TstNeighborIterator.html#method.next_back). See also the [`visit_neighbor_value_mut`](
-    /// ./struct.Tst.html#method.visit_neighbor_values_mut) method which produces the same sequence of mutable
-    /// values.
+    /// successively returns all values whose key lies inside `range`, such as `"a".."c"`, `"b"..="d"` or `"c"..`.
+    /// Both ends of the iterator walk down directly to their bound, without going over the keys before it. Values
+    /// are immutable, and are found in alphabetical order of keys by [`next`](
+    /// ./struct.TstRangeIterator.html#method.next), and in the opposite order by [`next_back`](
+    /// ./struct.TstRangeIterator.html#method.next_back). Methods [`current_key`](
+    /// ./struct.TstRangeIterator.html#method.current_key) and [`current_key_back`](
+    /// ./struct.TstRangeIterator.html#method.current_key_back) regenerate the key associated with the last value
+    /// returned by [`next`]( ./struct.TstRangeIterator.html#method.next) or [`next_back`](
+    /// struct.TstRangeIterator.html#method.next_back).
     ///
     /// ```
-    /// # use ternary_tree::Tst;
     /// # use ternary_tree::tst;
-    /// let map = tst!["foo" => "🍄🍄", "bar" => "🐟", "baz" => "㵅"];
+    /// let map = tst!["foo" => "🍄🍄", "bar" => "🐟", "baz" => "㵅", "qux" => "🦆"];
     ///
-    /// let mut it = map.iter_neighbor("bar", 1);
+    /// let mut it = map.range("bat".."qux");
     ///
     /// let first_value = it.next();
     /// let last_value = it.next_back();
@@ -1417,905 +2993,1406 @@ impl<T> Tst<T> {
     /// let first_key = it.current_key();
     /// let last_key = it.current_key_back();
     ///
-    /// assert_eq!((first_key, first_value), ("bar".to_string(), Some(&"🐟")));
-    /// assert_eq!((last_key, last_value), ("baz".to_string(), Some(&"㵅")));
+    /// assert_eq!((first_key, first_value), ("baz".to_string(), Some(&"㵅")));
+    /// assert_eq!((last_key, last_value), ("foo".to_string(), Some(&"🍄🍄")));
+    /// assert_eq!(it.next(), None);
     /// ```
 
-    pub fn iter_neighbor<'a, 'b>(&'a self, key: &'b str, range: usize) -> TstNeighborIterator<'a, 'b, T> {
+    pub fn range<'a, 'b, R>(&'a self, range: R) -> TstRangeIterator<'a, T>
+    where R: RangeBounds<&'b str> {
 
-        TstNeighborIterator::<T>::new(&self, key, range)
+        TstRangeIterator::<T>::new(self, range)
     }
 
 
-    /// Create a [double-ended]( http://doc.rust-lang.org/std/iter/trait.DoubleEndedIterator.html) iterator which
-    /// successively returns all values whose key _matches_ `pattern`. The `pattern` is a string slice where each
-    /// `joker` character stands for _any_ character. A `pattern` of _n_ `joker` characters will find all values
-    /// whose key length is exactly _n_. Values are immutable, and are found in alphabetical order of keys by
-    /// [`next`]( ./struct.TstCrosswordIterator.html#method.next), and in the opposite order by [`next_back`](
-    /// ./struct.TstCrosswordIterator.html#method.next_back). Methods [`current_key`](
-    /// ./struct.TstCrosswordIterator.html#method.current_key) and [`current_key_back`](
-    /// ./struct.TstCrosswordIterator.html#method.current_key_back) regenerate the key associated with the last
-    /// value returned by [`next`]( ./struct.TstCrosswordIterator.html#method.next) or [`next_back`](
-    /// struct.TstCrosswordIterator.html#method.next_back). See also the [`visit_crossword_value_mut`](
-    /// ./struct.Tst.html#method.visit_crossword_values_mut) method which produces the same sequence of mutable
-    /// values.
+    /// Returns the first key greater than or equal to `key`, with its value, or `None`. The tree is walked down
+    /// once, along `key`, then along the leftmost path of the first subtree found after it.
     ///
     /// ```
-    /// # use ternary_tree::Tst;
     /// # use ternary_tree::tst;
     /// let map = tst!["foo" => "🍄🍄", "bar" => "🐟", "baz" => "㵅"];
     ///
-    /// let mut it = map.iter_crossword("?a?", '?');
+    /// assert_eq!(map.ceiling("bat"), Some(("baz".to_string(), &"㵅")));
+    /// assert_eq!(map.ceiling("baz"), Some(("baz".to_string(), &"㵅")));
+    /// assert_eq!(map.ceiling("fop"), None);
+    /// ```
+
+    pub fn ceiling(&self, key: &str) -> Option<(String, &T)> {
+
+        let mut it = self.range((Bound::Included(key), Bound::Unbounded));
+
+        it.next().map(|value| (it.current_key(), value))
+    }
+
+
+    /// Returns the last key less than or equal to `key`, with its value, or `None`. See [`ceiling`](
+    /// ./struct.Tst.html#method.ceiling).
     ///
-    /// let first_value = it.next();
-    /// let last_value = it.next_back();
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let map = tst!["foo" => "🍄🍄", "bar" => "🐟", "baz" => "㵅"];
     ///
-    /// let first_key = it.current_key();
-    /// let last_key = it.current_key_back();
+    /// assert_eq!(map.floor("bat"), Some(("bar".to_string(), &"🐟")));
+    /// assert_eq!(map.floor("baz"), Some(("baz".to_string(), &"㵅")));
+    /// assert_eq!(map.floor("bar"), Some(("bar".to_string(), &"🐟")));
+    /// assert_eq!(map.floor("ba"), None);
+    /// ```
+
+    pub fn floor(&self, key: &str) -> Option<(String, &T)> {
+
+        let mut it = self.range((Bound::Unbounded, Bound::Included(key)));
+
+        it.next_back().map(|value| (it.current_key_back(), value))
+    }
+
+
+    /// Returns the first key strictly greater than `key`, with its value, or `None`. See [`ceiling`](
+    /// ./struct.Tst.html#method.ceiling).
     ///
-    /// assert_eq!((first_key, first_value), ("bar".to_string(), Some(&"🐟")));
-    /// assert_eq!((last_key, last_value), ("baz".to_string(), Some(&"㵅")));
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let map = tst!["foo" => "🍄🍄", "bar" => "🐟", "baz" => "㵅"];
+    ///
+    /// assert_eq!(map.successor("bar"), Some(("baz".to_string(), &"㵅")));
+    /// assert_eq!(map.successor("foo"), None);
     /// ```
 
-    pub fn iter_crossword<'a, 'b>(&'a self, pattern: &'b str, joker: char) -> TstCrosswordIterator<'a, 'b, T> {
+    pub fn successor(&self, key: &str) -> Option<(String, &T)> {
 
-        TstCrosswordIterator::<T>::new(&self, pattern, joker)
+        let mut it = self.range((Bound::Excluded(key), Bound::Unbounded));
+
+        it.next().map(|value| (it.current_key(), value))
     }
-}
 
 
-/// A shortcut macro to help create a small tree with a list of known `"key" => value` pairs. Calls [`insert`](
-/// ./struct.Tst.html#method.insert) on each pair, in order.
-///
-/// ```
-/// # use ternary_tree::Tst;
-/// # use ternary_tree::tst;
-/// let map = tst!["fo" => "🍄", "bar" => "🐟", "baz" => "㵅", "fooo" => "🍄🍄🍄"];
-/// assert_eq!(map.len(), 4)
-/// ````
+    /// Returns the last key strictly less than `key`, with its value, or `None`. See [`ceiling`](
+    /// ./struct.Tst.html#method.ceiling).
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let map = tst!["foo" => "🍄🍄", "bar" => "🐟", "baz" => "㵅"];
+    ///
+    /// assert_eq!(map.predecessor("baz"), Some(("bar".to_string(), &"🐟")));
+    /// assert_eq!(map.predecessor("bar"), None);
+    /// ```
 
-#[macro_export]
-macro_rules! tst {
+    pub fn predecessor(&self, key: &str) -> Option<(String, &T)> {
 
-    () => {{
-        $crate::Tst::new()
-    }};
+        let mut it = self.range((Bound::Unbounded, Bound::Excluded(key)));
 
-    ($($key:expr => $value:expr,)+) => (tst!($($key => $value),+));
+        it.next_back().map(|value| (it.current_key_back(), value))
+    }
 
-    ($($key: expr => $val: expr),*) => {{
 
-        let mut tst = $crate::Tst::new();
-        $(
-            tst.insert($key, $val);
-        )*
+    /// Keeps only the values for which `keep` returns `true`, and removes all the others. The tree is walked once,
+    /// in alphabetical order of keys, and `keep` is given each key along with its mutable value. Nodes left empty
+    /// are pruned on the way back up.
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let mut map = tst!["foo" => 1, "bar" => 2, "baz" => 3];
+    ///
+    /// map.retain(|key, value| { *value *= 10; key.starts_with('b') });
+    ///
+    /// assert_eq!(map.len(), 2);
+    /// assert_eq!(map.get("foo"), None);
+    /// assert_eq!(map.get("baz"), Some(&30));
+    /// ```
 
-        tst
-    }};
-}
+    pub fn retain<F>(&mut self, mut keep: F)
+    where F: FnMut(&str, &mut T) -> bool {
 
+        let mut count = 0;
 
-#[derive(Debug, PartialEq)]
-enum TstIteratorAction {
+        retain_r(&mut self.root, &mut String::new(), &mut keep, &mut |_, _| count += 1, self.balanced);
 
-    GoLeft,
-    Visit,
-    GoMiddle,
-    GoRight
-}
+        self.count -= count;
+    }
 
-use self::TstIteratorAction::*;
 
+    /// Removes all the values for which `filter` returns `true`, and returns them with their keys, in alphabetical
+    /// order. This is the opposite of [`retain`]( ./struct.Tst.html#method.retain), and the tree is walked once
+    /// too. This is not a lazy iterator: all matching values are removed and collected before the method returns.
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let mut map = tst!["foo" => 1, "bar" => 2, "baz" => 3];
+    ///
+    /// let extracted = map.extract_matching(|_, value| *value > 1);
+    ///
+    /// assert_eq!(extracted, vec![("bar".to_string(), 2), ("baz".to_string(), 3)]);
+    /// assert_eq!(map.len(), 1);
+    /// ```
 
-/// A [double-ended]( http://doc.rust-lang.org/std/iter/trait.DoubleEndedIterator.html) iterator which
-/// successively returns all values of the tree. See [`iter`]( struct.Tst.html#method.iter) method for a brief
-/// description with a short example.
+    pub fn extract_matching<F>(&mut self, mut filter: F) -> Vec<(String, T)>
+    where F: FnMut(&str, &mut T) -> bool {
 
-#[derive(Debug)]
-pub struct TstIterator<'a, T: 'a> {
+        let mut extracted = Vec::new();
 
-    todo_i: Vec<(&'a Node<T>, TstIteratorAction)>,
-    last_i: Option<&'a Node<T>>,
+        {
+            let mut keep = |key: &str, value: &mut T| !filter(key, value);
 
-    todo_j: Vec<(&'a Node<T>, TstIteratorAction)>,
-    last_j: Option<&'a Node<T>>
-}
+            retain_r(&mut self.root, &mut String::new(), &mut keep,
+                     &mut |key: &str, value| extracted.push((key.to_string(), value)), self.balanced);
+        }
 
+        self.count -= extracted.len();
 
-macro_rules! gen_it_path {
+        extracted
+    }
 
-    ($path_of_x:ident, $todo_x:ident, $a1:expr, $a2:expr) => (
 
-        pub fn $path_of_x(&self) -> String {
+    /// Removes all the values whose key begins with `prefix` (those [`iter_complete`](
+    /// ./struct.Tst.html#method.iter_complete) would return), and returns them in a new tree, where `prefix` is
+    /// stripped from their keys. The whole subtree is detached at once, not value by value. The value of `prefix`
+    /// itself, if any, stays in the tree, and an empty `prefix` takes all the values.
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let mut map = tst!["user:42:name" => "Ann", "user:42:mail" => "ann@example.org", "user:7:name" => "Bob"];
+    ///
+    /// let user = map.remove_prefix("user:42:");
+    ///
+    /// assert_eq!(map.len(), 1);
+    /// assert_eq!(user.len(), 2);
+    /// assert_eq!(user.get("name"), Some(&"Ann"));
+    /// ```
 
-            let mut path = String::new();
+    pub fn remove_prefix(&mut self, prefix: &str) -> Tst<T> {
 
-            for todo in self.$todo_x.iter() {
+        let mut prefix_tail = prefix.chars();
 
-                if todo.1 == $a1 || todo.1 == $a2 {
+        let root = match prefix_tail.next() {
 
-                    path.push(todo.0.label);
-                }
-            }
+            None => self.root.take(),
 
-            path
-        }
-    );
-}
+            Some(label) => remove_prefix_r(&mut self.root, label, prefix_tail, self.balanced)
+        };
 
+        let count = count(&root);
 
-impl<'a, T> TstIterator<'a, T> {
+        self.count -= count;
 
-    pub fn new(tst: &'a Tst<T>) -> Self {
+        Tst { root, count, balanced: self.balanced }
+    }
+
+
+    /// Same as [`remove_prefix`]( ./struct.Tst.html#method.remove_prefix), except that keys of the new tree keep
+    /// their `prefix`. The detached subtree is grafted below a single node holding `prefix`.
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let mut map = tst!["user:42:name" => "Ann", "user:7:name" => "Bob"];
+    ///
+    /// let user = map.remove_prefix_keeping("user:42:");
+    ///
+    /// assert_eq!(user.get("user:42:name"), Some(&"Ann"));
+    /// assert_eq!(user.get("name"), None);
+    /// ```
+
+    pub fn remove_prefix_keeping(&mut self, prefix: &str) -> Tst<T> {
+
+        let mut tst = self.remove_prefix(prefix);
+
+        tst.root = prepend_prefix(prefix, tst.root.take());
+
+        tst
+    }
+
+
+    /// Moves all the values whose key begins with `from` (those [`remove_prefix`](
+    /// ./struct.Tst.html#method.remove_prefix) would take) below `to`, so that their keys begin with `to` instead.
+    /// The subtree below `from` is detached and grafted below `to` as a whole. When a moved key is already in the
+    /// tree, the moved value replaces the existing one, which is returned with its key, in alphabetical order.
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let mut map = tst!["project/old/a" => 1, "project/old/b" => 2, "project/new/b" => 20];
+    ///
+    /// let collisions = map.rename_prefix("project/old/", "project/new/");
+    ///
+    /// assert_eq!(collisions, vec![("project/new/b".to_string(), 20)]);
+    /// assert_eq!(map.len(), 2);
+    /// assert_eq!(map.get("project/new/a"), Some(&1));
+    /// assert_eq!(map.get("project/new/b"), Some(&2));
+    /// ```
+
+    pub fn rename_prefix(&mut self, from: &str, to: &str) -> Vec<(String, T)> {
+
+        let mut collisions = Vec::new();
+
+        self.rename_prefix_with(from, to, |key, value, moved_value| {
+
+            collisions.push((key.to_string(), value));
+            moved_value
+        });
+
+        collisions
+    }
+
+
+    /// Same as [`rename_prefix`]( ./struct.Tst.html#method.rename_prefix), except that collisions are resolved by
+    /// `combine`, which is given the key, the existing value and the moved value, and returns the value to keep.
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let mut map = tst!["project/old/a" => 1, "project/old/b" => 2, "project/new/b" => 20];
+    ///
+    /// map.rename_prefix_with("project/old/", "project/new/", |_, value, moved_value| value + moved_value);
+    ///
+    /// assert_eq!(map.get("project/new/b"), Some(&22));
+    /// ```
+
+    pub fn rename_prefix_with<F>(&mut self, from: &str, to: &str, mut combine: F)
+    where F: FnMut(&str, T, T) -> T {
 
-        TstIterator::new_from_root(&tst.root)
+        let moved = self.remove_prefix(from);
+        let moved_root = prepend_prefix(to, moved.root);
+
+        self.root = merge_r(self.root.take(), moved_root, &mut String::new(), (true, true),
+                            &mut |key, value, moved_value| Some(combine(key, value, moved_value)), self.balanced);
+        self.count = count(&self.root);
     }
 
 
-    fn new_from_root(root: &'a Link<T>) -> Self {
+    /// Splits the tree in two at `key`: values whose key is equal to or greater than `key` are moved to the returned
+    /// tree, and the lower ones are kept. Only the nodes along the path of `key` are cut, whole subtrees on both
+    /// sides are moved as they are.
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let mut map = tst!["aa" => 1, "ab" => 2, "b" => 3, "ba" => 4];
+    ///
+    /// let high = map.split_off("ab");
+    ///
+    /// assert_eq!(map.len(), 1);
+    /// assert_eq!(high.len(), 3);
+    /// assert_eq!(map.get("aa"), Some(&1));
+    /// assert_eq!(high.get("ab"), Some(&2));
+    /// ```
+
+    pub fn split_off(&mut self, key: &str) -> Tst<T> {
+
+        let mut key_tail = key.chars();
+
+        let (low, high) = match key_tail.next() {
 
-        let mut it = TstIterator {
+            None => (None, self.root.take()),
 
-            todo_i: Vec::new(), last_i: None,
-            todo_j: Vec::new(), last_j: None,
+            Some(label) => split_r(self.root.take(), label, key_tail, self.balanced)
         };
 
-        if let Some(ref node) = root {
+        self.root = low;
+        self.count = count(&self.root);
+
+        Tst { count: count(&high), root: high, balanced: self.balanced }
+    }
+
+
+    /// Moves all values of `other` into this tree, leaving `other` empty. When a key is in both trees, the value of
+    /// `other` replaces the one of this tree. Subtrees whose keys are found in only one tree are grafted as they are,
+    /// so that appending trees with distinct key ranges (or prefixes) does not walk their values. This is not the
+    /// case if only this tree is balanced: values of `other` are then inserted one by one.
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let mut map = tst!["aa" => 1, "ab" => 2];
+    /// let mut other = tst!["ab" => 20, "b" => 30];
+    ///
+    /// map.append(&mut other);
+    ///
+    /// assert_eq!(map.len(), 3);
+    /// assert_eq!(other.len(), 0);
+    /// assert_eq!(map.get("ab"), Some(&20));
+    /// ```
+
+    pub fn append(&mut self, other: &mut Tst<T>) {
+
+        if self.balanced && !other.balanced {
+
+            for (key, value) in other.extract_matching(|_, _| true) {
+
+                self.insert(&key, value);
+            }
 
-            it.todo_i.push((node, GoLeft));
-            it.todo_j.push((node, GoRight));
+            return;
         }
 
-        it
+        let other_root = other.root.take();
+
+        other.count = 0;
+
+        self.root = merge_r(self.root.take(), other_root, &mut String::new(), (true, true),
+                            &mut |_, _, other_value| Some(other_value), self.balanced);
+        self.count = count(&self.root);
     }
 
 
-    gen_it_path!(current_key, todo_i, GoMiddle, GoRight);
-    gen_it_path!(current_key_back, todo_j, Visit, GoLeft);
-}
+    /// Returns a tree with the values of both trees. When a key is in both trees, `combine` is given this key, the
+    /// value of this tree and the one of `other`, and returns the value to keep (keys are given in alphabetical
+    /// order). Both trees are walked at once, and subtrees found in only one of them are moved as they are. The
+    /// result is balanced if both trees are.
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let user = tst!["color" => 2, "colour" => 1];
+    /// let global = tst!["color" => 10, "flavor" => 5];
+    ///
+    /// let merged = user.union(global, |_, user, global| user + global);
+    ///
+    /// assert_eq!(merged.len(), 3);
+    /// assert_eq!(merged.get("color"), Some(&12));
+    /// assert_eq!(merged.get("flavor"), Some(&5));
+    /// ```
 
+    pub fn union<F>(self, other: Tst<T>, mut combine: F) -> Tst<T>
+    where F: FnMut(&str, T, T) -> T {
 
-impl<'a, T> Iterator for TstIterator<'a, T> {
+        self.merge(other, (true, true), &mut |key, value, other_value| Some(combine(key, value, other_value)))
+    }
 
-    type Item = &'a T;
 
-    fn next(&mut self) -> Option<&'a T> {
+    /// Returns a tree with the keys found in both trees, whose values are given by `combine`, as with [`union`](
+    /// ./struct.Tst.html#method.union). Subtrees found in only one tree are dropped without being walked.
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let english = tst!["chat" => 1, "pain" => 2, "bread" => 3];
+    /// let french = tst!["chat" => 10, "pain" => 20, "chien" => 30];
+    ///
+    /// let both = english.intersection(french, |_, english, french| english.max(french));
+    ///
+    /// assert_eq!(both.len(), 2);
+    /// assert_eq!(both.get("pain"), Some(&20));
+    /// ```
+
+    pub fn intersection<F>(self, other: Tst<T>, mut combine: F) -> Tst<T>
+    where F: FnMut(&str, T, T) -> T {
+
+        self.merge(other, (false, false), &mut |key, value, other_value| Some(combine(key, value, other_value)))
+    }
+
+
+    /// Returns a tree with the values of this tree whose key is not in `other`.
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let english = tst!["chat" => 1, "pain" => 2, "bread" => 3];
+    /// let french = tst!["chat" => 10, "pain" => 20, "chien" => 30];
+    ///
+    /// let only_english = english.difference(french);
+    ///
+    /// assert_eq!(only_english.len(), 1);
+    /// assert_eq!(only_english.get("bread"), Some(&3));
+    /// ```
+
+    pub fn difference(self, other: Tst<T>) -> Tst<T> {
 
-        let mut found = None;
+        self.merge(other, (true, false), &mut |_, _, _| None)
+    }
+
+
+    /// Returns a tree with the values whose key is in one tree, but not in both.
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let english = tst!["chat" => 1, "pain" => 2, "bread" => 3];
+    /// let french = tst!["chat" => 10, "pain" => 20, "chien" => 30];
+    ///
+    /// let either = english.symmetric_difference(french);
+    ///
+    /// assert_eq!(either.len(), 2);
+    /// assert_eq!(either.get("chien"), Some(&30));
+    /// ```
 
-        while let Some((node, action)) = self.todo_i.pop() {
+    pub fn symmetric_difference(self, other: Tst<T>) -> Tst<T> {
 
-            match action {
+        self.merge(other, (true, true), &mut |_, _, _| None)
+    }
 
-                GoLeft => {
 
-                    self.todo_i.push((node, Visit));
+    fn merge<F>(self, other: Tst<T>, keep: (bool, bool), both: &mut F) -> Tst<T>
+    where F: FnMut(&str, T, T) -> Option<T> {
 
-                    if let Some(ref child) = node.left {
+        let balanced = self.balanced && other.balanced;
+        let root = merge_r(self.root, other.root, &mut String::new(), keep, both, balanced);
 
-                        self.todo_i.push((child, GoLeft));
-                    }
-                }
+        Tst { count: count(&root), root, balanced }
+    }
 
-                Visit => {
 
-                    if node.value.is_some() {
+    /// Returns the first key in alphabetical order, with its value, or `None` if the tree is empty.
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let map = tst!["foo" => "🍄🍄", "bar" => "🐟", "baz" => "㵅"];
+    ///
+    /// assert_eq!(map.first_key_value(), Some(("bar".to_string(), &"🐟")));
+    /// ```
 
-                        if let Some(node_j) = self.last_j {
+    pub fn first_key_value(&self) -> Option<(String, &T)> {
 
-                            if ptr::eq(node, node_j) {
+        let mut it = self.iter();
 
-                                self.todo_i.clear();
-                                self.todo_j.clear();
+        it.next().map(|value| (it.current_key(), value))
+    }
 
-                                found = None;
-                                break;
-                            }
-                        }
-                    }
 
-                    self.todo_i.push((node, GoMiddle));
+    /// Returns the last key in alphabetical order, with its value, or `None` if the tree is empty.
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let map = tst!["foo" => "🍄🍄", "bar" => "🐟", "baz" => "㵅"];
+    ///
+    /// assert_eq!(map.last_key_value(), Some(("foo".to_string(), &"🍄🍄")));
+    /// ```
 
-                    if let Some(ref value) = node.value {
+    pub fn last_key_value(&self) -> Option<(String, &T)> {
 
-                        self.last_i = Some(node);
-                        found = Some(value);
+        let mut it = self.iter();
 
-                        break;
-                    }
-                }
+        it.next_back().map(|value| (it.current_key_back(), value))
+    }
 
-                GoMiddle => {
 
-                    self.todo_i.push((node, GoRight));
+    /// Removes the first key in alphabetical order from the tree, and returns it with its value, or `None` if the
+    /// tree is empty. Nodes left empty are pruned, as with [`remove`]( ./struct.Tst.html#method.remove).
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let mut map = tst!["foo" => "🍄🍄", "bar" => "🐟", "baz" => "㵅"];
+    ///
+    /// assert_eq!(map.pop_first(), Some(("bar".to_string(), "🐟")));
+    /// assert_eq!(map.pop_first(), Some(("baz".to_string(), "㵅")));
+    /// assert_eq!(map.len(), 1);
+    /// ```
 
-                    if let Some(ref child) = node.middle {
+    pub fn pop_first(&mut self) -> Option<(String, T)> {
 
-                        self.todo_i.push((child, GoLeft));
-                    }
-                }
+        self.pop(true)
+    }
 
-                GoRight => {
 
-                    if let Some(ref child) = node.right {
+    /// Removes the last key in alphabetical order from the tree, and returns it with its value, or `None` if the
+    /// tree is empty. See [`pop_first`]( ./struct.Tst.html#method.pop_first).
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let mut map = tst!["foo" => "🍄🍄", "bar" => "🐟", "baz" => "㵅"];
+    ///
+    /// assert_eq!(map.pop_last(), Some(("foo".to_string(), "🍄🍄")));
+    /// assert_eq!(map.len(), 2);
+    /// ```
 
-                        self.todo_i.push((child, GoLeft));
-                    }
-                }
-            }
-        }
+    pub fn pop_last(&mut self) -> Option<(String, T)> {
 
-        found
+        self.pop(false)
     }
-}
 
 
-impl<'a, T> IntoIterator for &'a Tst<T> {
+    fn pop(&mut self, first: bool) -> Option<(String, T)> {
 
-    type Item = &'a T;
-    type IntoIter = TstIterator<'a, T>;
+        let mut key = String::new();
+        let value = pop_r(&mut self.root, &mut key, first, self.balanced)?;
 
-    fn into_iter(self) -> Self::IntoIter {
+        self.count -= 1;
 
-        self.iter()
+        Some((key, value))
     }
-}
 
 
-impl<'a, T> DoubleEndedIterator for TstIterator<'a, T> {
+    /// Create a [double-ended]( http://doc.rust-lang.org/std/iter/trait.DoubleEndedIterator.html) iterator which
+    /// successively returns all values whose key is _close_ to `key`. A key is considered _close_ to `key` within
+    /// a [Hamming distance]( http://en.wikipedia.org/wiki/Hamming_distance) of `range` from `key`. An empty `key`
+    /// is allowed, and with a `range` of _n_, it will find all values whose key length is up to _n_. Values are
+    /// immutable, and are found in alphabetical order of keys by [`next`](
+    /// ./struct.TstNeighborIterator.html#method.next), and in the opposite order by [`next_back`](
+    /// ./struct.TstNeighborIterator.html#method.next_back). Methods [`current_key`](
+    /// ./struct.TstNeighborIterator.html#method.current_key) and [`current_key_back`](
+    /// ./struct.TstNeighborIterator.html#method.current_key_back) regenerate the key associated with the last
+    /// value returned by [`next`]( ./struct.TstNeighborIterator.html#method.next) or [`next_back`](
+    /// struct.TstNeighborIterator.html#method.next_back). See also the [`visit_neighbor_value_mut`](
+    /// ./struct.Tst.html#method.visit_neighbor_values_mut) method which produces the same sequence of mutable
+    /// values.
+    ///
+    /// ```
+    /// # use ternary_tree::Tst;
+    /// # use ternary_tree::tst;
+    /// let map = tst!["foo" => "🍄🍄", "bar" => "🐟", "baz" => "㵅"];
+    ///
+    /// let mut it = map.iter_neighbor("bar", 1);
+    ///
+    /// let first_value = it.next();
+    /// let last_value = it.next_back();
+    ///
+    /// let first_key = it.current_key();
+    /// let last_key = it.current_key_back();
+    ///
+    /// assert_eq!((first_key, first_value), ("bar".to_string(), Some(&"🐟")));
+    /// assert_eq!((last_key, last_value), ("baz".to_string(), Some(&"㵅")));
+    /// ```
 
-    fn next_back(&mut self) -> Option<&'a T> {
+    #[allow(clippy::needless_borrow)]
+    pub fn iter_neighbor<'a, 'b>(&'a self, key: &'b str, range: usize) -> TstNeighborIterator<'a, 'b, T> {
 
-        let mut found = None;
+        TstNeighborIterator::<T>::new(&self, key, range)
+    }
 
-        while let Some((node, action)) = self.todo_j.pop() {
 
-            match action {
+    /// Create a [double-ended]( http://doc.rust-lang.org/std/iter/trait.DoubleEndedIterator.html) iterator which
+    /// successively returns all values whose key _matches_ `pattern`. The `pattern` is a string slice where each
+    /// `joker` character stands for _any_ character. A `pattern` of _n_ `joker` characters will find all values
+    /// whose key length is exactly _n_. Values are immutable, and are found in alphabetical order of keys by
+    /// [`next`]( ./struct.TstCrosswordIterator.html#method.next), and in the opposite order by [`next_back`](
+    /// ./struct.TstCrosswordIterator.html#method.next_back). Methods [`current_key`](
+    /// ./struct.TstCrosswordIterator.html#method.current_key) and [`current_key_back`](
+    /// ./struct.TstCrosswordIterator.html#method.current_key_back) regenerate the key associated with the last
+    /// value returned by [`next`]( ./struct.TstCrosswordIterator.html#method.next) or [`next_back`](
+    /// struct.TstCrosswordIterator.html#method.next_back). See also the [`visit_crossword_value_mut`](
+    /// ./struct.Tst.html#method.visit_crossword_values_mut) method which produces the same sequence of mutable
+    /// values.
+    ///
+    /// ```
+    /// # use ternary_tree::Tst;
+    /// # use ternary_tree::tst;
+    /// let map = tst!["foo" => "🍄🍄", "bar" => "🐟", "baz" => "㵅"];
+    ///
+    /// let mut it = map.iter_crossword("?a?", '?');
+    ///
+    /// let first_value = it.next();
+    /// let last_value = it.next_back();
+    ///
+    /// let first_key = it.current_key();
+    /// let last_key = it.current_key_back();
+    ///
+    /// assert_eq!((first_key, first_value), ("bar".to_string(), Some(&"🐟")));
+    /// assert_eq!((last_key, last_value), ("baz".to_string(), Some(&"㵅")));
+    /// ```
 
-                GoRight => {
+    #[allow(clippy::needless_borrow)]
+    pub fn iter_crossword<'a, 'b>(&'a self, pattern: &'b str, joker: char) -> TstCrosswordIterator<'a, 'b, T> {
 
-                    self.todo_j.push((node, GoMiddle));
+        TstCrosswordIterator::<T>::new(&self, pattern, joker)
+    }
 
-                    if let Some(ref child) = node.right {
 
-                        self.todo_j.push((child, GoRight));
-                    }
-                }
+    /// Create an iterator which successively returns all values whose key begins with a string _close_ to `prefix`:
+    /// at most `max_edits` insertions, deletions or substitutions of characters away from it. This helps to
+    /// complete a mistyped prefix. Values are immutable, and are found in alphabetical order of keys. A key is only
+    /// found once, even when several of its beginnings are close to `prefix`. Method [`current_key`](
+    /// ./struct.TstFuzzyCompleteIterator.html#method.current_key) regenerates the key associated with the last
+    /// value returned by [`next`]( ./struct.TstFuzzyCompleteIterator.html#method.next), and [`current_distance`](
+    /// ./struct.TstFuzzyCompleteIterator.html#method.current_distance) gives the edit distance between `prefix` and
+    /// the closest beginning of this key.
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let map = tst!["python3" => "🐍", "pythonic" => "✨", "pyramid" => "🔺"];
+    ///
+    /// let mut it = map.iter_fuzzy_complete("pyhton", 2);
+    ///
+    /// assert_eq!(it.next(), Some(&"🐍"));
+    /// assert_eq!((it.current_key(), it.current_distance()), ("python3".to_string(), 2));
+    ///
+    /// assert_eq!(it.next(), Some(&"✨"));
+    /// assert_eq!((it.current_key(), it.current_distance()), ("pythonic".to_string(), 2));
+    ///
+    /// assert_eq!(it.next(), None);
+    /// ```
+    ///
+    /// Unlike [`iter_complete`]( ./struct.Tst.html#method.iter_complete), a key close enough to `prefix` is found
+    /// too, since the user may have typed it all.
 
-                Visit => {
+    pub fn iter_fuzzy_complete<'a>(&'a self, prefix: &str, max_edits: usize) -> TstFuzzyCompleteIterator<'a, T> {
 
-                    if node.value.is_some() {
+        TstFuzzyCompleteIterator::<T>::new(self, prefix, max_edits)
+    }
 
-                        if let Some(node_i) = self.last_i {
 
-                            if ptr::eq(node, node_i) {
+    /// Create an iterator which successively returns the differences between this tree (the old version) and
+    /// `other` (the new version), in alphabetical order of keys: [`TstDiff::Added`]( ./enum.TstDiff.html) for a key
+    /// only found in `other`, `Removed` for a key only found in this tree, and `Changed` for a key whose value is not
+    /// the same in both trees. Keys with equal values are skipped.
+    ///
+    /// The nodes of both trees are walked together, character after character of the keys, whatever the shape of
+    /// each tree (balanced or not, with runs of characters split at different places). When a character leads to
+    /// nodes in one tree only, all the values below it are returned without looking at the other tree any more.
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// # use ternary_tree::TstDiff;
+    /// let old = tst!["bar" => 1, "baz" => 2, "foo" => 3];
+    /// let new = tst!["bar" => 1, "baz" => 20, "qux" => 4];
+    ///
+    /// let diff: Vec<_> = old.diff(&new).collect();
+    ///
+    /// assert_eq!(diff, vec![TstDiff::Changed("baz".to_string(), &2, &20),
+    ///                       TstDiff::Removed("foo".to_string(), &3),
+    ///                       TstDiff::Added("qux".to_string(), &4)]);
+    /// ```
 
-                                self.todo_i.clear();
-                                self.todo_j.clear();
+    pub fn diff<'a>(&'a self, other: &'a Tst<T>) -> TstDiffIterator<'a, T> where T: PartialEq {
 
-                                found = None;
-                                break;
-                            }
-                        }
-                    }
+        TstDiffIterator::<T>::new(self, other)
+    }
 
-                    self.todo_j.push((node, GoLeft));
 
-                    if let Some(ref value) = node.value {
+    /// Consumes the tree and turns it into a read-only [`FrozenTst`]( ./struct.FrozenTst.html), whose nodes are laid
+    /// out in a compact flat array. Use it for dictionaries which are built once and then only queried: searches
+    /// and iterations are the same, but faster, and the tree needs much less memory.
+    ///
+    /// A frozen node has no room for a run of characters, so each compressed node of the tree is expanded back
+    /// into one frozen node per character of its run. The frozen tree may thus count more nodes than the tree, but
+    /// each of them is several times smaller.
+    ///
+    /// ```
+    /// # use ternary_tree::Tst;
+    /// # use ternary_tree::tst;
+    /// let map = tst!["foo" => "🍄🍄", "bar" => "🐟", "baz" => "㵅"];
+    ///
+    /// let frozen = map.freeze();
+    ///
+    /// let mut it = frozen.iter_crossword("?a?", '?');
+    /// assert_eq!(it.next(), Some(&"🐟"));
+    /// assert_eq!(it.current_key(), "bar");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Frozen nodes are indexed with 32-bit integers. Panics if the frozen tree would need `u32::MAX` nodes or
+    /// more, once runs are expanded.
 
-                        self.last_j = Some(node);
-                        found = Some(value);
+    pub fn freeze(self) -> FrozenTst<T> {
 
-                        break;
-                    }
-                }
+        frozen::freeze(self)
+    }
 
-                GoMiddle => {
 
-                    self.todo_j.push((node, Visit));
+    /// Writes the tree to `writer`, in a compact binary format which keeps the structure of the nodes, so that
+    /// [`read_from`]( ./struct.Tst.html#method.read_from) can load it back without comparing any key. Values are
+    /// encoded with their [`ValueCodec`]( ./trait.ValueCodec.html) implementation.
+    ///
+    /// The file starts with a versioned header, and its content is protected by a checksum.
+    ///
+    /// ```
+    /// # use ternary_tree::Tst;
+    /// # use ternary_tree::tst;
+    /// let map = tst!["foo" => 1, "bar" => 2, "baz" => 3];
+    ///
+    /// let mut bytes = Vec::new();
+    /// map.write_to(&mut bytes).unwrap();
+    ///
+    /// let loaded: Tst<u32> = Tst::read_from(&mut bytes.as_slice()).unwrap();
+    /// assert_eq!(loaded.len(), 3);
+    /// assert_eq!(loaded.get("bar"), Some(&2));
+    /// ```
 
-                    if let Some(ref child) = node.middle {
+    pub fn write_to(&self, writer: &mut dyn Write) -> io::Result<()> where T: ValueCodec {
 
-                        self.todo_j.push((child, GoRight));
-                    }
-                }
+        binary::write_to(self, writer)
+    }
 
-                GoLeft => {
 
-                    if let Some(ref child) = node.left {
+    /// Reads a tree written by [`write_to`]( ./struct.Tst.html#method.write_to). Loading is a linear scan of the
+    /// nodes, which are rebuilt in the same shape (balanced or not) as the saved tree.
+    ///
+    /// Returns an error of kind `InvalidData` if the header is not recognized, if the checksum does not match, or if
+    /// a node or a value can not be decoded.
+    ///
+    /// ```
+    /// # use ternary_tree::Tst;
+    /// let mut bytes = b"not a tree".to_vec();
+    /// bytes.resize(64, 0);
+    ///
+    /// let loaded = Tst::<u32>::read_from(&mut bytes.as_slice());
+    /// assert_eq!(loaded.is_err(), true);
+    /// ```
 
-                        self.todo_j.push((child, GoRight));
-                    }
-                }
-            }
-        }
+    pub fn read_from(reader: &mut dyn Read) -> io::Result<Tst<T>> where T: ValueCodec {
 
-        found
+        binary::read_from(reader)
     }
-}
 
 
-/// A [double-ended]( http://doc.rust-lang.org/std/iter/trait.DoubleEndedIterator.html) iterator which
-/// successively returns all values whose key begins with `prefix`. See [`iter_complete`](
-/// struct.Tst.html#method.iter_complete) method for a brief description with a short example.
+    /// Reads a word list, with one key per line, and inserts each key with the value returned by `value` for its
+    /// line number (starting at 1). Line terminators (`\n` or `\r\n`) are removed, and empty lines are skipped.
+    /// Returns the number of keys read, or a [`TextError`]( ./enum.TextError.html) giving the line and column of a
+    /// line which is not valid utf-8. Keys read before an error stay in the tree.
+    ///
+    /// ```
+    /// # use ternary_tree::Tst;
+    /// let mut map = Tst::new();
+    ///
+    /// let count = map.read_words(&mut "foo\nbar\n\nbaz\n".as_bytes(), |line| line).unwrap();
+    /// assert_eq!(count, 3);
+    /// assert_eq!(map.get("baz"), Some(&4));
+    ///
+    /// let mut set = Tst::new();
+    /// set.read_words(&mut "foo\r\nbar".as_bytes(), |_| ()).unwrap();
+    /// assert_eq!(set.get("foo"), Some(&()));
+    /// ```
 
-#[derive(Debug)]
-pub struct TstCompleteIterator<'a, T: 'a> {
+    pub fn read_words<F>(&mut self, reader: &mut dyn BufRead, value: F) -> Result<usize, TextError>
+    where F: FnMut(usize) -> T {
 
-    it: TstIterator<'a, T>,
-    prefix: String
-}
+        text::read_words(self, reader, value)
+    }
 
 
-impl<'a, T> TstCompleteIterator<'a, T> {
+    /// Reads `key<TAB>value` lines, and inserts each key with the value returned by `parse` for the text after the
+    /// first tab. Empty lines are skipped. Returns the number of pairs read, or a [`TextError`](
+    /// ./enum.TextError.html) giving the line and column of the first bad line: a missing tab, an empty key, or a
+    /// value rejected by `parse` (the error message is the one returned by `parse`). Keys read before an error stay
+    /// in the tree.
+    ///
+    /// ```
+    /// # use ternary_tree::Tst;
+    /// let mut map: Tst<u32> = Tst::new();
+    ///
+    /// let count = map.read_tsv(&mut "foo\t1\nbar\t2\n".as_bytes(), |s| s.parse::<u32>()).unwrap();
+    /// assert_eq!(count, 2);
+    /// assert_eq!(map.get("bar"), Some(&2));
+    ///
+    /// let err = map.read_tsv(&mut "baz\t3\nqux\tfour\n".as_bytes(), |s| s.parse::<u32>()).unwrap_err();
+    /// assert_eq!(err.to_string(), "line 2, column 5: invalid digit found in string");
+    /// ```
 
-    pub fn new(tst: &'a Tst<T>, key_prefix: &str) -> Self {
+    pub fn read_tsv<F, E>(&mut self, reader: &mut dyn BufRead, parse: F) -> Result<usize, TextError>
+    where F: FnMut(&str) -> Result<T, E>, E: fmt::Display {
 
-        let mut key_tail = key_prefix.chars();
+        text::read_tsv(self, reader, parse)
+    }
 
-        TstCompleteIterator {
 
-            it : match key_tail.next() {
+    /// Reads JSON Lines, where each line is an object like `{"key": "foo", "value": 42}`, and inserts each key with
+    /// the value returned by `parse` for the JSON text of the value member. Other members are ignored, and empty
+    /// lines are skipped. Returns the number of pairs read, or a [`TextError`]( ./enum.TextError.html) giving the
+    /// line and column of the first bad line. Keys read before an error stay in the tree.
+    ///
+    /// ```
+    /// # use ternary_tree::Tst;
+    /// let mut map: Tst<String> = Tst::new();
+    ///
+    /// let lines = "{\"key\": \"foo\", \"value\": [1, 2]}\n{\"value\": null, \"key\": \"b\\u00e4r\"}\n";
+    ///
+    /// map.read_json_lines(&mut lines.as_bytes(), |json| Ok::<_, String>(json.to_string())).unwrap();
+    /// assert_eq!(map.get("foo"), Some(&"[1, 2]".to_string()));
+    /// assert_eq!(map.get("bär"), Some(&"null".to_string()));
+    /// ```
 
-                None => TstIterator::<T>::new(tst),
+    pub fn read_json_lines<F, E>(&mut self, reader: &mut dyn BufRead, parse: F) -> Result<usize, TextError>
+    where F: FnMut(&str) -> Result<T, E>, E: fmt::Display {
 
-                Some(label) => {
+        text::read_json_lines(self, reader, parse)
+    }
 
-                    let new_root = find_complete_root_r(&tst.root, label, key_tail);
-                    TstIterator::<T>::new_from_root(new_root)
-                }
-            },
 
-            prefix: key_prefix.to_string()
-        }
+    /// Writes all keys to `writer`, in order, one per line. Fails with an error of kind `InvalidInput` if a key
+    /// holds a line break.
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let map = tst!["foo" => 1, "bar" => 2];
+    ///
+    /// let mut out = Vec::new();
+    /// map.write_words(&mut out).unwrap();
+    /// assert_eq!(out, b"bar\nfoo\n");
+    /// ```
+
+    pub fn write_words(&self, writer: &mut dyn Write) -> io::Result<()> {
+
+        text::write_words(self, writer)
     }
 
 
-    pub fn current_key(&self) -> String {
+    /// Writes all keys and values to `writer`, in key order, as `key<TAB>value` lines where the value is given by
+    /// `format`. Fails with an error of kind `InvalidInput` if a key holds a tab or a line break, or if a formatted
+    /// value holds a line break.
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let map = tst!["foo" => 1, "bar" => 2];
+    ///
+    /// let mut out = Vec::new();
+    /// map.write_tsv(&mut out, |v| v.to_string()).unwrap();
+    /// assert_eq!(out, b"bar\t2\nfoo\t1\n");
+    /// ```
 
-        self.prefix.clone() + &self.it.current_key()
+    pub fn write_tsv<F>(&self, writer: &mut dyn Write, format: F) -> io::Result<()> where F: FnMut(&T) -> String {
+
+        text::write_tsv(self, writer, format)
     }
 
 
-    pub fn current_key_back(&self) -> String {
+    /// Writes all keys and values to `writer`, in key order, as JSON Lines which [`read_json_lines`](
+    /// ./struct.Tst.html#method.read_json_lines) can read back. `format` must turn a value into JSON text, without
+    /// line break.
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let map = tst!["foo" => 1, "b\"r" => 2];
+    ///
+    /// let mut out = Vec::new();
+    /// map.write_json_lines(&mut out, |v| v.to_string()).unwrap();
+    /// assert_eq!(out, &b"{\"key\":\"b\\\"r\",\"value\":2}\n{\"key\":\"foo\",\"value\":1}\n"[..]);
+    /// ```
 
-        self.prefix.clone() + &self.it.current_key_back()
-    }
-}
+    pub fn write_json_lines<F>(&self, writer: &mut dyn Write, format: F) -> io::Result<()>
+    where F: FnMut(&T) -> String {
 
+        text::write_json_lines(self, writer, format)
+    }
 
-impl<'a, T> Iterator for TstCompleteIterator<'a, T> {
 
-    type Item = &'a T;
+    /// Create a parallel iterator which returns all values of the tree, from several threads of the `rayon` thread
+    /// pool. Unlike [`iter`]( ./struct.Tst.html#method.iter), values do not come in alphabetical order of keys.
+    /// Requires the `rayon` feature.
+    ///
+    /// ```
+    /// # extern crate ternary_tree;
+    /// # extern crate rayon;
+    /// # use ternary_tree::tst;
+    /// use rayon::prelude::*;
+    ///
+    /// # fn main() {
+    ///
+    /// let map = tst!["foo" => 1, "bar" => 2, "baz" => 3];
+    ///
+    /// assert_eq!(map.par_iter().sum::<i32>(), 6);
+    /// # }
+    /// ```
 
-    fn next(&mut self) -> Option<&'a T> {
+    #[cfg(feature = "rayon")]
+    pub fn par_iter<'a>(&'a self) -> impl rayon::iter::ParallelIterator<Item = &'a T> + 'a
+    where T: Sync {
 
-        self.it.next()
+        parallel::par_iter(self)
     }
-}
 
 
-impl<'a, T> DoubleEndedIterator for TstCompleteIterator<'a, T> {
+    /// Walks the tree and calls `callback` closure on each mutable value, handing disjoint subtrees to separate
+    /// threads. See [`visit_values_mut`]( ./struct.Tst.html#method.visit_values_mut). Values are not visited in
+    /// any particular order. Requires the `rayon` feature.
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let mut map = tst!["foo" => 1, "bar" => 2, "baz" => 3];
+    ///
+    /// map.par_visit_values_mut(|v| *v *= 10);
+    /// assert_eq!(map.get("baz"), Some(&30));
+    /// ```
 
-    fn next_back(&mut self) -> Option<&'a T> {
+    #[cfg(feature = "rayon")]
+    pub fn par_visit_values_mut<C>(&mut self, callback: C)
+    where T: Send, C: Fn(&mut T) + Sync {
 
-       self.it.next_back()
+        parallel::par_visit_values_mut(self, callback)
     }
-}
 
 
-/// A [double-ended]( http://doc.rust-lang.org/std/iter/trait.DoubleEndedIterator.html) iterator which
-/// successively returns all values whose key is _close_ to `key`. See [`iter_neighbor`](
-/// struct.Tst.html#method.iter_neighbor) method for a brief description with a short example.
+    /// Same as [`stat`]( ./struct.Tst.html#method.stat), but the top of the tree is walked by several threads
+    /// which sum up the metrics of their subtrees. Requires the `rayon` feature.
 
-#[derive(Debug)]
-pub struct TstNeighborIterator<'a, 'b, T: 'a> {
+    #[cfg(feature = "rayon")]
+    pub fn par_stat(&self) -> Stats
+    where T: Sync {
 
-    todo_i: Vec<(&'a Node<T>, TstIteratorAction, Option<char>, Chars<'b>, usize, usize)>,
-    last_i: Option<&'a Node<T>>,
+        parallel::par_stat(self)
+    }
 
-    todo_j: Vec<(&'a Node<T>, TstIteratorAction, Option<char>, Chars<'b>, usize, usize)>,
-    last_j: Option<&'a Node<T>>
-}
 
+    /// Builds a tree from `entries`, which should be sorted by key. The median entry of each group of siblings
+    /// becomes their root, so that the tree is well balanced, and groups which do not overlap are built on
+    /// separate threads. Unsorted entries are sorted first, and when a key is repeated, its last value is kept, as
+    /// with successive calls to [`insert`]( ./struct.Tst.html#method.insert). Empty keys are ignored. Requires the
+    /// `rayon` feature.
+    ///
+    /// ```
+    /// # use ternary_tree::Tst;
+    /// let entries = vec![("bar".to_string(), 2), ("baz".to_string(), 3), ("foo".to_string(), 1)];
+    ///
+    /// let map = Tst::par_from_sorted(entries);
+    ///
+    /// assert_eq!(map.len(), 3);
+    /// assert_eq!(map.get("baz"), Some(&3));
+    /// ```
 
-impl<'a, 'b, T> TstNeighborIterator<'a, 'b, T> {
+    #[cfg(feature = "rayon")]
+    pub fn par_from_sorted(entries: Vec<(String, T)>) -> Tst<T>
+    where T: Send {
 
-    pub fn new(tst: &'a Tst<T>, key: &'b str, range: usize) -> Self {
+        parallel::par_from_sorted(entries)
+    }
+}
 
-        let mut it = TstNeighborIterator {
 
-            todo_i: Vec::new(), last_i: None,
-            todo_j: Vec::new(), last_j: None,
-        };
+/// A shortcut macro to help create a small tree with a list of known `"key" => value` pairs. Calls [`insert`](
+/// ./struct.Tst.html#method.insert) on each pair, in order.
+///
+/// ```
+/// # use ternary_tree::Tst;
+/// # use ternary_tree::tst;
+/// let map = tst!["fo" => "🍄", "bar" => "🐟", "baz" => "㵅", "fooo" => "🍄🍄🍄"];
+/// assert_eq!(map.len(), 4)
+/// ````
 
-        if let Some(ref node) = &tst.root {
+#[macro_export]
+macro_rules! tst {
 
-            let mut key_tail = key.chars();
-            let key_len = key.chars().count();
-            let label = key_tail.next();
-            let tail_len = if key_len == 0 { 0 } else {key_len-1 };
+    () => {{
+        $crate::Tst::new()
+    }};
 
-            it.todo_i.push((node, GoLeft, label, key_tail.clone(), tail_len, range));
-            it.todo_j.push((node, GoRight, label, key_tail, tail_len, range));
-        }
+    ($($key:expr => $value:expr,)+) => (tst!($($key => $value),+));
 
-        it
-    }
+    ($($key: expr => $val: expr),*) => {{
 
+        let mut tst = $crate::Tst::new();
+        $(
+            tst.insert($key, $val);
+        )*
 
-    gen_it_path!(current_key, todo_i, GoMiddle, GoRight);
-    gen_it_path!(current_key_back, todo_j, Visit, GoLeft);
+        tst
+    }};
 }
 
 
-impl<'a, 'b, T> Iterator for TstNeighborIterator<'a, 'b, T> {
-
-    type Item = &'a T;
-
-    fn next(&mut self) -> Option<&'a T> {
 
-        let mut found = None;
+/// A [double-ended]( http://doc.rust-lang.org/std/iter/trait.DoubleEndedIterator.html) iterator which
+/// successively returns all values of the tree. See [`iter`]( struct.Tst.html#method.iter) method for a brief
+/// description with a short example.
 
-        while let Some((node, action, label, mut key_tail, tail_len, range)) = self.todo_i.pop() {
+#[derive(Debug)]
+pub struct TstIterator<'a, T: 'a> {
 
-            match action {
+    it: Walker<&'a Node<T>>
+}
 
-                GoLeft => {
 
-                    self.todo_i.push((node, Visit, label, key_tail.clone(), tail_len, range));
+impl<'a, T> TstIterator<'a, T> {
 
-                    if let Some(label) = label {
+    pub fn new(tst: &'a Tst<T>) -> Self {
 
-                        if range == 0 && label >= node.label {
+        TstIterator { it: Walker::new(tst.root.as_deref(), true) }
+    }
 
-                            continue;
-                        }
-                    }
 
-                    if let Some(ref child) = node.left {
+    pub fn current_key(&self) -> String {
 
-                        self.todo_i.push((child, GoLeft, label, key_tail, tail_len, range));
-                    }
-                }
+        self.it.current_key()
+    }
 
-                Visit => {
 
-                    if node.value.is_some() {
+    pub fn current_key_back(&self) -> String {
 
-                        if let Some(node_j) = self.last_j {
+        self.it.current_key_back()
+    }
 
-                            if ptr::eq(node, node_j) {
 
-                                self.todo_i.clear();
-                                self.todo_j.clear();
+    /// Moves the iterator so that [`next`]( ./struct.TstIterator.html#method.next) returns the value of the first
+    /// key greater than or equal to `key`. The tree is walked down once along `key`, whatever the number of values
+    /// before it. The back of the iterator is moved to the last value, so that the iterator now returns the values
+    /// of all keys from `key` up to the end.
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let map = tst!["foo" => "🍄🍄", "bar" => "🐟", "baz" => "㵅"];
+    ///
+    /// let mut it = map.iter();
+    ///
+    /// it.seek("bat");
+    /// assert_eq!(it.next(), Some(&"㵅"));
+    /// assert_eq!(it.current_key(), "baz");
+    ///
+    /// it.seek("bar");
+    /// assert_eq!(it.next(), Some(&"🐟"));
+    /// ```
 
-                                found = None;
-                                break;
-                            }
-                        }
-                    }
+    pub fn seek(&mut self, key: &str) {
 
-                    self.todo_i.push((node, GoMiddle, label, key_tail, tail_len, range));
+        self.it.seek(key)
+    }
 
-                    if let Some(ref value) = node.value {
 
-                        let delta = match label {
+    /// Moves the iterator so that [`next_back`]( ./struct.TstIterator.html#method.next_back) returns the value of
+    /// the last key less than or equal to `key`. The front of the iterator is moved to the first value, so that
+    /// the iterator now returns the values of all keys from the beginning up to `key`. See [`seek`](
+    /// ./struct.TstIterator.html#method.seek).
 
-                            None => 1,
+    pub fn seek_back(&mut self, key: &str) {
 
-                            Some(label) => if label==node.label { 0 } else { 1 }
+        self.it.seek_back(key)
+    }
+}
 
-                        };
 
-                        if range >= delta {
+// Compares the next characters of `key_tail` with `run`. A key which ends inside the run comes first.
 
-                            let new_range = range - delta;
+fn crossing_run(run: &str, key_tail: &mut Chars) -> std::cmp::Ordering {
 
-                            if tail_len  <= new_range {
+    for run_label in run.chars() {
 
-                                self.last_i = Some(node);
-                                found = Some(value);
+        match key_tail.next() {
 
-                                break;
-                            }
-                        }
-                    }
-                }
+            None => return Less,
 
-                GoMiddle => {
+            Some(label) => if label != run_label {
 
-                    self.todo_i.push((node, GoRight, label, key_tail.clone(), tail_len, range));
+                return label.cmp(&run_label);
+            }
+        }
+    }
 
-                    let delta = match label {
+    Equal
+}
 
-                        None => 1,
 
-                        Some(label) => if label==node.label { 0 } else { 1 }
-                    };
+impl<'a, T> Iterator for TstIterator<'a, T> {
 
-                    if range >= delta {
+    type Item = &'a T;
 
-                        let new_range = range - delta;
+    fn next(&mut self) -> Option<&'a T> {
 
-                        let new_label = key_tail.next();
-                        let new_len = if tail_len > 0 { tail_len-1 } else { tail_len };
+        self.it.next()
+    }
+}
 
-                        if let Some(ref child) = node.middle {
 
-                            self.todo_i.push((child, GoLeft, new_label, key_tail, new_len, new_range));
-                        }
-                    }
-                }
+impl<'a, T> IntoIterator for &'a Tst<T> {
 
-                GoRight => {
+    type Item = &'a T;
+    type IntoIter = TstIterator<'a, T>;
 
-                    if let Some(label) = label {
+    fn into_iter(self) -> Self::IntoIter {
 
-                        if range == 0 && label <= node.label {
+        self.iter()
+    }
+}
 
-                            continue;
-                        }
-                    }
 
-                    if let Some(ref child) = node.right {
+impl<'a, T> DoubleEndedIterator for TstIterator<'a, T> {
 
-                        self.todo_i.push((child, GoLeft, label, key_tail, tail_len, range));
-                    }
-                }
-            }
-        }
+    fn next_back(&mut self) -> Option<&'a T> {
 
-        found
+        self.it.next_back()
     }
 }
 
 
-impl<'a, 'b, T> DoubleEndedIterator for TstNeighborIterator<'a, 'b, T> {
+/// A [double-ended]( http://doc.rust-lang.org/std/iter/trait.DoubleEndedIterator.html) iterator which
+/// successively returns all values whose key begins with `prefix`. See [`iter_complete`](
+/// struct.Tst.html#method.iter_complete) method for a brief description with a short example.
 
-    fn next_back(&mut self) -> Option<&'a T> {
+#[derive(Debug)]
+pub struct TstCompleteIterator<'a, T: 'a> {
 
-        let mut found = None;
+    it: CompleteWalker<&'a Node<T>>
+}
 
-        while let Some((node, action, label, mut key_tail, tail_len, range)) = self.todo_j.pop() {
 
-            match action {
+impl<'a, T> TstCompleteIterator<'a, T> {
 
-                GoRight => {
+    pub fn new(tst: &'a Tst<T>, key_prefix: &str) -> Self {
 
-                    self.todo_j.push((node, GoMiddle, label, key_tail.clone(), tail_len, range));
+        TstCompleteIterator { it: CompleteWalker::new(tst.root.as_deref(), key_prefix) }
+    }
 
-                    if let Some(label) = label {
 
-                        if range == 0 && label <= node.label {
+    pub fn current_key(&self) -> String {
 
-                            continue;
-                        }
-                    }
+        self.it.current_key()
+    }
 
-                    if let Some(ref child) = node.right {
 
-                        self.todo_j.push((child, GoRight, label, key_tail, tail_len, range));
-                    }
-                }
+    pub fn current_key_back(&self) -> String {
 
-                Visit => {
+        self.it.current_key_back()
+    }
 
-                    if node.value.is_some() {
 
-                        if let Some(node_i) = self.last_i {
+    /// Moves the iterator so that [`next`]( ./struct.TstCompleteIterator.html#method.next) returns the value of
+    /// the first key greater than or equal to `key`, among the keys which begin with the prefix of the iterator.
+    /// The back of the iterator is moved to the last of those keys. See [`TstIterator::seek`](
+    /// ./struct.TstIterator.html#method.seek).
+    ///
+    /// ```
+    /// # use ternary_tree::tst;
+    /// let map = tst!["foo" => "🍄🍄", "bar" => "🐟", "baz" => "㵅"];
+    ///
+    /// let mut it = map.iter_complete("ba");
+    ///
+    /// it.seek("bat");
+    /// assert_eq!(it.next(), Some(&"㵅"));
+    /// assert_eq!(it.current_key(), "baz");
+    ///
+    /// it.seek("a");
+    /// assert_eq!(it.next(), Some(&"🐟"));
+    ///
+    /// it.seek("foo");
+    /// assert_eq!(it.next(), None);
+    /// ```
 
-                            if ptr::eq(node, node_i) {
+    pub fn seek(&mut self, key: &str) {
 
-                                self.todo_i.clear();
-                                self.todo_j.clear();
+        self.it.seek(key)
+    }
 
-                                found = None;
-                                break;
-                            }
-                        }
-                    }
 
-                    self.todo_j.push((node, GoLeft, label, key_tail, tail_len, range));
+    /// Moves the iterator so that [`next_back`]( ./struct.TstCompleteIterator.html#method.next_back) returns the
+    /// value of the last key less than or equal to `key`, among the keys which begin with the prefix of the
+    /// iterator. The front of the iterator is moved to the first of those keys. See [`TstIterator::seek`](
+    /// ./struct.TstIterator.html#method.seek).
 
-                    if let Some(ref value) = node.value {
+    pub fn seek_back(&mut self, key: &str) {
 
-                        let delta = match label {
+        self.it.seek_back(key)
+    }
+}
 
-                            None => 1,
 
-                            Some(label) => if label==node.label { 0 } else { 1 }
+impl<'a, T> Iterator for TstCompleteIterator<'a, T> {
 
-                        };
+    type Item = &'a T;
 
-                        if range >= delta {
+    fn next(&mut self) -> Option<&'a T> {
 
-                            let new_range = range - delta;
+        self.it.next()
+    }
+}
 
-                            if tail_len  <= new_range {
 
-                                self.last_j = Some(node);
-                                found = Some(value);
+impl<'a, T> DoubleEndedIterator for TstCompleteIterator<'a, T> {
 
-                                break;
-                            }
-                        }
-                    }
-                }
+    fn next_back(&mut self) -> Option<&'a T> {
 
-                GoMiddle => {
+        self.it.next_back()
+    }
+}
 
-                    self.todo_j.push((node, Visit, label, key_tail.clone(), tail_len, range));
 
-                    let delta = match label {
+/// A [double-ended]( http://doc.rust-lang.org/std/iter/trait.DoubleEndedIterator.html) iterator which
+/// successively returns all values whose key lies between two bounds. See [`range`](
+/// struct.Tst.html#method.range) method for a brief description with a short example.
 
-                        None => 1,
+#[derive(Debug)]
+pub struct TstRangeIterator<'a, T: 'a> {
 
-                        Some(label) => if label==node.label { 0 } else { 1 }
+    it: Walker<&'a Node<T>>,
+    start: Bound<String>,
+    end: Bound<String>
+}
 
-                    };
 
-                    if range >= delta {
+fn owned_bound(bound: Bound<&&str>) -> Bound<String> {
 
-                        let new_range = range - delta;
+    match bound {
 
-                        let new_label = key_tail.next();
-                        let new_len = if tail_len > 0 { tail_len-1 } else { tail_len };
+        Bound::Included(key) => Bound::Included(key.to_string()),
 
-                        if let Some(ref child) = node.middle {
+        Bound::Excluded(key) => Bound::Excluded(key.to_string()),
 
-                            self.todo_j.push((child, GoRight, new_label, key_tail, new_len, new_range));
-                        }
-                    }
-                }
+        Bound::Unbounded => Bound::Unbounded
+    }
+}
 
-                GoLeft => {
 
-                    if let Some(label) = label {
+impl<'a, T> TstRangeIterator<'a, T> {
 
-                        if range == 0 && label >= node.label {
+    pub fn new<'b, R>(tst: &'a Tst<T>, range: R) -> Self
+    where R: RangeBounds<&'b str> {
 
-                            continue;
-                        }
-                    }
+        let mut it = Walker::new(tst.root.as_deref(), true);
 
-                    if let Some(ref child) = node.left {
+        let start = owned_bound(range.start_bound());
+        let end = owned_bound(range.end_bound());
 
-                        self.todo_j.push((child, GoRight, label, key_tail, tail_len, range));
-                    }
-                }
-            }
-        }
+        match start {
 
-        found
-    }
-}
+            Bound::Included(ref key) => it.seek_front(key, true),
 
+            Bound::Excluded(ref key) => it.seek_front(key, false),
 
-/// A [double-ended]( http://doc.rust-lang.org/std/iter/trait.DoubleEndedIterator.html) iterator which
-/// successively returns all values whose key _matches_ `pattern`. See [`iter_crossword`](
-/// struct.Tst.html#method.iter_crossword) method for a brief description with a short example.
+            Bound::Unbounded => ()
+        }
 
-#[derive(Debug)]
-pub struct TstCrosswordIterator<'a, 'b, T: 'a> {
+        match end {
 
-    todo_i: Vec<(&'a Node<T>, TstIteratorAction, char, Chars<'b>, usize)>,
-    last_i: Option<&'a Node<T>>,
+            Bound::Included(ref key) => it.seek_back_to(key, true),
 
-    todo_j: Vec<(&'a Node<T>, TstIteratorAction, char, Chars<'b>, usize)>,
-    last_j: Option<&'a Node<T>>,
+            Bound::Excluded(ref key) => it.seek_back_to(key, false),
 
-    joker: char
-}
+            Bound::Unbounded => ()
+        }
 
+        TstRangeIterator { it, start, end }
+    }
 
-impl<'a, 'b, T> TstCrosswordIterator<'a, 'b, T> {
 
-    pub fn new(tst: &'a Tst<T>, key: &'b str, joker: char) -> Self {
+    pub fn current_key(&self) -> String {
 
-        let mut it = TstCrosswordIterator {
+        self.it.current_key()
+    }
 
-            todo_i: Vec::new(), last_i: None,
-            todo_j: Vec::new(), last_j: None,
-            joker,
 
-        };
+    pub fn current_key_back(&self) -> String {
 
-        if let Some(ref node) = &tst.root {
+        self.it.current_key_back()
+    }
 
-            let mut key_tail = key.chars();
 
-            if let Some(label) = key_tail.next() {
+    // Both ends walk towards each other from their bound, but when the range holds no value, or when only one end
+    // is used, an end may walk past the bound of the other.
 
-                let tail_len = key.chars().count()-1;
+    fn done(&mut self) -> Option<&'a T> {
 
-                it.todo_i.push((node, GoLeft, label, key_tail.clone(), tail_len));
-                it.todo_j.push((node, GoRight, label, key_tail, tail_len));
-            }
-        }
+        self.it.clear();
 
-        it
+        None
     }
-
-
-    gen_it_path!(current_key, todo_i, GoMiddle, GoRight);
-    gen_it_path!(current_key_back, todo_j, Visit, GoLeft);
 }
 
 
-impl<'a, 'b, T> Iterator for TstCrosswordIterator<'a, 'b, T> {
+impl<'a, T> Iterator for TstRangeIterator<'a, T> {
 
     type Item = &'a T;
 
     fn next(&mut self) -> Option<&'a T> {
 
-        let mut found = None;
-
-        while let Some((node, action, label, mut key_tail, tail_len)) = self.todo_i.pop() {
+        let value = self.it.next()?;
 
-            match action {
+        let inside = match self.end {
 
-                GoLeft => {
+            Bound::Included(ref key) => self.it.current_key() <= *key,
 
-                    self.todo_i.push((node, Visit, label, key_tail.clone(), tail_len));
+            Bound::Excluded(ref key) => self.it.current_key() < *key,
 
-                    if label == self.joker || label < node.label {
+            Bound::Unbounded => true
+        };
 
-                        if let Some(ref child) = node.left {
+        if inside { Some(value) } else { self.done() }
+    }
+}
 
-                            self.todo_i.push((child, GoLeft, label, key_tail, tail_len));
-                        }
-                    }
-                }
 
-                Visit => {
+impl<'a, T> DoubleEndedIterator for TstRangeIterator<'a, T> {
 
-                    if node.value.is_some() {
+    fn next_back(&mut self) -> Option<&'a T> {
 
-                        if let Some(node_j) = self.last_j {
+        let value = self.it.next_back()?;
 
-                            if ptr::eq(node, node_j) {
+        let inside = match self.start {
 
-                                self.todo_i.clear();
-                                self.todo_j.clear();
+            Bound::Included(ref key) => self.it.current_key_back() >= *key,
 
-                                found = None;
-                                break;
-                            }
-                        }
-                    }
+            Bound::Excluded(ref key) => self.it.current_key_back() > *key,
 
-                    self.todo_i.push((node, GoMiddle, label, key_tail, tail_len));
+            Bound::Unbounded => true
+        };
 
-                    if let Some(ref value) = node.value {
+        if inside { Some(value) } else { self.done() }
+    }
+}
 
-                        if tail_len == 0 && (label == self.joker || label == node.label) {
 
-                            self.last_i = Some(node);
-                            found = Some(value);
+/// A [double-ended]( http://doc.rust-lang.org/std/iter/trait.DoubleEndedIterator.html) iterator which
+/// successively returns all values whose key is _close_ to `key`. See [`iter_neighbor`](
+/// struct.Tst.html#method.iter_neighbor) method for a brief description with a short example.
 
-                            break;
-                        }
-                    }
-                }
+#[derive(Debug)]
+pub struct TstNeighborIterator<'a, 'b, T: 'a> {
 
-                GoMiddle => {
+    it: NeighborWalker<'b, &'a Node<T>>
+}
 
-                    self.todo_i.push((node, GoRight, label, key_tail.clone(), tail_len));
 
-                    if label == self.joker || label == node.label {
+impl<'a, 'b, T> TstNeighborIterator<'a, 'b, T> {
 
-                        if let Some(ref child) = node.middle {
+    pub fn new(tst: &'a Tst<T>, key: &'b str, range: usize) -> Self {
 
-                            if let Some(new_label) = key_tail.next() {
+        TstNeighborIterator { it: NeighborWalker::new(tst.root.as_deref(), key, range) }
+    }
 
-                                self.todo_i.push((child, GoLeft, new_label, key_tail, tail_len-1));
-                            }
-                        }
-                    }
-                }
 
-                GoRight => {
+    pub fn current_key(&self) -> String {
 
-                    if label == self.joker || label > node.label {
+        self.it.current_key()
+    }
 
-                        if let Some(ref child) = node.right {
 
-                            self.todo_i.push((child, GoLeft, label, key_tail, tail_len));
-                        }
-                    }
-                }
-            }
-        }
+    pub fn current_key_back(&self) -> String {
 
-        found
+        self.it.current_key_back()
     }
 }
 
 
-impl<'a, 'b, T> DoubleEndedIterator for TstCrosswordIterator<'a, 'b, T> {
-
-    fn next_back(&mut self) -> Option<&'a T> {
+impl<'a, 'b, T> Iterator for TstNeighborIterator<'a, 'b, T> {
 
-        let mut found = None;
+    type Item = &'a T;
 
-        while let Some((node, action, label, mut key_tail, tail_len)) = self.todo_j.pop() {
+    fn next(&mut self) -> Option<&'a T> {
 
-            match action {
+        self.it.next()
+    }
+}
 
-                GoRight => {
 
-                    self.todo_j.push((node, GoMiddle, label, key_tail.clone(), tail_len));
+impl<'a, 'b, T> DoubleEndedIterator for TstNeighborIterator<'a, 'b, T> {
 
-                    if label == self.joker || label > node.label {
+    fn next_back(&mut self) -> Option<&'a T> {
 
-                        if let Some(ref child) = node.right {
+        self.it.next_back()
+    }
+}
 
-                            self.todo_j.push((child, GoRight, label, key_tail, tail_len));
-                        }
-                    }
-                }
 
-                Visit => {
+/// A [double-ended]( http://doc.rust-lang.org/std/iter/trait.DoubleEndedIterator.html) iterator which
+/// successively returns all values whose key _matches_ `pattern`. See [`iter_crossword`](
+/// struct.Tst.html#method.iter_crossword) method for a brief description with a short example.
 
-                    if node.value.is_some() {
+#[derive(Debug)]
+pub struct TstCrosswordIterator<'a, 'b, T: 'a> {
 
-                        if let Some(node_i) = self.last_i {
+    it: CrosswordWalker<'b, &'a Node<T>>
+}
 
-                            if ptr::eq(node, node_i) {
 
-                                self.todo_i.clear();
-                                self.todo_j.clear();
+impl<'a, 'b, T> TstCrosswordIterator<'a, 'b, T> {
 
-                                found = None;
-                                break;
-                            }
-                        }
-                    }
+    pub fn new(tst: &'a Tst<T>, key: &'b str, joker: char) -> Self {
 
-                    self.todo_j.push((node, GoLeft, label, key_tail, tail_len));
+        TstCrosswordIterator { it: CrosswordWalker::new(tst.root.as_deref(), key, joker) }
+    }
 
-                    if let Some(ref value) = node.value {
 
-                        if tail_len == 0 && (label == self.joker || label == node.label) {
+    pub fn current_key(&self) -> String {
 
-                            self.last_j = Some(node);
-                            found = Some(value);
+        self.it.current_key()
+    }
 
-                            break;
-                        }
-                    }
-                }
 
-                GoMiddle => {
+    pub fn current_key_back(&self) -> String {
 
-                    self.todo_j.push((node, Visit, label, key_tail.clone(), tail_len));
+        self.it.current_key_back()
+    }
+}
 
-                    if label == self.joker || label == node.label {
 
-                        if let Some(ref child) = node.middle {
+impl<'a, 'b, T> Iterator for TstCrosswordIterator<'a, 'b, T> {
 
-                            if let Some(new_label) = key_tail.next() {
+    type Item = &'a T;
 
-                                self.todo_j.push((child, GoRight, new_label, key_tail, tail_len-1));
-                            }
-                        }
-                    }
-                }
+    fn next(&mut self) -> Option<&'a T> {
 
-                GoLeft => {
+        self.it.next()
+    }
+}
 
-                    if label == self.joker || label < node.label {
 
-                        if let Some(ref child) = node.left {
+impl<'a, 'b, T> DoubleEndedIterator for TstCrosswordIterator<'a, 'b, T> {
 
-                            self.todo_j.push((child, GoRight, label, key_tail, tail_len));
-                        }
-                    }
-                }
-            }
-        }
+    fn next_back(&mut self) -> Option<&'a T> {
 
-        found
+        self.it.next_back()
     }
 }
@@ -0,0 +1,220 @@
+use std::mem;
+
+use rayon;
+use rayon::iter::ParallelIterator;
+use rayon::slice::ParallelSliceMut;
+
+use super::Tst;
+use super::Node;
+use super::Link;
+use super::Stats;
+use super::DistStat;
+use super::stat_r;
+use super::visit_values_r_mut;
+use super::sorted::SortedEntry;
+use super::sorted::sort_entries;
+use super::sorted::split_entries;
+use super::sorted::new_node;
+use super::sorted::build_r;
+
+
+// Down to this depth, the three children of a node are handed to separate tasks. Deeper subtrees are small enough
+// to be walked by the current thread.
+
+const FORK_DEPTH: usize = 12;
+
+
+// Slices with fewer entries than this are built by the current thread.
+
+const FORK_ENTRIES: usize = 1024;
+
+
+pub fn par_iter<'a, T>(tst: &'a Tst<T>) -> impl ParallelIterator<Item = &'a T> + 'a
+where T: Sync + 'a {
+
+    // Only the root may be `None`, other missing children are skipped.
+
+    let children_of = |node: &Option<&'a Node<T>>| {
+
+        node.map(|node| [node.left.as_deref(), node.middle.as_deref(), node.right.as_deref()])
+            .into_iter()
+            .flatten()
+            .filter(|child| child.is_some())
+    };
+
+    rayon::iter::walk_tree_prefix(tst.root.as_deref(), children_of)
+        .filter_map(|node| node.and_then(|node| node.value.as_ref()))
+}
+
+
+pub fn par_visit_values_mut<T, C>(tst: &mut Tst<T>, callback: C)
+where T: Send, C: Fn(&mut T) + Sync {
+
+    par_visit_values_mut_r(&mut tst.root, &callback, FORK_DEPTH);
+}
+
+
+fn par_visit_values_mut_r<T, C>(link: &mut Link<T>, callback: &C, fork: usize)
+where T: Send, C: Fn(&mut T) + Sync {
+
+    if fork == 0 {
+
+        visit_values_r_mut(link, &mut |value: &mut T| callback(value));
+        return;
+    }
+
+    if let Some(ref mut node) = *link {
+
+        let node = &mut **node;
+
+        if let Some(ref mut value) = node.value {
+
+            callback(value);
+        }
+
+        let left = &mut node.left;
+        let middle = &mut node.middle;
+        let right = &mut node.right;
+
+        rayon::join(|| par_visit_values_mut_r(left, callback, fork-1),
+                    || rayon::join(|| par_visit_values_mut_r(middle, callback, fork-1),
+                                   || par_visit_values_mut_r(right, callback, fork-1)));
+    }
+}
+
+
+pub fn par_stat<T>(tst: &Tst<T>) -> Stats
+where T: Sync {
+
+    let mut stats = par_stat_r(&tst.root, 0, 0, 0, FORK_DEPTH);
+
+    stats.bytes.node = mem::size_of::<Node<T>>();
+    stats.bytes.total += mem::size_of::<Tst<T>>()+stats.count.nodes*stats.bytes.node;
+
+    stats
+}
+
+
+// Same walk as `stat_r`, but each subtree gathers its own metrics, which are then summed up.
+
+fn par_stat_r<T>(link: &Link<T>, matches: usize, sides: usize, depth: usize, fork: usize) -> Stats
+where T: Sync {
+
+    if fork == 0 {
+
+        return stat_r(Default::default(), link, matches, sides, depth);
+    }
+
+    match *link {
+
+        None => Default::default(),
+
+        Some(ref node) => {
+
+            let mut stats: Stats = Default::default();
+
+            stats.count.nodes+=1;
+            stats.bytes.total+=node.run.bytes();
+            stats.bytes.counts+=mem::size_of::<usize>();
+
+            let run_len = node.run.chars().count();
+
+            if node.value.is_some() {
+
+                let matches = matches + 1 + run_len;
+                let depth = depth + 1 + run_len;
+
+                while stats.dist.len() <= depth {
+
+                    stats.dist.push(DistStat { matches: 0, sides: 0, depth: 0 });
+                }
+
+                stats.dist[matches].matches+=1;
+                stats.dist[sides].sides+=1;
+                stats.dist[depth].depth+=1;
+
+                stats.key_len.min = matches;
+                stats.key_len.max = matches;
+
+                stats.count.values+=1;
+            }
+
+            let (left, (middle, right)) = rayon::join(
+
+                || par_stat_r(&node.left, matches, sides+1, depth+1, fork-1),
+                || rayon::join(|| par_stat_r(&node.middle, matches+1+run_len, sides, depth+1+run_len, fork-1),
+                               || par_stat_r(&node.right, matches, sides+1, depth+1, fork-1)));
+
+            add_stats(add_stats(add_stats(stats, left), middle), right)
+        }
+    }
+}
+
+
+fn add_stats(mut stats: Stats, other: Stats) -> Stats {
+
+    while stats.dist.len() < other.dist.len() {
+
+        stats.dist.push(DistStat { matches: 0, sides: 0, depth: 0 });
+    }
+
+    for (dist, other) in stats.dist.iter_mut().zip(other.dist.iter()) {
+
+        dist.matches+=other.matches;
+        dist.sides+=other.sides;
+        dist.depth+=other.depth;
+    }
+
+    if stats.key_len.min == 0 || (other.key_len.min != 0 && other.key_len.min < stats.key_len.min) {
+
+        stats.key_len.min = other.key_len.min;
+    }
+
+    if other.key_len.max > stats.key_len.max {
+
+        stats.key_len.max = other.key_len.max;
+    }
+
+    stats.count.nodes+=other.count.nodes;
+    stats.count.values+=other.count.values;
+    stats.bytes.total+=other.bytes.total;
+    stats.bytes.counts+=other.bytes.counts;
+
+    stats
+}
+
+
+pub fn par_from_sorted<T>(entries: Vec<(String, T)>) -> Tst<T>
+where T: Send {
+
+    let mut entries = sort_entries(entries, |entries| entries.par_sort_by(|a, b| a.0.cmp(&b.0)));
+
+    let count = entries.len();
+    let root = par_build_r(&mut entries, 0);
+
+    Tst { root, count, balanced: false }
+}
+
+
+// Subtrees which do not overlap are built on separate threads, until they hold too few entries.
+
+fn par_build_r<T>(entries: &mut [SortedEntry<T>], pos: usize) -> Link<T>
+where T: Send {
+
+    if entries.len() < FORK_ENTRIES {
+
+        return build_r(entries, pos);
+    }
+
+    let (label, value, [before, same, after]) = split_entries(entries, pos);
+
+    let next_pos = pos + label.len_utf8();
+
+    let (left, (middle, right)) = rayon::join(
+
+        || par_build_r(before, pos),
+        || rayon::join(|| par_build_r(same, next_pos),
+                       || par_build_r(after, pos)));
+
+    new_node(label, value, left, middle, right)
+}
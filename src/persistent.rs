@@ -0,0 +1,1146 @@
+use std::str::Chars;
+use std::cmp::Ordering::Less;
+use std::cmp::Ordering::Equal;
+use std::cmp::Ordering::Greater;
+use std::sync::Arc;
+use std::ptr;
+use std::fmt;
+use std::mem;
+
+use super::Stats;
+use super::DistStat;
+use super::walk::NodeRef;
+use super::walk::Walker;
+use super::walk::CompleteWalker;
+use super::walk::NeighborWalker;
+use super::walk::CrosswordWalker;
+
+
+/// A `PersistentTst` is a ternary tree whose nodes are shared, through reference counting, between all the copies
+/// of the tree. Cloning a `PersistentTst` is O(1): it only clones the pointer to the root node. Later, `insert` and
+/// `remove` copy the nodes along the path of the changed key (and only those nodes, when they are shared), so that
+/// the other copies are left untouched.
+///
+/// This makes it easy to hand a consistent snapshot of the tree to readers, while a writer keeps on updating its
+/// own copy. Nodes are counted with an `Arc`, so snapshots can be sent to other threads. Values are stored in an
+/// `Arc` too, and are never copied.
+///
+/// Nodes count the values below them, as in a `Tst`, so that [`count_prefix`](
+/// ./struct.PersistentTst.html#method.count_prefix), [`rank`]( ./struct.PersistentTst.html#method.rank) and
+/// [`select`]( ./struct.PersistentTst.html#method.select) give the same results, without walking over the keys.
+/// However, runs of characters are not compressed: each node holds a single character, so that `stat` counts more
+/// nodes than for a `Tst` with the same keys, and the tree is never balanced.
+///
+/// ```
+/// # use ternary_tree::PersistentTst;
+/// let mut map = PersistentTst::new();
+///
+/// map.insert("foo", "🍄🍄");
+/// map.insert("bar", "🐟");
+///
+/// let snapshot = map.clone();
+///
+/// map.insert("baz", "㵅");
+/// map.remove("foo");
+///
+/// assert_eq!(map.len(), 2);
+/// assert_eq!(map.get("foo"), None);
+///
+/// assert_eq!(snapshot.len(), 2);
+/// assert_eq!(snapshot.get("foo"), Some(&"🍄🍄"));
+/// assert_eq!(snapshot.get("baz"), None);
+/// ```
+
+pub struct PersistentTst<T> {
+
+    root: Link<T>,
+    count: usize
+}
+
+
+type Link<T> = Option<Arc<PersistentNode<T>>>;
+
+
+struct PersistentNode<T> {
+
+    label: char,
+    value: Option<Arc<T>>,
+    left: Link<T>,
+    middle: Link<T>,
+    right: Link<T>,
+    count: usize
+}
+
+
+impl<T> Default for PersistentNode<T> {
+
+    fn default() -> PersistentNode<T> {
+
+        PersistentNode {
+
+            label: '\0',
+            value: None,
+            left: None,
+            middle: None,
+            right: None,
+            count: 0
+        }
+    }
+}
+
+
+// Copying a node only copies its pointers: children and value stay shared.
+
+impl<T> Clone for PersistentNode<T> {
+
+    fn clone(&self) -> PersistentNode<T> {
+
+        PersistentNode {
+
+            label: self.label,
+            value: self.value.clone(),
+            left: self.left.clone(),
+            middle: self.middle.clone(),
+            right: self.right.clone(),
+            count: self.count
+        }
+    }
+}
+
+
+impl<T> fmt::Debug for PersistentNode<T> {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+
+            let value_box = match self.value {
+
+                None => "☐", Some(_) => "☑"
+            };
+
+        write!(f, "{}-{}", value_box, self.label)
+    }
+}
+
+
+fn count<T>(link: &Link<T>) -> usize {
+
+    match *link {
+
+        None => 0,
+
+        Some(ref node) => node.count
+    }
+}
+
+
+// As in a `Tst`, each node counts the values of its subtree, siblings included. Since only the nodes along the path
+// of a changed key are copied, only their counts need an update.
+
+fn update_count<T>(node: &mut PersistentNode<T>) {
+
+    node.count = node.value.iter().count() + count(&node.left) + count(&node.middle) + count(&node.right);
+}
+
+
+// `Arc::make_mut` gives a mutable node, after copying it if it is shared with another tree. Walking down the key
+// this way copies the path of the key, and nothing else.
+
+fn insert_r<T>(link: &mut Link<T>, label: char, mut key_tail: Chars, value: Arc<T>) -> Option<Arc<T>> {
+
+    if link.is_none() {
+
+        *link = Some(Arc::new(PersistentNode::<T>{label, .. Default::default()}));
+    }
+
+    let node = match *link {
+
+        None => return None,
+
+        Some(ref mut node) => Arc::make_mut(node)
+    };
+
+    let old_value = match label.cmp(&node.label) {
+
+        Less => insert_r(&mut node.left, label, key_tail, value),
+
+        Greater => insert_r(&mut node.right, label, key_tail, value),
+
+        Equal => {
+
+            let new_label = key_tail.next();
+
+            match new_label {
+
+                None => node.value.replace(value),
+
+                Some(label) => insert_r(&mut node.middle, label, key_tail, value)
+            }
+        }
+    };
+
+    update_count(node);
+
+    old_value
+}
+
+
+// The key is known to be in the tree, so that only nodes which do change are copied.
+
+fn remove_r<T>(link: &mut Link<T>, label: char, key_tail: &mut Chars) -> Option<Arc<T>> {
+
+    let node = match *link {
+
+        None => return None,
+
+        Some(ref mut node) => Arc::make_mut(node)
+    };
+
+    let old_value = match label.cmp(&node.label) {
+
+        Less => remove_r(&mut node.left, label, key_tail),
+
+        Greater => remove_r(&mut node.right, label, key_tail),
+
+        Equal => {
+
+            let new_label = key_tail.next();
+
+            match new_label {
+
+                None => node.value.take(),
+
+                Some(label) => remove_r(&mut node.middle, label, key_tail)
+            }
+        }
+    };
+
+    update_count(node);
+
+    if node.value.is_none() && node.left.is_none() && node.middle.is_none() && node.right.is_none() {
+
+        *link = None;
+    }
+
+    old_value
+}
+
+
+fn get_r<'a, T>(link: &'a Link<T>, label: char, key_tail: &mut Chars) -> Option<&'a T> {
+
+    match *link {
+
+        None => None,
+
+        Some(ref node) => match label.cmp(&node.label) {
+
+            Less => get_r(&node.left, label, key_tail),
+
+            Equal => {
+
+                let new_label = key_tail.next();
+
+                match new_label {
+
+                    None => match node.value {
+
+                        None => None,
+
+                        Some(ref value) => Some(&**value)
+                    }
+
+                    Some(label) => get_r(&node.middle, label, key_tail)
+                }
+            },
+
+            Greater => get_r(&node.right, label, key_tail),
+        }
+    }
+}
+
+
+fn stat_r<T>(stats: Stats, link: &Link<T>, matches: usize, sides: usize, depth: usize) -> Stats {
+
+    match *link {
+
+        None => stats,
+
+        Some(ref node) => {
+
+            let mut stats = stat_r(stats, &node.left, matches, sides+1, depth+1);
+
+            stats.count.nodes+=1;
+            stats.bytes.counts+=mem::size_of::<usize>();
+
+            if node.value.is_some() {
+
+                let matches = matches + 1;
+                let depth = depth + 1;
+
+                while stats.dist.len() <= depth {
+
+                    stats.dist.push(DistStat { matches: 0, sides: 0, depth: 0 });
+                }
+
+                stats.dist[matches].matches+=1;
+                stats.dist[sides].sides+=1;
+                stats.dist[depth].depth+=1;
+
+                if stats.key_len.min == 0 || matches < stats.key_len.min {
+
+                    stats.key_len.min = matches;
+                }
+
+                if matches > stats.key_len.max {
+
+                    stats.key_len.max = matches;
+                }
+
+                stats.count.values+=1;
+            }
+
+            let stats = stat_r(stats, &node.middle, matches+1, sides, depth+1);
+
+            stat_r(stats, &node.right, matches, sides+1, depth+1)
+        }
+    }
+}
+
+
+fn find_complete_root_r<'a, T>(link: &'a Link<T>, label: char, mut key_tail: Chars) -> &'a Link<T> {
+
+    match *link {
+
+        None => link,
+
+        Some(ref node) => match label.cmp(&node.label) {
+
+            Less => find_complete_root_r(&node.left, label, key_tail),
+
+            Greater => find_complete_root_r(&node.right, label, key_tail),
+
+            Equal => {
+
+                let new_label = key_tail.next();
+
+                match new_label {
+
+                    None => &node.middle,
+
+                    Some(label) => find_complete_root_r(&node.middle, label, key_tail)
+                }
+            }
+        }
+    }
+}
+
+
+// Counts the keys which come before `label` and `key_tail` in alphabetical order, skipping whole subtrees thanks
+// to their value counts.
+
+fn rank_r<T>(link: &Link<T>, label: char, key_tail: &mut Chars) -> usize {
+
+    match *link {
+
+        None => 0,
+
+        Some(ref node) => match label.cmp(&node.label) {
+
+            Less => rank_r(&node.left, label, key_tail),
+
+            Greater => count(&node.left) + node.value.iter().count() + count(&node.middle) + rank_r(&node.right, label, key_tail),
+
+            Equal => match key_tail.next() {
+
+                None => count(&node.left),
+
+                Some(label) => count(&node.left) + node.value.iter().count() + rank_r(&node.middle, label, key_tail)
+            }
+        }
+    }
+}
+
+
+// Walks down to the value of rank `n`, and builds its key on the way.
+
+fn select_r<'a, T>(link: &'a Link<T>, mut n: usize, key: &mut String) -> Option<&'a T> {
+
+    match *link {
+
+        None => None,
+
+        Some(ref node) => {
+
+            let left = count(&node.left);
+
+            if n < left {
+
+                return select_r(&node.left, n, key);
+            }
+
+            n -= left;
+
+            if let Some(ref value) = node.value {
+
+                if n == 0 {
+
+                    key.push(node.label);
+                    return Some(value);
+                }
+
+                n -= 1;
+            }
+
+            let middle = count(&node.middle);
+
+            if n < middle {
+
+                key.push(node.label);
+                return select_r(&node.middle, n, key);
+            }
+
+            select_r(&node.right, n - middle, key)
+        }
+    }
+}
+
+
+fn visit_values_r<T, C>(link: &Link<T>, callback: &mut C)
+where C: FnMut (&T) {
+
+    match *link {
+
+        None => (),
+
+        Some(ref node) => {
+
+            visit_values_r(&node.left, callback);
+
+            if let Some(ref value) = node.value {
+
+                callback(value);
+            }
+
+            visit_values_r(&node.middle, callback);
+            visit_values_r(&node.right, callback);
+        }
+    }
+}
+
+
+fn visit_complete_values_r<T, C>(link: &Link<T>, callback: &mut C)
+where C: FnMut (&T) {
+
+    match *link {
+
+        None => (),
+
+        Some(ref node) => {
+
+            visit_values_r(&node.left, callback);
+
+            if let Some(ref value) = node.value {
+
+                callback(value);
+            }
+
+            visit_values_r(&node.middle, callback);
+            visit_values_r(&node.right, callback);
+        }
+    }
+}
+
+
+fn visit_neighbor_values_r<T, C>(link: &Link<T>, label: Option<char>, key_tail: &mut Chars, tail_len: usize, range: usize, callback: &mut C)
+where C: FnMut (&T) {
+
+    if range == 0 {
+
+        if let Some(label) = label {
+
+            if let Some(value) = get_r(link, label, key_tail) {
+
+                callback(value);
+            }
+        }
+
+    } else {
+
+        if let Some(ref node) = *link {
+
+            visit_neighbor_values_r(&node.left, label, key_tail, tail_len, range, callback);
+
+            if let Some(ref value) = node.value {
+
+                let new_range = match label {
+
+                    None => range-1,
+
+                    Some(label) => if label==node.label { range } else { range-1 }
+                };
+
+                if tail_len <= new_range {
+
+                    callback(value);
+                }
+            }
+
+            {
+                let new_range = match label {
+
+                    None => range-1,
+
+                    Some(label) => if label==node.label { range } else { range-1 }
+                };
+
+                let mut new_tail = key_tail.clone();
+                let new_label = new_tail.next();
+
+                let new_len = if tail_len > 0 { tail_len-1 } else { tail_len };
+
+                visit_neighbor_values_r(&node.middle, new_label, &mut new_tail, new_len, new_range, callback);
+            }
+
+            visit_neighbor_values_r(&node.right, label, key_tail, tail_len, range, callback);
+        }
+    }
+}
+
+
+fn visit_crossword_values_r<T, C>(link: &Link<T>, label: char, key_tail: &mut Chars, joker: char, callback: &mut C)
+    where C: FnMut (&T) {
+
+    match *link {
+
+        None => (),
+
+        Some(ref node) => {
+
+            if label == joker || label < node.label {
+
+                visit_crossword_values_r(&node.left, label, key_tail, joker, callback);
+            }
+
+            if label == joker || label == node.label {
+
+                let mut new_tail = key_tail.clone();
+                let new_label = new_tail.next();
+
+                match new_label {
+
+                    None =>  if let Some(ref value) = node.value {
+
+                        callback(value);
+                    },
+
+                    Some(label) => visit_crossword_values_r(&node.middle, label, &mut new_tail, joker, callback)
+                }
+            }
+
+            if label == joker || label > node.label {
+
+                visit_crossword_values_r(&node.right, label, key_tail, joker, callback);
+            }
+        }
+    }
+}
+
+
+impl<T> Default for PersistentTst<T> {
+
+    fn default() -> Self {
+
+        PersistentTst::new()
+    }
+}
+
+
+impl<T> PersistentTst<T> {
+
+    /// Create a new, empty `PersistentTst`.
+
+    pub fn new() -> Self {
+
+        PersistentTst { root: None, count: 0 }
+    }
+
+
+    /// Inserts `key` and `value` pair in the tree, returning any value previously associated with `key`. An empty
+    /// `key` is meaningless, and `value` is given back. See [`Tst::insert`]( ./struct.Tst.html#method.insert).
+    ///
+    /// Values are returned in an `Arc`, since an old value may still be held by other copies of the tree.
+
+    pub fn insert(&mut self, key: &str, value: T) -> Option<Arc<T>> {
+
+        let mut key_tail = key.chars();
+
+        match key_tail.next() {
+
+            None => Some(Arc::new(value)),
+
+            Some(label) => {
+
+                let old_value = insert_r(&mut self.root, label, key_tail, Arc::new(value));
+
+                if old_value.is_none() {
+
+                    self.count += 1;
+                }
+
+                old_value
+            }
+        }
+    }
+
+
+    /// Returns an immutable reference to the value associated with `key`, or None.
+
+    pub fn get(&self, key: &str) -> Option<&T> {
+
+        let mut key_tail = key.chars();
+
+        match key_tail.next() {
+
+            None => None,
+
+            Some(label) => get_r(&self.root, label, &mut key_tail)
+        }
+    }
+
+
+    /// Removes the value associated with `key` from the tree, and returns it. Does nothing if no value is
+    /// associated with `key`, and returns `None`. Nodes are only copied when the key is found.
+
+    pub fn remove(&mut self, key: &str) -> Option<Arc<T>> {
+
+        let mut key_tail = key.chars();
+
+        let old_value = match key_tail.next() {
+
+            Some(label) if self.get(key).is_some() => remove_r(&mut self.root, label, &mut key_tail),
+
+            _ => None
+        };
+
+        if old_value.is_some() {
+
+            self.count -= 1;
+        }
+
+        old_value
+    }
+
+
+    /// Returns the number of values stored in the tree.
+
+    pub fn len(&self) -> usize {
+
+        self.count
+    }
+
+
+    /// Returns `true` if the tree stores no value.
+
+    pub fn is_empty(&self) -> bool {
+
+        self.len() == 0
+    }
+
+
+    /// Returns the number of values whose key begins with `prefix`, in time proportional to the length of
+    /// `prefix`. See [`Tst::count_prefix`]( ./struct.Tst.html#method.count_prefix).
+    ///
+    /// ```
+    /// # use ternary_tree::PersistentTst;
+    /// let mut map = PersistentTst::new();
+    ///
+    /// map.insert("foo", "🍄🍄");
+    /// map.insert("bar", "🐟");
+    /// map.insert("baz", "㵅");
+    ///
+    /// assert_eq!(map.count_prefix("ba"), 2);
+    /// assert_eq!(map.count_prefix(""), 3);
+    /// ```
+
+    pub fn count_prefix(&self, prefix: &str) -> usize {
+
+        let mut prefix_tail = prefix.chars();
+
+        match prefix_tail.next() {
+
+            None => self.count,
+
+            Some(label) => count(find_complete_root_r(&self.root, label, prefix_tail))
+        }
+    }
+
+
+    /// Returns the number of keys which come before `key` in alphabetical order. See [`Tst::rank`](
+    /// ./struct.Tst.html#method.rank).
+
+    pub fn rank(&self, key: &str) -> usize {
+
+        let mut key_tail = key.chars();
+
+        match key_tail.next() {
+
+            None => 0,
+
+            Some(label) => rank_r(&self.root, label, &mut key_tail)
+        }
+    }
+
+
+    /// Returns the key and the value found at position `n` (starting at 0) in alphabetical order of keys, or `None`
+    /// if the tree holds `n` values or less. See [`Tst::select`]( ./struct.Tst.html#method.select).
+    ///
+    /// ```
+    /// # use ternary_tree::PersistentTst;
+    /// let mut map = PersistentTst::new();
+    ///
+    /// map.insert("foo", "🍄🍄");
+    /// map.insert("bar", "🐟");
+    /// map.insert("baz", "㵅");
+    ///
+    /// assert_eq!(map.rank("baz"), 1);
+    /// assert_eq!(map.select(1), Some(("baz".to_string(), &"㵅")));
+    /// assert_eq!(map.select(3), None);
+    /// ```
+
+    pub fn select(&self, n: usize) -> Option<(String, &T)> {
+
+        let mut key = String::new();
+
+        select_r(&self.root, n, &mut key).map(|value| (key, value))
+    }
+
+
+    /// Returns true if both trees share the same root node, which means that neither of them changed since one
+    /// was cloned from the other.
+    ///
+    /// ```
+    /// # use ternary_tree::PersistentTst;
+    /// let mut map = PersistentTst::new();
+    /// map.insert("foo", 1);
+    ///
+    /// let snapshot = map.clone();
+    /// assert_eq!(map.ptr_eq(&snapshot), true);
+    ///
+    /// map.insert("bar", 2);
+    /// assert_eq!(map.ptr_eq(&snapshot), false);
+    /// ```
+
+    pub fn ptr_eq(&self, other: &PersistentTst<T>) -> bool {
+
+        match (&self.root, &other.root) {
+
+            (&None, &None) => true,
+
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+
+            _ => false
+        }
+    }
+
+
+    /// Walks the tree, gathers various metrics about nodes, keys and values, and returns a [`Stats`](
+    /// ./struct.Stats.html) structure to sum it up. Nodes shared with other copies of the tree are counted as
+    /// well, so `bytes.total` is an upper bound of the memory owned by this copy alone.
+
+    pub fn stat(&self) -> Stats {
+
+        let empty_stats: Stats = Default::default();
+
+        let mut stats = stat_r(empty_stats, &self.root, 0, 0, 0);
+
+        stats.bytes.node = mem::size_of::<PersistentNode<T>>();
+        stats.bytes.total = mem::size_of::<PersistentTst<T>>()+stats.count.nodes*stats.bytes.node;
+
+        stats
+    }
+
+
+    /// Deletes every node and value stored in the tree. Other copies of the tree are left untouched.
+
+    pub fn clear(&mut self) {
+
+        self.root = None;
+        self.count = 0;
+    }
+
+
+    /// Recursively walks the tree and calls `callback` closure on each immutable value. See [`Tst::visit_values`](
+    /// ./struct.Tst.html#method.visit_values).
+
+    pub fn visit_values<C>(&self, mut callback: C)
+    where C: FnMut (&T) {
+
+        visit_values_r(&self.root, &mut callback);
+    }
+
+
+    /// Recursively walks the tree and calls `callback` closure on each immutable value whose key begins with
+    /// `key_prefix`. See [`Tst::visit_complete_values`]( ./struct.Tst.html#method.visit_complete_values).
+
+    pub fn visit_complete_values<C>(&self, key_prefix: &str, mut callback: C)
+    where C: FnMut (&T) {
+
+        let mut prefix_tail = key_prefix.chars();
+
+        match prefix_tail.next() {
+
+            None => visit_values_r(&self.root, &mut callback),
+
+            Some(label) => {
+
+                let new_root = find_complete_root_r(&self.root, label, prefix_tail);
+                visit_complete_values_r(new_root, &mut callback)
+            }
+        }
+    }
+
+
+    /// Recursively walks the tree and calls `callback` closure on each immutable value whose key is _close_ to
+    /// `key` (Hamming distance of `range`). See [`Tst::visit_neighbor_values`](
+    /// ./struct.Tst.html#method.visit_neighbor_values).
+
+    pub fn visit_neighbor_values<C>(&self, key: &str, range: usize, mut callback: C)
+    where C: FnMut (&T) {
+
+        let mut key_tail = key.chars();
+        let key_len = key.chars().count();
+        let label = key_tail.next();
+        let tail_len = if key_len == 0 { 0 } else { key_len-1 };
+
+        visit_neighbor_values_r(&self.root, label, &mut key_tail, tail_len, range, &mut callback);
+    }
+
+
+    /// Recursively walks the tree and calls `callback` closure on each immutable value whose key _matches_
+    /// `pattern` with `joker` characters. See [`Tst::visit_crossword_values`](
+    /// ./struct.Tst.html#method.visit_crossword_values).
+
+    pub fn visit_crossword_values<C>(&self, pattern: &str, joker: char, mut callback: C)
+    where C: FnMut (&T) {
+
+        let mut pattern_tail = pattern.chars();
+
+        if let Some(label) = pattern_tail.next() {
+
+            visit_crossword_values_r(&self.root, label, &mut pattern_tail, joker, &mut callback);
+        }
+    }
+
+
+    /// Create a double-ended iterator which successively returns all values of the tree, in alphabetical order of
+    /// keys. See [`Tst::iter`]( ./struct.Tst.html#method.iter).
+
+    pub fn iter(&self) -> PersistentTstIterator<'_, T> {
+
+        PersistentTstIterator::<T>::new(self)
+    }
+
+
+    /// Create a double-ended iterator which successively returns all values whose key begins with `prefix`. See
+    /// [`Tst::iter_complete`]( ./struct.Tst.html#method.iter_complete).
+
+    pub fn iter_complete(&self, prefix: &str) -> PersistentTstCompleteIterator<'_, T> {
+
+        PersistentTstCompleteIterator::<T>::new(self, prefix)
+    }
+
+
+    /// Create a double-ended iterator which successively returns all values whose key is _close_ to `key`. See
+    /// [`Tst::iter_neighbor`]( ./struct.Tst.html#method.iter_neighbor).
+
+    pub fn iter_neighbor<'a, 'b>(&'a self, key: &'b str, range: usize) -> PersistentTstNeighborIterator<'a, 'b, T> {
+
+        PersistentTstNeighborIterator::<T>::new(self, key, range)
+    }
+
+
+    /// Create a double-ended iterator which successively returns all values whose key _matches_ `pattern`. See
+    /// [`Tst::iter_crossword`]( ./struct.Tst.html#method.iter_crossword).
+
+    pub fn iter_crossword<'a, 'b>(&'a self, pattern: &'b str, joker: char) -> PersistentTstCrosswordIterator<'a, 'b, T> {
+
+        PersistentTstCrosswordIterator::<T>::new(self, pattern, joker)
+    }
+}
+
+
+// Cloning a tree does not clone any node nor value, and does not need `T: Clone`.
+
+impl<T> Clone for PersistentTst<T> {
+
+    fn clone(&self) -> PersistentTst<T> {
+
+        PersistentTst { root: self.root.clone(), count: self.count }
+    }
+}
+
+
+impl<'a, T> NodeRef for &'a PersistentNode<T> {
+
+    type Value = &'a T;
+
+    fn label(&self) -> char {
+
+        self.label
+    }
+
+
+    fn run(&self) -> &str {
+
+        ""
+    }
+
+
+    fn value(&self) -> Option<&'a T> {
+
+        self.value.as_deref()
+    }
+
+
+    fn left(&self) -> Option<Self> {
+
+        self.left.as_deref()
+    }
+
+
+    fn middle(&self) -> Option<Self> {
+
+        self.middle.as_deref()
+    }
+
+
+    fn right(&self) -> Option<Self> {
+
+        self.right.as_deref()
+    }
+
+
+    fn is(&self, other: &Self) -> bool {
+
+        ptr::eq(*self, *other)
+    }
+}
+
+
+/// A [double-ended]( http://doc.rust-lang.org/std/iter/trait.DoubleEndedIterator.html) iterator which
+/// successively returns all values of the tree. See [`iter`]( struct.PersistentTst.html#method.iter) method for a
+/// brief description with a short example.
+
+#[derive(Debug)]
+pub struct PersistentTstIterator<'a, T: 'a> {
+
+    it: Walker<&'a PersistentNode<T>>
+}
+
+
+impl<'a, T> PersistentTstIterator<'a, T> {
+
+    pub fn new(tst: &'a PersistentTst<T>) -> Self {
+
+        PersistentTstIterator { it: Walker::new(tst.root.as_deref(), true) }
+    }
+
+
+    pub fn current_key(&self) -> String {
+
+        self.it.current_key()
+    }
+
+
+    pub fn current_key_back(&self) -> String {
+
+        self.it.current_key_back()
+    }
+}
+
+
+impl<'a, T> Iterator for PersistentTstIterator<'a, T> {
+
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+
+        self.it.next()
+    }
+}
+
+
+impl<'a, T> IntoIterator for &'a PersistentTst<T> {
+
+    type Item = &'a T;
+    type IntoIter = PersistentTstIterator<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+
+        self.iter()
+    }
+}
+
+
+impl<'a, T> DoubleEndedIterator for PersistentTstIterator<'a, T> {
+
+    fn next_back(&mut self) -> Option<&'a T> {
+
+        self.it.next_back()
+    }
+}
+
+
+/// A [double-ended]( http://doc.rust-lang.org/std/iter/trait.DoubleEndedIterator.html) iterator which
+/// successively returns all values whose key begins with `prefix`. See [`iter_complete`](
+/// struct.PersistentTst.html#method.iter_complete) method for a brief description with a short example.
+
+#[derive(Debug)]
+pub struct PersistentTstCompleteIterator<'a, T: 'a> {
+
+    it: CompleteWalker<&'a PersistentNode<T>>
+}
+
+
+impl<'a, T> PersistentTstCompleteIterator<'a, T> {
+
+    pub fn new(tst: &'a PersistentTst<T>, key_prefix: &str) -> Self {
+
+        PersistentTstCompleteIterator { it: CompleteWalker::new(tst.root.as_deref(), key_prefix) }
+    }
+
+
+    pub fn current_key(&self) -> String {
+
+        self.it.current_key()
+    }
+
+
+    pub fn current_key_back(&self) -> String {
+
+        self.it.current_key_back()
+    }
+}
+
+
+impl<'a, T> Iterator for PersistentTstCompleteIterator<'a, T> {
+
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+
+        self.it.next()
+    }
+}
+
+
+impl<'a, T> DoubleEndedIterator for PersistentTstCompleteIterator<'a, T> {
+
+    fn next_back(&mut self) -> Option<&'a T> {
+
+        self.it.next_back()
+    }
+}
+
+
+/// A [double-ended]( http://doc.rust-lang.org/std/iter/trait.DoubleEndedIterator.html) iterator which
+/// successively returns all values whose key is _close_ to `key`. See [`iter_neighbor`](
+/// struct.PersistentTst.html#method.iter_neighbor) method for a brief description with a short example.
+
+#[derive(Debug)]
+pub struct PersistentTstNeighborIterator<'a, 'b, T: 'a> {
+
+    it: NeighborWalker<'b, &'a PersistentNode<T>>
+}
+
+
+impl<'a, 'b, T> PersistentTstNeighborIterator<'a, 'b, T> {
+
+    pub fn new(tst: &'a PersistentTst<T>, key: &'b str, range: usize) -> Self {
+
+        PersistentTstNeighborIterator { it: NeighborWalker::new(tst.root.as_deref(), key, range) }
+    }
+
+
+    pub fn current_key(&self) -> String {
+
+        self.it.current_key()
+    }
+
+
+    pub fn current_key_back(&self) -> String {
+
+        self.it.current_key_back()
+    }
+}
+
+
+impl<'a, 'b, T> Iterator for PersistentTstNeighborIterator<'a, 'b, T> {
+
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+
+        self.it.next()
+    }
+}
+
+
+impl<'a, 'b, T> DoubleEndedIterator for PersistentTstNeighborIterator<'a, 'b, T> {
+
+    fn next_back(&mut self) -> Option<&'a T> {
+
+        self.it.next_back()
+    }
+}
+
+
+/// A [double-ended]( http://doc.rust-lang.org/std/iter/trait.DoubleEndedIterator.html) iterator which
+/// successively returns all values whose key _matches_ `pattern`. See [`iter_crossword`](
+/// struct.PersistentTst.html#method.iter_crossword) method for a brief description with a short example.
+
+#[derive(Debug)]
+pub struct PersistentTstCrosswordIterator<'a, 'b, T: 'a> {
+
+    it: CrosswordWalker<'b, &'a PersistentNode<T>>
+}
+
+
+impl<'a, 'b, T> PersistentTstCrosswordIterator<'a, 'b, T> {
+
+    pub fn new(tst: &'a PersistentTst<T>, key: &'b str, joker: char) -> Self {
+
+        PersistentTstCrosswordIterator { it: CrosswordWalker::new(tst.root.as_deref(), key, joker) }
+    }
+
+
+    pub fn current_key(&self) -> String {
+
+        self.it.current_key()
+    }
+
+
+    pub fn current_key_back(&self) -> String {
+
+        self.it.current_key_back()
+    }
+}
+
+
+impl<'a, 'b, T> Iterator for PersistentTstCrosswordIterator<'a, 'b, T> {
+
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+
+        self.it.next()
+    }
+}
+
+
+impl<'a, 'b, T> DoubleEndedIterator for PersistentTstCrosswordIterator<'a, 'b, T> {
+
+    fn next_back(&mut self) -> Option<&'a T> {
+
+        self.it.next_back()
+    }
+}
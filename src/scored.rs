@@ -0,0 +1,491 @@
+use std::str::Chars;
+use std::cmp::Ordering;
+use std::cmp::Ordering::Less;
+use std::cmp::Ordering::Equal;
+use std::cmp::Ordering::Greater;
+use std::collections::BinaryHeap;
+use std::fmt;
+
+
+/// A `ScoredTst` is a ternary tree whose values come with a score, for instance the number of times a word was
+/// searched. Each node caches the highest score found below it (siblings included), so that [`top_k_complete`](
+/// ./struct.ScoredTst.html#method.top_k_complete) can find the best completions of a prefix with a best-first
+/// search, skipping every subtree whose cached score is too low, instead of walking all of them.
+///
+/// ```
+/// # use ternary_tree::ScoredTst;
+/// let mut map = ScoredTst::new();
+///
+/// map.insert("python", "🐍", 50);
+/// map.insert("python3", "🐍3", 80);
+/// map.insert("pythonic", "✨", 20);
+/// map.insert("pyramid", "🔺", 70);
+///
+/// let best: Vec<_> = map.top_k_complete("py", 2).into_iter().map(|(key, _, score)| (key, score)).collect();
+///
+/// assert_eq!(best, vec![("python3".to_string(), 80), ("pyramid".to_string(), 70)]);
+/// ```
+///
+/// The cached scores are updated by `insert` and `remove`, along the path of the changed key.
+
+pub struct ScoredTst<T> {
+
+    root: Link<T>,
+    count: usize
+}
+
+
+type Link<T> = Option<Box<ScoredNode<T>>>;
+
+
+struct ScoredNode<T> {
+
+    label: char,
+    value: Option<(T, u64)>,
+    max_score: u64,
+    left: Link<T>,
+    middle: Link<T>,
+    right: Link<T>
+}
+
+
+impl<T> Default for ScoredNode<T> {
+
+    fn default() -> ScoredNode<T> {
+
+        ScoredNode {
+
+            label: '\0',
+            value: None,
+            max_score: 0,
+            left: None,
+            middle: None,
+            right: None
+        }
+    }
+}
+
+
+impl<T> fmt::Debug for ScoredNode<T> {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+
+            let value_box = match self.value {
+
+                None => "☐", Some(_) => "☑"
+            };
+
+        write!(f, "{}-{} ({})", value_box, self.label, self.max_score)
+    }
+}
+
+
+fn max_score<T>(link: &Link<T>) -> u64 {
+
+    match *link {
+
+        None => 0,
+
+        Some(ref node) => node.max_score
+    }
+}
+
+
+// The highest score of the node value and of its three subtrees. Called on the way back up from a change.
+
+fn update_max_score<T>(node: &mut ScoredNode<T>) {
+
+    let own_score = match node.value {
+
+        None => 0,
+
+        Some((_, score)) => score
+    };
+
+    node.max_score = own_score.max(max_score(&node.left)).max(max_score(&node.middle)).max(max_score(&node.right));
+}
+
+
+fn insert_r<T>(link: &mut Link<T>, label: char, mut key_tail: Chars, value: T, score: u64) -> Option<(T, u64)> {
+
+    if link.is_none() {
+
+        *link = Some(Box::new(ScoredNode::<T>{label, .. Default::default()}));
+    }
+
+    let node = match *link {
+
+        None => return None,
+
+        Some(ref mut node) => node
+    };
+
+    let old_value = match label.cmp(&node.label) {
+
+        Less => insert_r(&mut node.left, label, key_tail, value, score),
+
+        Greater => insert_r(&mut node.right, label, key_tail, value, score),
+
+        Equal => {
+
+            let new_label = key_tail.next();
+
+            match new_label {
+
+                None => node.value.replace((value, score)),
+
+                Some(label) => insert_r(&mut node.middle, label, key_tail, value, score)
+            }
+        }
+    };
+
+    update_max_score(node);
+
+    old_value
+}
+
+
+fn get_r<'a, T>(link: &'a Link<T>, label: char, key_tail: &mut Chars) -> Option<&'a (T, u64)> {
+
+    match *link {
+
+        None => None,
+
+        Some(ref node) => match label.cmp(&node.label) {
+
+            Less => get_r(&node.left, label, key_tail),
+
+            Equal => {
+
+                let new_label = key_tail.next();
+
+                match new_label {
+
+                    None => node.value.as_ref(),
+
+                    Some(label) => get_r(&node.middle, label, key_tail)
+                }
+            },
+
+            Greater => get_r(&node.right, label, key_tail),
+        }
+    }
+}
+
+
+fn remove_r<T>(link: &mut Link<T>, label: char, key_tail: &mut Chars) -> Option<(T, u64)> {
+
+    let node = match *link {
+
+        None => return None,
+
+        Some(ref mut node) => node
+    };
+
+    let old_value = match label.cmp(&node.label) {
+
+        Less => remove_r(&mut node.left, label, key_tail),
+
+        Greater => remove_r(&mut node.right, label, key_tail),
+
+        Equal => {
+
+            let new_label = key_tail.next();
+
+            match new_label {
+
+                None => node.value.take(),
+
+                Some(label) => remove_r(&mut node.middle, label, key_tail)
+            }
+        }
+    };
+
+    update_max_score(node);
+
+    if node.value.is_none() && node.left.is_none() && node.middle.is_none() && node.right.is_none() {
+
+        *link = None;
+    }
+
+    old_value
+}
+
+
+fn find_complete_root_r<'a, T>(link: &'a Link<T>, label: char, mut key_tail: Chars) -> &'a Link<T> {
+
+    match *link {
+
+        None => link,
+
+        Some(ref node) => match label.cmp(&node.label) {
+
+            Less => find_complete_root_r(&node.left, label, key_tail),
+
+            Greater => find_complete_root_r(&node.right, label, key_tail),
+
+            Equal => {
+
+                let new_label = key_tail.next();
+
+                match new_label {
+
+                    None => &node.middle,
+
+                    Some(label) => find_complete_root_r(&node.middle, label, key_tail)
+                }
+            }
+        }
+    }
+}
+
+
+// An entry of the best-first search: either a whole subtree, whose keys all begin with `key`, or the value of a
+// node, whose key is `key`. Entries are sorted by score, and on equal scores, subtrees come first (so that every
+// value of this score is known before any is returned), then values in alphabetical order of keys.
+
+struct Candidate<'a, T: 'a> {
+
+    score: u64,
+    node: &'a ScoredNode<T>,
+    key: String,
+    is_value: bool
+}
+
+
+impl<'a, T> PartialEq for Candidate<'a, T> {
+
+    fn eq(&self, other: &Candidate<'a, T>) -> bool {
+
+        self.cmp(other) == Equal
+    }
+}
+
+
+impl<'a, T> Eq for Candidate<'a, T> {}
+
+
+impl<'a, T> PartialOrd for Candidate<'a, T> {
+
+    fn partial_cmp(&self, other: &Candidate<'a, T>) -> Option<Ordering> {
+
+        Some(self.cmp(other))
+    }
+}
+
+
+impl<'a, T> Ord for Candidate<'a, T> {
+
+    fn cmp(&self, other: &Candidate<'a, T>) -> Ordering {
+
+        self.score.cmp(&other.score)
+            .then_with(|| other.is_value.cmp(&self.is_value))
+            .then_with(|| other.key.cmp(&self.key))
+    }
+}
+
+
+fn push_subtree<'a, T>(heap: &mut BinaryHeap<Candidate<'a, T>>, link: &'a Link<T>, key: &str) {
+
+    if let Some(ref node) = *link {
+
+        heap.push(Candidate { score: node.max_score, node, key: key.to_string(), is_value: false });
+    }
+}
+
+
+impl<T> Default for ScoredTst<T> {
+
+    fn default() -> Self {
+
+        ScoredTst::new()
+    }
+}
+
+
+impl<T> ScoredTst<T> {
+
+    /// Create a new, empty `ScoredTst`.
+
+    pub fn new() -> Self {
+
+        ScoredTst { root: None, count: 0 }
+    }
+
+
+    /// Inserts `key` and `value` pair in the tree, with its `score`, returning any value and score previously
+    /// associated with `key`. The cached scores along the path of `key` are updated. An empty `key` is
+    /// meaningless, and `value` and `score` are given back.
+
+    pub fn insert(&mut self, key: &str, value: T, score: u64) -> Option<(T, u64)> {
+
+        let mut key_tail = key.chars();
+
+        match key_tail.next() {
+
+            None => Some((value, score)),
+
+            Some(label) => {
+
+                let old_value = insert_r(&mut self.root, label, key_tail, value, score);
+
+                if old_value.is_none() {
+
+                    self.count += 1;
+                }
+
+                old_value
+            }
+        }
+    }
+
+
+    /// Returns an immutable reference to the value associated with `key`, or None.
+
+    pub fn get(&self, key: &str) -> Option<&T> {
+
+        self.get_scored(key).map(|(value, _)| value)
+    }
+
+
+    /// Returns the score of the value associated with `key`, or None.
+
+    pub fn get_score(&self, key: &str) -> Option<u64> {
+
+        self.get_scored(key).map(|&(_, score)| score)
+    }
+
+
+    fn get_scored(&self, key: &str) -> Option<&(T, u64)> {
+
+        let mut key_tail = key.chars();
+
+        match key_tail.next() {
+
+            None => None,
+
+            Some(label) => get_r(&self.root, label, &mut key_tail)
+        }
+    }
+
+
+    /// Removes the value associated with `key` from the tree, and returns it with its score. Does nothing if no
+    /// value is associated with `key`, and returns `None`.
+
+    pub fn remove(&mut self, key: &str) -> Option<(T, u64)> {
+
+        let mut key_tail = key.chars();
+
+        let old_value = match key_tail.next() {
+
+            None => None,
+
+            Some(label) => remove_r(&mut self.root, label, &mut key_tail)
+        };
+
+        if old_value.is_some() {
+
+            self.count -= 1;
+        }
+
+        old_value
+    }
+
+
+    /// Returns the number of values stored in the tree.
+
+    pub fn len(&self) -> usize {
+
+        self.count
+    }
+
+
+    /// Returns `true` if the tree stores no value.
+
+    pub fn is_empty(&self) -> bool {
+
+        self.len() == 0
+    }
+
+
+    /// Deletes every node and value stored in the tree.
+
+    pub fn clear(&mut self) {
+
+        self.root = None;
+        self.count = 0;
+    }
+
+
+    /// Returns the `k` values with the highest scores among those whose key begins with `prefix` (like
+    /// [`Tst::iter_complete`]( ./struct.Tst.html#method.iter_complete), the value of `prefix` itself is not
+    /// included). Each value comes with its key and score, by decreasing score, and in alphabetical order of keys
+    /// for equal scores. An empty `prefix` ranks all values of the tree.
+    ///
+    /// ```
+    /// # use ternary_tree::ScoredTst;
+    /// let mut map = ScoredTst::new();
+    ///
+    /// map.insert("bar", 2, 10);
+    /// map.insert("baz", 3, 30);
+    /// map.insert("foo", 1, 20);
+    ///
+    /// let best = map.top_k_complete("", 2);
+    ///
+    /// assert_eq!(best, vec![("baz".to_string(), &3, 30), ("foo".to_string(), &1, 20)]);
+    /// ```
+
+    pub fn top_k_complete(&self, prefix: &str, k: usize) -> Vec<(String, &T, u64)> {
+
+        let mut prefix_tail = prefix.chars();
+
+        let root = match prefix_tail.next() {
+
+            None => &self.root,
+
+            Some(label) => find_complete_root_r(&self.root, label, prefix_tail)
+        };
+
+        let mut best = Vec::new();
+        let mut heap = BinaryHeap::new();
+
+        push_subtree(&mut heap, root, prefix);
+
+        while best.len() < k {
+
+            let candidate = match heap.pop() {
+
+                None => break,
+
+                Some(candidate) => candidate
+            };
+
+            let node = candidate.node;
+
+            if candidate.is_value {
+
+                if let Some((ref value, score)) = node.value {
+
+                    best.push((candidate.key, value, score));
+                }
+
+                continue;
+            }
+
+            push_subtree(&mut heap, &node.left, &candidate.key);
+            push_subtree(&mut heap, &node.right, &candidate.key);
+
+            let mut key = candidate.key;
+            key.push(node.label);
+
+            push_subtree(&mut heap, &node.middle, &key);
+
+            if let Some((_, score)) = node.value {
+
+                heap.push(Candidate { score, node, key, is_value: true });
+            }
+        }
+
+        best
+    }
+}
@@ -0,0 +1,79 @@
+use serde::ser::Serialize;
+use serde::ser::Serializer;
+use serde::ser::SerializeMap;
+use serde::de::Deserialize;
+use serde::de::Deserializer;
+use serde::de::MapAccess;
+use serde::de::Visitor;
+use std::fmt;
+use std::marker::PhantomData;
+
+use super::Tst;
+use super::sorted::sort_entries;
+use super::sorted::build_r;
+
+
+// A Tst is serialized as a map of keys to values, in key order.
+
+impl<T> Serialize for Tst<T> where T: Serialize {
+
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+
+        let mut it = self.iter();
+
+        while let Some(value) = it.next() {
+
+            map.serialize_entry(&it.current_key(), value)?;
+        }
+
+        map.end()
+    }
+}
+
+
+struct TstVisitor<T> {
+
+    marker: PhantomData<T>
+}
+
+
+impl<'de, T> Visitor<'de> for TstVisitor<T> where T: Deserialize<'de> {
+
+    type Value = Tst<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+
+        formatter.write_str("a map of string keys to values")
+    }
+
+    // Entries are read first, so that the tree is built at once from their sorted keys. The serialized map does
+    // not tell if the tree was created with `new_balanced`, so the loaded tree never is.
+
+    fn visit_map<M>(self, mut access: M) -> Result<Self::Value, M::Error> where M: MapAccess<'de> {
+
+        let mut entries: Vec<(String, T)> = Vec::with_capacity(access.size_hint().unwrap_or(0));
+
+        while let Some(entry) = access.next_entry()? {
+
+            entries.push(entry);
+        }
+
+        let mut entries = sort_entries(entries, |entries| entries.sort_by(|a, b| a.0.cmp(&b.0)));
+
+        let count = entries.len();
+        let root = build_r(&mut entries, 0);
+
+        Ok(Tst { root, count, balanced: false })
+    }
+}
+
+
+impl<'de, T> Deserialize<'de> for Tst<T> where T: Deserialize<'de> {
+
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+
+        deserializer.deserialize_map(TstVisitor { marker: PhantomData })
+    }
+}
@@ -0,0 +1,111 @@
+use super::Node;
+use super::Link;
+use super::merge_run;
+use super::update_height;
+use super::update_count;
+
+
+// An entry to build a tree from. The value is taken out of the entry when its node is built.
+
+pub type SortedEntry<T> = (String, Option<T>);
+
+
+// Turns `entries` into entries sorted by key, without empty or repeated keys. Unsorted entries are sorted by `sort`,
+// which must be stable, so that the last value of a repeated key wins, as with a sequence of inserts.
+
+pub fn sort_entries<T, S>(entries: Vec<(String, T)>, sort: S) -> Vec<SortedEntry<T>>
+where S: FnOnce(&mut Vec<SortedEntry<T>>) {
+
+    let mut entries: Vec<SortedEntry<T>> = entries.into_iter()
+        .filter(|(key, _)| !key.is_empty())
+        .map(|(key, value)| (key, Some(value)))
+        .collect();
+
+    if entries.windows(2).all(|pair| pair[0].0 < pair[1].0) {
+
+        return entries;
+    }
+
+    sort(&mut entries);
+
+    let mut unique: Vec<SortedEntry<T>> = Vec::with_capacity(entries.len());
+
+    for entry in entries {
+
+        match unique.last_mut() {
+
+            Some(last) if last.0 == entry.0 => *last = entry,
+
+            _ => unique.push(entry)
+        }
+    }
+
+    unique
+}
+
+
+fn label_at(key: &str, pos: usize) -> char {
+
+    key[pos..].chars().next().unwrap_or('\0')
+}
+
+
+// All keys of `entries` are sorted, share their first `pos` bytes, and are longer than that. The label of the node
+// is taken from the median entry, so that siblings form a balanced tree. Returns this label, the value of the key
+// which ends right after it, and the entries which go to the left, middle and right subtrees of the node.
+
+pub fn split_entries<T>(entries: &mut [SortedEntry<T>], pos: usize) -> (char, Option<T>, [&mut [SortedEntry<T>]; 3]) {
+
+    let len = entries.len();
+    let label = label_at(&entries[len/2].0, pos);
+
+    let start = entries.partition_point(|entry| label_at(&entry.0, pos) < label);
+    let end = entries.partition_point(|entry| label_at(&entry.0, pos) <= label);
+
+    let (before, rest) = entries.split_at_mut(start);
+    let (same, after) = rest.split_at_mut(end-start);
+
+    let next_pos = pos + label.len_utf8();
+
+    // Shorter keys come first: only the first entry with this label may end right after it.
+
+    let ends = same.iter().take_while(|entry| entry.0.len() == next_pos).count();
+    let (first, same) = same.split_at_mut(ends);
+
+    let value = first.first_mut().and_then(|entry| entry.1.take());
+
+    (label, value, [before, same, after])
+}
+
+
+pub fn new_node<T>(label: char, value: Option<T>, left: Link<T>, middle: Link<T>, right: Link<T>) -> Link<T> {
+
+    let mut node = Box::new(Node::<T>{label, value, left, middle, right, .. Default::default()});
+
+    merge_run(&mut node);
+    update_height(&mut node);
+    update_count(&mut node);
+
+    Some(node)
+}
+
+
+// Builds the subtree of `entries`, whose keys all share their first `pos` bytes.
+
+pub fn build_r<T>(entries: &mut [SortedEntry<T>], pos: usize) -> Link<T> {
+
+    if entries.is_empty() {
+
+        return None;
+    }
+
+    let (label, value, [before, same, after]) = split_entries(entries, pos);
+
+    let next_pos = pos + label.len_utf8();
+
+    let left = build_r(before, pos);
+    let middle = build_r(same, next_pos);
+    let right = build_r(after, pos);
+
+    new_node(label, value, left, middle, right)
+}
@@ -0,0 +1,691 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+use std::str;
+
+use super::Tst;
+
+
+/// The error returned by the text loaders of a `Tst`, such as [`read_tsv`]( ./struct.Tst.html#method.read_tsv).
+/// Parse errors tell where the problem is, with 1-based line and column numbers (the column counts characters, not
+/// bytes).
+
+#[derive(Debug)]
+pub enum TextError {
+
+    /// The underlying reader failed.
+    Io(io::Error),
+
+    /// A line could not be parsed.
+    Parse { line: usize, column: usize, message: String }
+}
+
+
+impl fmt::Display for TextError {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+
+        match *self {
+
+            TextError::Io(ref err) => write!(f, "{}", err),
+
+            TextError::Parse { line, column, ref message } => write!(f, "line {}, column {}: {}", line, column, message)
+        }
+    }
+}
+
+
+impl Error for TextError {
+
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+
+        match *self {
+
+            TextError::Io(ref err) => Some(err),
+
+            TextError::Parse { .. } => None
+        }
+    }
+}
+
+
+impl From<io::Error> for TextError {
+
+    fn from(err: io::Error) -> TextError {
+
+        TextError::Io(err)
+    }
+}
+
+
+fn parse_error(line: usize, text: &str, byte: usize, message: &str) -> TextError {
+
+    TextError::Parse { line, column: text[..byte].chars().count() + 1, message: message.to_string() }
+}
+
+
+// Calls `callback` with the number and the content of each line of `reader`, without its line terminator. Lines
+// which are not valid utf-8 are reported with the position of the first bad byte.
+
+fn for_each_line<F>(reader: &mut dyn BufRead, mut callback: F) -> Result<(), TextError>
+where F: FnMut(usize, &str) -> Result<(), TextError> {
+
+    let mut buffer = Vec::new();
+    let mut line_number = 0;
+
+    loop {
+
+        buffer.clear();
+
+        if reader.read_until(b'\n', &mut buffer)? == 0 {
+
+            return Ok(());
+        }
+
+        line_number += 1;
+
+        if buffer.last() == Some(&b'\n') {
+
+            buffer.pop();
+
+            if buffer.last() == Some(&b'\r') {
+
+                buffer.pop();
+            }
+        }
+
+        match str::from_utf8(&buffer) {
+
+            Ok(line) => callback(line_number, line)?,
+
+            Err(err) => {
+
+                let valid = str::from_utf8(&buffer[..err.valid_up_to()]).unwrap_or("");
+
+                return Err(parse_error(line_number, valid, valid.len(), "invalid utf-8"));
+            }
+        }
+    }
+}
+
+
+pub fn read_words<T, F>(tst: &mut Tst<T>, reader: &mut dyn BufRead, mut value: F) -> Result<usize, TextError>
+where F: FnMut(usize) -> T {
+
+    let mut count = 0;
+
+    for_each_line(reader, |line_number, line| {
+
+        if !line.is_empty() {
+
+            tst.insert(line, value(line_number));
+            count += 1;
+        }
+
+        Ok(())
+    })?;
+
+    Ok(count)
+}
+
+
+pub fn read_tsv<T, F, E>(tst: &mut Tst<T>, reader: &mut dyn BufRead, mut parse: F) -> Result<usize, TextError>
+where F: FnMut(&str) -> Result<T, E>, E: fmt::Display {
+
+    let mut count = 0;
+
+    for_each_line(reader, |line_number, line| {
+
+        if line.is_empty() {
+
+            return Ok(());
+        }
+
+        let tab = match line.find('\t') {
+
+            None => return Err(parse_error(line_number, line, line.len(), "missing tab after key")),
+
+            Some(tab) => tab
+        };
+
+        if tab == 0 {
+
+            return Err(parse_error(line_number, line, 0, "empty key"));
+        }
+
+        match parse(&line[tab + 1..]) {
+
+            Ok(value) => { tst.insert(&line[..tab], value); }
+
+            Err(err) => return Err(parse_error(line_number, line, tab + 1, &err.to_string()))
+        }
+
+        count += 1;
+
+        Ok(())
+    })?;
+
+    Ok(count)
+}
+
+
+// Arrays and objects nested deeper than this are rejected, instead of overflowing the stack of `value`.
+
+const MAX_JSON_DEPTH: usize = 128;
+
+
+// A tiny scanner for one line of JSON Lines, which only decodes strings, and skips over other JSON values. `depth`
+// is the number of arrays and objects around the current position.
+
+struct JsonScanner<'a> {
+
+    text: &'a str,
+    pos: usize,
+    line: usize,
+    depth: usize
+}
+
+
+impl<'a> JsonScanner<'a> {
+
+    fn error(&self, message: &str) -> TextError {
+
+        parse_error(self.line, self.text, self.pos, message)
+    }
+
+
+    fn peek(&self) -> Option<char> {
+
+        self.text[self.pos..].chars().next()
+    }
+
+
+    fn bump(&mut self) -> Option<char> {
+
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+
+        Some(c)
+    }
+
+
+    fn skip_whitespace(&mut self) {
+
+        while let Some(c) = self.peek() {
+
+            if c != ' ' && c != '\t' && c != '\r' && c != '\n' {
+
+                break;
+            }
+
+            self.pos += 1;
+        }
+    }
+
+
+    fn expect(&mut self, expected: char) -> Result<(), TextError> {
+
+        self.skip_whitespace();
+
+        if self.peek() != Some(expected) {
+
+            return Err(self.error(&format!("expected '{}'", expected)));
+        }
+
+        self.pos += 1;
+
+        Ok(())
+    }
+
+
+    fn hex4(&mut self) -> Result<u32, TextError> {
+
+        let mut code = 0;
+
+        for _ in 0..4 {
+
+            let digit = self.peek().and_then(|c| c.to_digit(16)).ok_or_else(|| self.error("bad unicode escape"))?;
+
+            code = code * 16 + digit;
+            self.pos += 1;
+        }
+
+        Ok(code)
+    }
+
+
+    fn string(&mut self) -> Result<String, TextError> {
+
+        self.expect('"')?;
+
+        let mut s = String::new();
+
+        loop {
+
+            let start = self.pos;
+
+            let c = self.bump().ok_or_else(|| self.error("unterminated string"))?;
+
+            match c {
+
+                '"' => return Ok(s),
+
+                '\\' => {
+
+                    let escaped = match self.bump() {
+
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+
+                        Some('u') => {
+
+                            let mut code = self.hex4()?;
+
+                            if (0xD800..0xDC00).contains(&code) {
+
+                                if !self.text[self.pos..].starts_with("\\u") {
+
+                                    self.pos = start;
+                                    return Err(self.error("lone surrogate in unicode escape"));
+                                }
+
+                                self.pos += 2;
+
+                                let low = self.hex4()?;
+
+                                if !(0xDC00..0xE000).contains(&low) {
+
+                                    self.pos = start;
+                                    return Err(self.error("lone surrogate in unicode escape"));
+                                }
+
+                                code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                            }
+
+                            match ::std::char::from_u32(code) {
+
+                                Some(c) => c,
+
+                                None => {
+
+                                    self.pos = start;
+                                    return Err(self.error("lone surrogate in unicode escape"));
+                                }
+                            }
+                        }
+
+                        _ => {
+
+                            self.pos = start;
+                            return Err(self.error("bad escape sequence"));
+                        }
+                    };
+
+                    s.push(escaped);
+                }
+
+                c if (c as u32) < 0x20 => {
+
+                    self.pos = start;
+                    return Err(self.error("control character in string"));
+                }
+
+                c => s.push(c)
+            }
+        }
+    }
+
+
+    fn literal(&mut self, word: &str) -> Result<(), TextError> {
+
+        if !self.text[self.pos..].starts_with(word) {
+
+            return Err(self.error("unexpected character"));
+        }
+
+        self.pos += word.len();
+
+        Ok(())
+    }
+
+
+    fn digits(&mut self) -> Result<(), TextError> {
+
+        let start = self.pos;
+
+        while let Some('0'..='9') = self.peek() {
+
+            self.pos += 1;
+        }
+
+        if self.pos == start {
+
+            return Err(self.error("expected a digit"));
+        }
+
+        Ok(())
+    }
+
+
+    fn number(&mut self) -> Result<(), TextError> {
+
+        if self.peek() == Some('-') {
+
+            self.pos += 1;
+        }
+
+        self.digits()?;
+
+        if self.peek() == Some('.') {
+
+            self.pos += 1;
+            self.digits()?;
+        }
+
+        if let Some('e') | Some('E') = self.peek() {
+
+            self.pos += 1;
+
+            if let Some('+') | Some('-') = self.peek() {
+
+                self.pos += 1;
+            }
+
+            self.digits()?;
+        }
+
+        Ok(())
+    }
+
+
+    fn enter(&mut self) -> Result<(), TextError> {
+
+        if self.depth == MAX_JSON_DEPTH {
+
+            return Err(self.error("too deeply nested value"));
+        }
+
+        self.depth += 1;
+        self.pos += 1;
+
+        Ok(())
+    }
+
+
+    // Skips over any JSON value, and returns its text.
+
+    fn value(&mut self) -> Result<&'a str, TextError> {
+
+        self.skip_whitespace();
+
+        let start = self.pos;
+
+        match self.peek() {
+
+            Some('"') => { self.string()?; }
+
+            Some('t') => self.literal("true")?,
+
+            Some('f') => self.literal("false")?,
+
+            Some('n') => self.literal("null")?,
+
+            Some('-') | Some('0'..='9') => self.number()?,
+
+            Some('[') => {
+
+                self.enter()?;
+                self.skip_whitespace();
+
+                if self.peek() == Some(']') {
+
+                    self.pos += 1;
+
+                } else {
+
+                    loop {
+
+                        self.value()?;
+                        self.skip_whitespace();
+
+                        match self.peek() {
+
+                            Some(',') => self.pos += 1,
+                            Some(']') => { self.pos += 1; break; }
+                            _ => return Err(self.error("expected ',' or ']'"))
+                        }
+                    }
+                }
+
+                self.depth -= 1;
+            }
+
+            Some('{') => {
+
+                self.enter()?;
+                self.skip_whitespace();
+
+                if self.peek() == Some('}') {
+
+                    self.pos += 1;
+
+                } else {
+
+                    loop {
+
+                        self.string()?;
+                        self.expect(':')?;
+                        self.value()?;
+                        self.skip_whitespace();
+
+                        match self.peek() {
+
+                            Some(',') => self.pos += 1,
+                            Some('}') => { self.pos += 1; break; }
+                            _ => return Err(self.error("expected ',' or '}'"))
+                        }
+                    }
+                }
+
+                self.depth -= 1;
+            }
+
+            _ => return Err(self.error("expected a value"))
+        }
+
+        let text = self.text;
+
+        Ok(&text[start..self.pos])
+    }
+}
+
+
+// Each line holds an object with a "key" string member and a "value" member, which is given as JSON text to
+// `parse`. Other members are ignored.
+
+pub fn read_json_lines<T, F, E>(tst: &mut Tst<T>, reader: &mut dyn BufRead, mut parse: F) -> Result<usize, TextError>
+where F: FnMut(&str) -> Result<T, E>, E: fmt::Display {
+
+    let mut count = 0;
+
+    for_each_line(reader, |line_number, line| {
+
+        if line.trim().is_empty() {
+
+            return Ok(());
+        }
+
+        let mut scanner = JsonScanner { text: line, pos: 0, line: line_number, depth: 0 };
+
+        let mut key = None;
+        let mut value = None;
+
+        scanner.expect('{')?;
+
+        loop {
+
+            scanner.skip_whitespace();
+
+            let name = scanner.string()?;
+
+            scanner.expect(':')?;
+            scanner.skip_whitespace();
+
+            let start = scanner.pos;
+
+            match name.as_str() {
+
+                "key" => key = Some((scanner.string()?, start)),
+
+                "value" => value = Some((scanner.value()?, start)),
+
+                _ => { scanner.value()?; }
+            }
+
+            scanner.skip_whitespace();
+
+            match scanner.peek() {
+
+                Some(',') => scanner.pos += 1,
+                Some('}') => { scanner.pos += 1; break; }
+                _ => return Err(scanner.error("expected ',' or '}'"))
+            }
+        }
+
+        scanner.skip_whitespace();
+
+        if scanner.pos < line.len() {
+
+            return Err(scanner.error("trailing characters after object"));
+        }
+
+        let (key, key_start) = key.ok_or_else(|| scanner.error("missing \"key\" member"))?;
+        let (value, value_start) = value.ok_or_else(|| scanner.error("missing \"value\" member"))?;
+
+        if key.is_empty() {
+
+            return Err(parse_error(line_number, line, key_start, "empty key"));
+        }
+
+        match parse(value) {
+
+            Ok(value) => { tst.insert(&key, value); }
+
+            Err(err) => return Err(parse_error(line_number, line, value_start, &err.to_string()))
+        }
+
+        count += 1;
+
+        Ok(())
+    })?;
+
+    Ok(count)
+}
+
+
+fn check_line(text: &str, what: &str) -> io::Result<()> {
+
+    if text.contains('\n') || text.contains('\r') {
+
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("line break in {}", what)));
+    }
+
+    Ok(())
+}
+
+
+pub fn write_words<T>(tst: &Tst<T>, writer: &mut dyn Write) -> io::Result<()> {
+
+    let mut it = tst.iter();
+
+    while it.next().is_some() {
+
+        let key = it.current_key();
+
+        check_line(&key, "key")?;
+        writeln!(writer, "{}", key)?;
+    }
+
+    Ok(())
+}
+
+
+pub fn write_tsv<T, F>(tst: &Tst<T>, writer: &mut dyn Write, mut format: F) -> io::Result<()>
+where F: FnMut(&T) -> String {
+
+    let mut it = tst.iter();
+
+    while let Some(value) = it.next() {
+
+        let key = it.current_key();
+        let value = format(value);
+
+        check_line(&key, "key")?;
+        check_line(&value, "value")?;
+
+        if key.contains('\t') {
+
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "tab in key"));
+        }
+
+        writeln!(writer, "{}\t{}", key, value)?;
+    }
+
+    Ok(())
+}
+
+
+fn push_json_string(out: &mut String, s: &str) {
+
+    out.push('"');
+
+    for c in s.chars() {
+
+        match c {
+
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c)
+        }
+    }
+
+    out.push('"');
+}
+
+
+pub fn write_json_lines<T, F>(tst: &Tst<T>, writer: &mut dyn Write, mut format: F) -> io::Result<()>
+where F: FnMut(&T) -> String {
+
+    let mut it = tst.iter();
+    let mut line = String::new();
+
+    while let Some(value) = it.next() {
+
+        let value = format(value);
+
+        check_line(&value, "value")?;
+
+        line.clear();
+        line.push_str("{\"key\":");
+        push_json_string(&mut line, &it.current_key());
+        line.push_str(",\"value\":");
+        line.push_str(&value);
+        line.push('}');
+
+        writeln!(writer, "{}", line)?;
+    }
+
+    Ok(())
+}
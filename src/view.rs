@@ -0,0 +1,531 @@
+use std::io;
+use std::str;
+use std::cmp::Ordering::Less;
+use std::cmp::Ordering::Equal;
+use std::cmp::Ordering::Greater;
+
+use super::skip_run;
+use super::walk::NodeRef;
+use super::walk::Walker;
+use super::walk::CompleteWalker;
+use super::walk::NeighborWalker;
+use super::walk::CrosswordWalker;
+use super::binary;
+use super::binary::NIL;
+
+
+/// A `TstView` runs queries directly over the bytes of a tree saved by [`write_to`](
+/// ./struct.Tst.html#method.write_to), for instance a memory-mapped file, without building any node. Nodes are
+/// decoded from the buffer when a query walks through them, and values are returned as slices of the buffer, holding
+/// the bytes written by their [`ValueCodec`]( ./trait.ValueCodec.html).
+///
+/// The whole buffer is checked when the view is opened (header, checksum, node layout, and bounds of every run and
+/// value), so that later queries can not read out of it.
+///
+/// ```
+/// # use ternary_tree::Tst;
+/// # use ternary_tree::TstView;
+/// # use ternary_tree::ValueCodec;
+/// # use ternary_tree::tst;
+/// let map = tst!["foo" => 1u32, "bar" => 2, "baz" => 3];
+///
+/// let mut bytes = Vec::new();
+/// map.write_to(&mut bytes).unwrap();
+///
+/// let view = TstView::open(&bytes).unwrap();
+/// assert_eq!(view.len(), 3);
+///
+/// let value = view.get("bar").unwrap();
+/// assert_eq!(u32::decode(value).unwrap(), 2);
+///
+/// let mut it = view.iter_complete("ba");
+/// it.next();
+/// assert_eq!(it.current_key(), "bar");
+/// ```
+
+#[derive(Clone, Copy)]
+pub struct TstView<'a> {
+
+    nodes: &'a [u8],
+    runs: &'a [u8],
+    values: &'a [u8],
+    count: usize
+}
+
+
+// A node decoded from the buffer. Its children are indices of other nodes, or NIL.
+
+#[derive(Clone, Copy)]
+struct ViewNode<'a> {
+
+    index: u32,
+    label: char,
+    run: &'a str,
+    value: Option<&'a [u8]>,
+    left: u32,
+    middle: u32,
+    right: u32
+}
+
+
+fn validate_r(view: &TstView, index: u32, next: &mut u32, nodes: u32, count: &mut usize) -> io::Result<()> {
+
+    if index == NIL {
+
+        return Ok(());
+    }
+
+    binary::check_next(index, next, nodes)?;
+
+    let record = binary::read_node(view.nodes, view.runs, index)?;
+
+    if record.value != NIL {
+
+        binary::read_value(view.values, record.value)?;
+        *count += 1;
+    }
+
+    validate_r(view, record.left, next, nodes, count)?;
+    validate_r(view, record.middle, next, nodes, count)?;
+    validate_r(view, record.right, next, nodes, count)
+}
+
+
+impl<'a> TstView<'a> {
+
+    /// Checks the content of `bytes` and opens a view over it. Returns an error of kind `InvalidData` if the
+    /// buffer does not hold a tree written by [`write_to`]( ./struct.Tst.html#method.write_to), or if any part of
+    /// it is damaged or truncated.
+    ///
+    /// ```
+    /// # use ternary_tree::TstView;
+    /// assert_eq!(TstView::open(b"not a tree").is_err(), true);
+    /// ```
+
+    pub fn open(bytes: &'a [u8]) -> io::Result<TstView<'a>> {
+
+        let header = binary::parse_header(bytes)?;
+
+        let (nodes, runs, values) = binary::split_body(&header, &bytes[binary::HEADER_LEN..])?;
+
+        let mut view = TstView { nodes, runs, values, count: 0 };
+
+        let mut next = 0;
+        let mut count = 0;
+
+        validate_r(&view, view.root(), &mut next, header.nodes, &mut count)?;
+
+        if next != header.nodes || count != header.values as usize {
+
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "bad node count in ternary tree file"));
+        }
+
+        view.count = count;
+
+        Ok(view)
+    }
+
+
+    fn root(&self) -> u32 {
+
+        if self.nodes.is_empty() { NIL } else { 0 }
+    }
+
+
+    // Decodes node `index`, which is known to be valid since the whole buffer was checked by `open`.
+
+    fn node(&self, index: u32) -> ViewNode<'a> {
+
+        let record = binary::read_node(self.nodes, self.runs, index).expect("node checked on open");
+
+        let value = if record.value == NIL {
+
+            None
+
+        } else {
+
+            Some(binary::read_value(self.values, record.value).expect("value checked on open"))
+        };
+
+        ViewNode {
+
+            index,
+            label: record.label,
+            run: str::from_utf8(&self.runs[record.run.0..record.run.1]).expect("run checked on open"),
+            value,
+            left: record.left,
+            middle: record.middle,
+            right: record.right
+        }
+    }
+
+
+    fn child(&self, index: u32) -> Option<ViewNode<'a>> {
+
+        if index == NIL { None } else { Some(self.node(index)) }
+    }
+
+
+    /// Returns the bytes of the value associated with `key`, wrapped in `Some`, or `None` if the key is not in
+    /// the tree.
+
+    pub fn get(&self, key: &str) -> Option<&'a [u8]> {
+
+        let mut key_tail = key.chars();
+
+        let mut label = key_tail.next()?;
+        let mut index = self.root();
+
+        while let Some(node) = self.child(index) {
+
+            match label.cmp(&node.label) {
+
+                Less => index = node.left,
+
+                Greater => index = node.right,
+
+                Equal => {
+
+                    if !skip_run(node.run, &mut key_tail) {
+
+                        return None;
+                    }
+
+                    match key_tail.next() {
+
+                        None => return node.value,
+
+                        Some(new_label) => {
+
+                            label = new_label;
+                            index = node.middle;
+                        }
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+
+    /// Returns the number of values stored in the tree.
+
+    pub fn len(&self) -> usize {
+
+        self.count
+    }
+
+
+    /// Returns true if the tree holds no value.
+
+    pub fn is_empty(&self) -> bool {
+
+        self.count == 0
+    }
+
+
+    /// Creates a [`TstViewIterator`]( ./struct.TstViewIterator.html) over all values, in key order.
+
+    pub fn iter(&self) -> TstViewIterator<'a> {
+
+        TstViewIterator::new(*self)
+    }
+
+
+    /// Creates a [`TstViewCompleteIterator`]( ./struct.TstViewCompleteIterator.html) over the values whose key
+    /// begins with `prefix`.
+
+    pub fn iter_complete(&self, prefix: &str) -> TstViewCompleteIterator<'a> {
+
+        TstViewCompleteIterator::new(*self, prefix)
+    }
+
+
+    /// Creates a [`TstViewNeighborIterator`]( ./struct.TstViewNeighborIterator.html) over the values whose key is
+    /// within `range` of `key`, with the same distance as [`iter_neighbor`]( ./struct.Tst.html#method.iter_neighbor).
+
+    pub fn iter_neighbor<'b>(&self, key: &'b str, range: usize) -> TstViewNeighborIterator<'a, 'b> {
+
+        TstViewNeighborIterator::new(*self, key, range)
+    }
+
+
+    /// Creates a [`TstViewCrosswordIterator`]( ./struct.TstViewCrosswordIterator.html) over the values whose key
+    /// matches `pattern`, where `joker` matches any character.
+
+    pub fn iter_crossword<'b>(&self, pattern: &'b str, joker: char) -> TstViewCrosswordIterator<'a, 'b> {
+
+        TstViewCrosswordIterator::new(*self, pattern, joker)
+    }
+}
+
+
+// A node of the view, with the view itself to decode its children, as seen by the walkers shared with the other
+// trees.
+
+#[derive(Clone, Copy)]
+struct ViewRef<'a> {
+
+    view: TstView<'a>,
+    node: ViewNode<'a>
+}
+
+
+impl<'a> ViewRef<'a> {
+
+    fn new(view: TstView<'a>, index: u32) -> Option<Self> {
+
+        view.child(index).map(|node| ViewRef { view, node })
+    }
+}
+
+
+impl<'a> NodeRef for ViewRef<'a> {
+
+    type Value = &'a [u8];
+
+    fn label(&self) -> char {
+
+        self.node.label
+    }
+
+
+    fn run(&self) -> &str {
+
+        self.node.run
+    }
+
+
+    fn value(&self) -> Option<&'a [u8]> {
+
+        self.node.value
+    }
+
+
+    fn left(&self) -> Option<Self> {
+
+        ViewRef::new(self.view, self.node.left)
+    }
+
+
+    fn middle(&self) -> Option<Self> {
+
+        ViewRef::new(self.view, self.node.middle)
+    }
+
+
+    fn right(&self) -> Option<Self> {
+
+        ViewRef::new(self.view, self.node.right)
+    }
+
+
+    fn is(&self, other: &Self) -> bool {
+
+        self.node.index == other.node.index
+    }
+}
+
+
+/// A [double-ended]( http://doc.rust-lang.org/std/iter/trait.DoubleEndedIterator.html) iterator which
+/// successively returns the bytes of all values of a [`TstView`]( ./struct.TstView.html).
+
+pub struct TstViewIterator<'a> {
+
+    it: Walker<ViewRef<'a>>
+}
+
+
+impl<'a> TstViewIterator<'a> {
+
+    fn new(view: TstView<'a>) -> Self {
+
+        TstViewIterator { it: Walker::new(ViewRef::new(view, view.root()), true) }
+    }
+
+
+    pub fn current_key(&self) -> String {
+
+        self.it.current_key()
+    }
+
+
+    pub fn current_key_back(&self) -> String {
+
+        self.it.current_key_back()
+    }
+}
+
+
+impl<'a> Iterator for TstViewIterator<'a> {
+
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+
+        self.it.next()
+    }
+}
+
+
+impl<'a> DoubleEndedIterator for TstViewIterator<'a> {
+
+    fn next_back(&mut self) -> Option<&'a [u8]> {
+
+        self.it.next_back()
+    }
+}
+
+
+/// A [double-ended]( http://doc.rust-lang.org/std/iter/trait.DoubleEndedIterator.html) iterator which
+/// successively returns the bytes of all values of a [`TstView`]( ./struct.TstView.html) whose key begins with
+/// `prefix`.
+
+pub struct TstViewCompleteIterator<'a> {
+
+    it: CompleteWalker<ViewRef<'a>>
+}
+
+
+impl<'a> TstViewCompleteIterator<'a> {
+
+    fn new(view: TstView<'a>, key_prefix: &str) -> Self {
+
+        TstViewCompleteIterator { it: CompleteWalker::new(ViewRef::new(view, view.root()), key_prefix) }
+    }
+
+
+    pub fn current_key(&self) -> String {
+
+        self.it.current_key()
+    }
+
+
+    pub fn current_key_back(&self) -> String {
+
+        self.it.current_key_back()
+    }
+}
+
+
+impl<'a> Iterator for TstViewCompleteIterator<'a> {
+
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+
+        self.it.next()
+    }
+}
+
+
+impl<'a> DoubleEndedIterator for TstViewCompleteIterator<'a> {
+
+    fn next_back(&mut self) -> Option<&'a [u8]> {
+
+        self.it.next_back()
+    }
+}
+
+
+/// A [double-ended]( http://doc.rust-lang.org/std/iter/trait.DoubleEndedIterator.html) iterator which
+/// successively returns the bytes of all values of a [`TstView`]( ./struct.TstView.html) whose key is _close_ to
+/// `key`.
+
+pub struct TstViewNeighborIterator<'a, 'b> {
+
+    it: NeighborWalker<'b, ViewRef<'a>>
+}
+
+
+impl<'a, 'b> TstViewNeighborIterator<'a, 'b> {
+
+    fn new(view: TstView<'a>, key: &'b str, range: usize) -> Self {
+
+        TstViewNeighborIterator { it: NeighborWalker::new(ViewRef::new(view, view.root()), key, range) }
+    }
+
+
+    pub fn current_key(&self) -> String {
+
+        self.it.current_key()
+    }
+
+
+    pub fn current_key_back(&self) -> String {
+
+        self.it.current_key_back()
+    }
+}
+
+
+impl<'a, 'b> Iterator for TstViewNeighborIterator<'a, 'b> {
+
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+
+        self.it.next()
+    }
+}
+
+
+impl<'a, 'b> DoubleEndedIterator for TstViewNeighborIterator<'a, 'b> {
+
+    fn next_back(&mut self) -> Option<&'a [u8]> {
+
+        self.it.next_back()
+    }
+}
+
+
+/// A [double-ended]( http://doc.rust-lang.org/std/iter/trait.DoubleEndedIterator.html) iterator which
+/// successively returns the bytes of all values of a [`TstView`]( ./struct.TstView.html) whose key _matches_
+/// `pattern`.
+
+pub struct TstViewCrosswordIterator<'a, 'b> {
+
+    it: CrosswordWalker<'b, ViewRef<'a>>
+}
+
+
+impl<'a, 'b> TstViewCrosswordIterator<'a, 'b> {
+
+    fn new(view: TstView<'a>, key: &'b str, joker: char) -> Self {
+
+        TstViewCrosswordIterator { it: CrosswordWalker::new(ViewRef::new(view, view.root()), key, joker) }
+    }
+
+
+    pub fn current_key(&self) -> String {
+
+        self.it.current_key()
+    }
+
+
+    pub fn current_key_back(&self) -> String {
+
+        self.it.current_key_back()
+    }
+}
+
+
+impl<'a, 'b> Iterator for TstViewCrosswordIterator<'a, 'b> {
+
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+
+        self.it.next()
+    }
+}
+
+
+impl<'a, 'b> DoubleEndedIterator for TstViewCrosswordIterator<'a, 'b> {
+
+    fn next_back(&mut self) -> Option<&'a [u8]> {
+
+        self.it.next_back()
+    }
+}
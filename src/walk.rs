@@ -0,0 +1,1071 @@
+use std::str::Chars;
+use std::cmp::Ordering::Less;
+use std::cmp::Ordering::Equal;
+use std::cmp::Ordering::Greater;
+
+use super::match_run;
+use super::crossing_run;
+use super::neighbor_run;
+use super::crossword_run;
+
+
+// The iterators of all trees of this crate (`Tst`, `ArenaTst`, `FrozenTst`, `PersistentTst` and `TstView`) are thin
+// wrappers around the walkers of this module, which only see the nodes of a tree through the `NodeRef` trait.
+
+
+#[derive(Debug, PartialEq)]
+pub enum TstIteratorAction {
+
+    GoLeft,
+    Visit,
+    GoMiddle,
+    GoRight
+}
+
+use self::TstIteratorAction::*;
+
+
+// A handle on a node of some tree. Nodes of a `Tst` or of a `TstView` hold a run of characters after their label,
+// while nodes of other trees always have an empty run.
+
+pub trait NodeRef: Copy {
+
+    type Value;
+
+    fn label(&self) -> char;
+    fn run(&self) -> &str;
+    fn value(&self) -> Option<Self::Value>;
+
+    fn left(&self) -> Option<Self>;
+    fn middle(&self) -> Option<Self>;
+    fn right(&self) -> Option<Self>;
+
+    // Tells if both handles are on the same node.
+    fn is(&self, other: &Self) -> bool;
+}
+
+
+// Regenerates the key of the last value returned by a walk, from the nodes left in its `todo` stack with one of
+// actions `a1` or `a2`.
+
+fn path<N, S>(todo: &[(N, TstIteratorAction, S)], a1: TstIteratorAction, a2: TstIteratorAction) -> String
+where N: NodeRef {
+
+    let mut path = String::new();
+
+    for todo in todo.iter() {
+
+        if todo.1 == a1 || todo.1 == a2 {
+
+            path.push(todo.0.label());
+            path.push_str(todo.0.run());
+        }
+    }
+
+    path
+}
+
+
+// Tells if `node` was already returned by the other end of a walk, so that both ends stop when they meet.
+
+fn met<N: NodeRef>(node: &N, last: &Option<N>) -> bool {
+
+    match *last {
+
+        Some(ref last) => node.value().is_some() && node.is(last),
+
+        None => false
+    }
+}
+
+
+// Walks all values below a root, from both ends. Each step of the walk holds a node and the next action to take on
+// it (the unit is there so that `path` works with the steps of all walks).
+
+#[derive(Debug)]
+pub struct Walker<N> {
+
+    todo_i: Vec<(N, TstIteratorAction, ())>,
+    last_i: Option<N>,
+
+    todo_j: Vec<(N, TstIteratorAction, ())>,
+    last_j: Option<N>,
+
+    root: Option<N>,
+    root_siblings: bool
+}
+
+
+impl<N: NodeRef> Walker<N> {
+
+    // Walks all nodes reachable from `root` or, without `root_siblings`, the value and the middle child of `root`
+    // only.
+
+    pub fn new(root: Option<N>, root_siblings: bool) -> Self {
+
+        let mut it = Walker {
+
+            todo_i: Vec::new(), last_i: None,
+            todo_j: Vec::new(), last_j: None,
+            root,
+            root_siblings
+        };
+
+        it.restart_front();
+        it.restart_back();
+
+        it
+    }
+
+
+    pub fn current_key(&self) -> String {
+
+        path(&self.todo_i, GoMiddle, GoRight)
+    }
+
+
+    pub fn current_key_back(&self) -> String {
+
+        path(&self.todo_j, Visit, GoLeft)
+    }
+
+
+    pub fn seek(&mut self, key: &str) {
+
+        // The back of the walk stops at the last value before `key`, found with `next_back`
+
+        self.seek_back_to(key, false);
+        self.last_i = None;
+        self.next_back();
+
+        let last_before = self.last_j;
+
+        self.seek_front(key, true);
+        self.restart_back();
+
+        self.last_i = last_before;
+    }
+
+
+    pub fn seek_back(&mut self, key: &str) {
+
+        // The front of the walk stops at the first value after `key`, found with `next`
+
+        self.seek_front(key, false);
+        self.last_j = None;
+        self.next();
+
+        let first_after = self.last_i;
+
+        self.seek_back_to(key, true);
+        self.restart_front();
+
+        self.last_j = first_after;
+    }
+
+
+    pub fn restart_front(&mut self) {
+
+        self.todo_i.clear();
+        self.last_i = None;
+
+        if let Some(node) = self.root {
+
+            self.todo_i.push((node, if self.root_siblings { GoLeft } else { Visit }, ()));
+        }
+    }
+
+
+    pub fn restart_back(&mut self) {
+
+        self.todo_j.clear();
+        self.last_j = None;
+
+        if let Some(node) = self.root {
+
+            self.todo_j.push((node, if self.root_siblings { GoRight } else { GoMiddle }, ()));
+        }
+    }
+
+
+    pub fn clear(&mut self) {
+
+        self.todo_i.clear();
+        self.todo_j.clear();
+    }
+
+
+    // Fills `todo_i` as if `next` had just returned the last value before `key` (or before and at `key`, if not
+    // `inclusive`), by walking down from the root along `key`. Each node of the walk is left with the action which
+    // `next` would take next on it. Siblings of the root are skipped, unless the walk goes over them.
+
+    pub fn seek_front(&mut self, key: &str, inclusive: bool) {
+
+        self.restart_front();
+
+        let mut key_tail = key.chars();
+
+        let mut label = match key_tail.next() {
+
+            None => return,
+
+            Some(label) => label
+        };
+
+        self.todo_i.clear();
+
+        let mut next = self.root;
+        let mut siblings = self.root_siblings;
+
+        while let Some(node) = next {
+
+            match label.cmp(&node.label()) {
+
+                Less => {
+
+                    self.todo_i.push((node, Visit, ()));
+                    next = if siblings { node.left() } else { None };
+                }
+
+                Greater => next = if siblings { node.right() } else { None },
+
+                Equal => match crossing_run(node.run(), &mut key_tail) {
+
+                    Less => return self.todo_i.push((node, Visit, ())),
+
+                    Greater => return self.todo_i.push((node, GoRight, ())),
+
+                    Equal => match key_tail.next() {
+
+                        None => return self.todo_i.push((node, if inclusive { Visit } else { GoMiddle }, ())),
+
+                        Some(next_label) => {
+
+                            self.todo_i.push((node, GoRight, ()));
+
+                            label = next_label;
+                            next = node.middle();
+                            siblings = true;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+
+    // Same as `seek_front`, but fills `todo_j` as if `next_back` had just returned the first value after `key` (or
+    // after and at `key`, if not `inclusive`).
+
+    pub fn seek_back_to(&mut self, key: &str, inclusive: bool) {
+
+        self.restart_back();
+        self.todo_j.clear();
+
+        let mut key_tail = key.chars();
+
+        let mut label = match key_tail.next() {
+
+            None => return,
+
+            Some(label) => label
+        };
+
+        let mut next = self.root;
+        let mut siblings = self.root_siblings;
+
+        while let Some(node) = next {
+
+            match label.cmp(&node.label()) {
+
+                Less => next = if siblings { node.left() } else { None },
+
+                Greater => {
+
+                    self.todo_j.push((node, GoMiddle, ()));
+                    next = if siblings { node.right() } else { None };
+                }
+
+                Equal => match crossing_run(node.run(), &mut key_tail) {
+
+                    Less => return self.todo_j.push((node, GoLeft, ())),
+
+                    Greater => return self.todo_j.push((node, GoMiddle, ())),
+
+                    Equal => match key_tail.next() {
+
+                        None => return self.todo_j.push((node, if inclusive { Visit } else { GoLeft }, ())),
+
+                        Some(next_label) => {
+
+                            self.todo_j.push((node, Visit, ()));
+
+                            label = next_label;
+                            next = node.middle();
+                            siblings = true;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+
+    pub fn next(&mut self) -> Option<N::Value> {
+
+        while let Some((node, action, ())) = self.todo_i.pop() {
+
+            match action {
+
+                GoLeft => {
+
+                    self.todo_i.push((node, Visit, ()));
+
+                    if let Some(child) = node.left() {
+
+                        self.todo_i.push((child, GoLeft, ()));
+                    }
+                }
+
+                Visit => {
+
+                    if met(&node, &self.last_j) {
+
+                        self.clear();
+                        return None;
+                    }
+
+                    self.todo_i.push((node, GoMiddle, ()));
+
+                    if let Some(value) = node.value() {
+
+                        self.last_i = Some(node);
+                        return Some(value);
+                    }
+                }
+
+                GoMiddle => {
+
+                    self.todo_i.push((node, GoRight, ()));
+
+                    if let Some(child) = node.middle() {
+
+                        self.todo_i.push((child, GoLeft, ()));
+                    }
+                }
+
+                GoRight => {
+
+                    if self.todo_i.is_empty() && !self.root_siblings {
+
+                        continue;
+                    }
+
+                    if let Some(child) = node.right() {
+
+                        self.todo_i.push((child, GoLeft, ()));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+
+    pub fn next_back(&mut self) -> Option<N::Value> {
+
+        while let Some((node, action, ())) = self.todo_j.pop() {
+
+            match action {
+
+                GoRight => {
+
+                    self.todo_j.push((node, GoMiddle, ()));
+
+                    if let Some(child) = node.right() {
+
+                        self.todo_j.push((child, GoRight, ()));
+                    }
+                }
+
+                Visit => {
+
+                    if met(&node, &self.last_i) {
+
+                        self.clear();
+                        return None;
+                    }
+
+                    self.todo_j.push((node, GoLeft, ()));
+
+                    if let Some(value) = node.value() {
+
+                        self.last_j = Some(node);
+                        return Some(value);
+                    }
+                }
+
+                GoMiddle => {
+
+                    self.todo_j.push((node, Visit, ()));
+
+                    if let Some(child) = node.middle() {
+
+                        self.todo_j.push((child, GoRight, ()));
+                    }
+                }
+
+                GoLeft => {
+
+                    if self.todo_j.is_empty() && !self.root_siblings {
+
+                        continue;
+                    }
+
+                    if let Some(child) = node.left() {
+
+                        self.todo_j.push((child, GoRight, ()));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+
+// Walks the values whose key begins with a prefix. When the prefix ends inside a run, the walk starts at the node
+// of this run, and `prefix` is only the part of the prefix above this node.
+
+#[derive(Debug)]
+pub struct CompleteWalker<N> {
+
+    it: Walker<N>,
+    prefix: String,
+    key_prefix: String
+}
+
+
+impl<N: NodeRef> CompleteWalker<N> {
+
+    pub fn new(root: Option<N>, key_prefix: &str) -> Self {
+
+        let mut key_tail = key_prefix.chars();
+
+        let mut label = match key_tail.next() {
+
+            None => return CompleteWalker { it: Walker::new(root, true), prefix: String::new(), key_prefix: String::new() },
+
+            Some(label) => label
+        };
+
+        let mut next = root;
+
+        while let Some(node) = next {
+
+            match label.cmp(&node.label()) {
+
+                Less => next = node.left(),
+
+                Greater => next = node.right(),
+
+                Equal => {
+
+                    let matched = match_run(node.run(), &mut key_tail);
+                    let new_label = key_tail.next();
+
+                    if matched < node.run().chars().count() {
+
+                        if new_label.is_some() {
+
+                            break;
+                        }
+
+                        let prefix_len = key_prefix.chars().count() - 1 - matched;
+
+                        return CompleteWalker {
+
+                            it: Walker::new(Some(node), false),
+                            prefix: key_prefix.chars().take(prefix_len).collect(),
+                            key_prefix: key_prefix.to_string()
+                        };
+                    }
+
+                    match new_label {
+
+                        None => return CompleteWalker {
+
+                            it: Walker::new(node.middle(), true),
+                            prefix: key_prefix.to_string(),
+                            key_prefix: key_prefix.to_string()
+                        },
+
+                        Some(new_label) => {
+
+                            label = new_label;
+                            next = node.middle();
+                        }
+                    }
+                }
+            }
+        }
+
+        CompleteWalker { it: Walker::new(None, true), prefix: key_prefix.to_string(), key_prefix: key_prefix.to_string() }
+    }
+
+
+    pub fn current_key(&self) -> String {
+
+        self.prefix.clone() + &self.it.current_key()
+    }
+
+
+    pub fn current_key_back(&self) -> String {
+
+        self.prefix.clone() + &self.it.current_key_back()
+    }
+
+
+    pub fn seek(&mut self, key: &str) {
+
+        if key.starts_with(&self.key_prefix) {
+
+            self.it.seek(&key[self.prefix.len()..]);
+
+        } else if key < self.key_prefix.as_str() {
+
+            self.it.restart_front();
+            self.it.restart_back();
+
+        } else {
+
+            self.it.clear();
+        }
+    }
+
+
+    pub fn seek_back(&mut self, key: &str) {
+
+        if key.starts_with(&self.key_prefix) {
+
+            self.it.seek_back(&key[self.prefix.len()..]);
+
+        } else if key < self.key_prefix.as_str() {
+
+            self.it.clear();
+
+        } else {
+
+            self.it.restart_front();
+            self.it.restart_back();
+        }
+    }
+
+
+    pub fn next(&mut self) -> Option<N::Value> {
+
+        self.it.next()
+    }
+
+
+    pub fn next_back(&mut self) -> Option<N::Value> {
+
+        self.it.next_back()
+    }
+}
+
+
+// The next label and the tail of `key` to compare, the length of this tail, and the number of differences still
+// allowed.
+
+type NeighborState<'b> = (Option<char>, Chars<'b>, usize, usize);
+
+
+// Walks the values whose key is _close_ to a key (Hamming distance).
+
+#[derive(Debug)]
+pub struct NeighborWalker<'b, N> {
+
+    todo_i: Vec<(N, TstIteratorAction, NeighborState<'b>)>,
+    last_i: Option<N>,
+
+    todo_j: Vec<(N, TstIteratorAction, NeighborState<'b>)>,
+    last_j: Option<N>
+}
+
+
+impl<'b, N: NodeRef> NeighborWalker<'b, N> {
+
+    pub fn new(root: Option<N>, key: &'b str, range: usize) -> Self {
+
+        let mut it = NeighborWalker {
+
+            todo_i: Vec::new(), last_i: None,
+            todo_j: Vec::new(), last_j: None,
+        };
+
+        if let Some(node) = root {
+
+            let mut key_tail = key.chars();
+            let key_len = key.chars().count();
+            let label = key_tail.next();
+            let tail_len = if key_len == 0 { 0 } else { key_len-1 };
+
+            it.todo_i.push((node, GoLeft, (label, key_tail.clone(), tail_len, range)));
+            it.todo_j.push((node, GoRight, (label, key_tail, tail_len, range)));
+        }
+
+        it
+    }
+
+
+    pub fn current_key(&self) -> String {
+
+        path(&self.todo_i, GoMiddle, GoRight)
+    }
+
+
+    pub fn current_key_back(&self) -> String {
+
+        path(&self.todo_j, Visit, GoLeft)
+    }
+
+
+    // Tells if the value of `node` is within range, once its run is compared with the key.
+
+    fn in_range(node: &N, label: Option<char>, mut key_tail: Chars, tail_len: usize, range: usize) -> bool {
+
+        let delta = if label == Some(node.label()) { 0 } else { 1 };
+
+        if range < delta {
+
+            return false;
+        }
+
+        let mut new_len = tail_len;
+
+        match neighbor_run(node.run(), &mut key_tail, &mut new_len, range - delta) {
+
+            Some(new_range) => new_len <= new_range,
+
+            None => false
+        }
+    }
+
+
+    // Returns the state of the walk in the middle child of `node`, if the run of `node` is within range.
+
+    fn middle_state(node: &N, label: Option<char>, mut key_tail: Chars<'b>, tail_len: usize, range: usize)
+    -> Option<NeighborState<'b>> {
+
+        let delta = if label == Some(node.label()) { 0 } else { 1 };
+
+        if range < delta {
+
+            return None;
+        }
+
+        let mut new_len = tail_len;
+        let new_range = neighbor_run(node.run(), &mut key_tail, &mut new_len, range - delta)?;
+
+        let new_label = key_tail.next();
+        let new_len = if new_len > 0 { new_len-1 } else { new_len };
+
+        Some((new_label, key_tail, new_len, new_range))
+    }
+
+
+    pub fn next(&mut self) -> Option<N::Value> {
+
+        while let Some((node, action, (label, key_tail, tail_len, range))) = self.todo_i.pop() {
+
+            match action {
+
+                GoLeft => {
+
+                    self.todo_i.push((node, Visit, (label, key_tail.clone(), tail_len, range)));
+
+                    if let Some(label) = label {
+
+                        if range == 0 && label >= node.label() {
+
+                            continue;
+                        }
+                    }
+
+                    if let Some(child) = node.left() {
+
+                        self.todo_i.push((child, GoLeft, (label, key_tail, tail_len, range)));
+                    }
+                }
+
+                Visit => {
+
+                    if met(&node, &self.last_j) {
+
+                        self.todo_i.clear();
+                        self.todo_j.clear();
+                        return None;
+                    }
+
+                    self.todo_i.push((node, GoMiddle, (label, key_tail.clone(), tail_len, range)));
+
+                    if let Some(value) = node.value() {
+
+                        if Self::in_range(&node, label, key_tail, tail_len, range) {
+
+                            self.last_i = Some(node);
+                            return Some(value);
+                        }
+                    }
+                }
+
+                GoMiddle => {
+
+                    self.todo_i.push((node, GoRight, (label, key_tail.clone(), tail_len, range)));
+
+                    if let Some(child) = node.middle() {
+
+                        if let Some(state) = Self::middle_state(&node, label, key_tail, tail_len, range) {
+
+                            self.todo_i.push((child, GoLeft, state));
+                        }
+                    }
+                }
+
+                GoRight => {
+
+                    if let Some(label) = label {
+
+                        if range == 0 && label <= node.label() {
+
+                            continue;
+                        }
+                    }
+
+                    if let Some(child) = node.right() {
+
+                        self.todo_i.push((child, GoLeft, (label, key_tail, tail_len, range)));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+
+    pub fn next_back(&mut self) -> Option<N::Value> {
+
+        while let Some((node, action, (label, key_tail, tail_len, range))) = self.todo_j.pop() {
+
+            match action {
+
+                GoRight => {
+
+                    self.todo_j.push((node, GoMiddle, (label, key_tail.clone(), tail_len, range)));
+
+                    if let Some(label) = label {
+
+                        if range == 0 && label <= node.label() {
+
+                            continue;
+                        }
+                    }
+
+                    if let Some(child) = node.right() {
+
+                        self.todo_j.push((child, GoRight, (label, key_tail, tail_len, range)));
+                    }
+                }
+
+                Visit => {
+
+                    if met(&node, &self.last_i) {
+
+                        self.todo_i.clear();
+                        self.todo_j.clear();
+                        return None;
+                    }
+
+                    self.todo_j.push((node, GoLeft, (label, key_tail.clone(), tail_len, range)));
+
+                    if let Some(value) = node.value() {
+
+                        if Self::in_range(&node, label, key_tail, tail_len, range) {
+
+                            self.last_j = Some(node);
+                            return Some(value);
+                        }
+                    }
+                }
+
+                GoMiddle => {
+
+                    self.todo_j.push((node, Visit, (label, key_tail.clone(), tail_len, range)));
+
+                    if let Some(child) = node.middle() {
+
+                        if let Some(state) = Self::middle_state(&node, label, key_tail, tail_len, range) {
+
+                            self.todo_j.push((child, GoRight, state));
+                        }
+                    }
+                }
+
+                GoLeft => {
+
+                    if let Some(label) = label {
+
+                        if range == 0 && label >= node.label() {
+
+                            continue;
+                        }
+                    }
+
+                    if let Some(child) = node.left() {
+
+                        self.todo_j.push((child, GoRight, (label, key_tail, tail_len, range)));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+
+// The next label and the tail of `pattern` to compare, and the length of this tail.
+
+type CrosswordState<'b> = (char, Chars<'b>, usize);
+
+
+// Walks the values whose key _matches_ a pattern, where a joker matches any character.
+
+#[derive(Debug)]
+pub struct CrosswordWalker<'b, N> {
+
+    todo_i: Vec<(N, TstIteratorAction, CrosswordState<'b>)>,
+    last_i: Option<N>,
+
+    todo_j: Vec<(N, TstIteratorAction, CrosswordState<'b>)>,
+    last_j: Option<N>,
+
+    joker: char
+}
+
+
+impl<'b, N: NodeRef> CrosswordWalker<'b, N> {
+
+    pub fn new(root: Option<N>, pattern: &'b str, joker: char) -> Self {
+
+        let mut it = CrosswordWalker {
+
+            todo_i: Vec::new(), last_i: None,
+            todo_j: Vec::new(), last_j: None,
+            joker
+        };
+
+        if let Some(node) = root {
+
+            let mut key_tail = pattern.chars();
+
+            if let Some(label) = key_tail.next() {
+
+                let tail_len = pattern.chars().count()-1;
+
+                it.todo_i.push((node, GoLeft, (label, key_tail.clone(), tail_len)));
+                it.todo_j.push((node, GoRight, (label, key_tail, tail_len)));
+            }
+        }
+
+        it
+    }
+
+
+    pub fn current_key(&self) -> String {
+
+        path(&self.todo_i, GoMiddle, GoRight)
+    }
+
+
+    pub fn current_key_back(&self) -> String {
+
+        path(&self.todo_j, Visit, GoLeft)
+    }
+
+
+    fn matches(&self, node: &N, label: char) -> bool {
+
+        label == self.joker || label == node.label()
+    }
+
+
+    // Tells if the pattern ends with the run of `node`.
+
+    fn ends_at(&self, node: &N, label: char, mut key_tail: Chars, tail_len: usize) -> bool {
+
+        tail_len == node.run().chars().count() && self.matches(node, label) &&
+            crossword_run(node.run(), &mut key_tail, self.joker)
+    }
+
+
+    // Returns the state of the walk in the middle child of `node`, if the run of `node` matches the pattern.
+
+    fn middle_state(&self, node: &N, label: char, mut key_tail: Chars<'b>, tail_len: usize)
+    -> Option<CrosswordState<'b>> {
+
+        if !self.matches(node, label) || !crossword_run(node.run(), &mut key_tail, self.joker) {
+
+            return None;
+        }
+
+        let new_label = key_tail.next()?;
+        let new_len = tail_len - node.run().chars().count() - 1;
+
+        Some((new_label, key_tail, new_len))
+    }
+
+
+    pub fn next(&mut self) -> Option<N::Value> {
+
+        while let Some((node, action, (label, key_tail, tail_len))) = self.todo_i.pop() {
+
+            match action {
+
+                GoLeft => {
+
+                    self.todo_i.push((node, Visit, (label, key_tail.clone(), tail_len)));
+
+                    if label == self.joker || label < node.label() {
+
+                        if let Some(child) = node.left() {
+
+                            self.todo_i.push((child, GoLeft, (label, key_tail, tail_len)));
+                        }
+                    }
+                }
+
+                Visit => {
+
+                    if met(&node, &self.last_j) {
+
+                        self.todo_i.clear();
+                        self.todo_j.clear();
+                        return None;
+                    }
+
+                    self.todo_i.push((node, GoMiddle, (label, key_tail.clone(), tail_len)));
+
+                    if let Some(value) = node.value() {
+
+                        if self.ends_at(&node, label, key_tail, tail_len) {
+
+                            self.last_i = Some(node);
+                            return Some(value);
+                        }
+                    }
+                }
+
+                GoMiddle => {
+
+                    self.todo_i.push((node, GoRight, (label, key_tail.clone(), tail_len)));
+
+                    if let Some(child) = node.middle() {
+
+                        if let Some(state) = self.middle_state(&node, label, key_tail, tail_len) {
+
+                            self.todo_i.push((child, GoLeft, state));
+                        }
+                    }
+                }
+
+                GoRight => {
+
+                    if label == self.joker || label > node.label() {
+
+                        if let Some(child) = node.right() {
+
+                            self.todo_i.push((child, GoLeft, (label, key_tail, tail_len)));
+                        }
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+
+    pub fn next_back(&mut self) -> Option<N::Value> {
+
+        while let Some((node, action, (label, key_tail, tail_len))) = self.todo_j.pop() {
+
+            match action {
+
+                GoRight => {
+
+                    self.todo_j.push((node, GoMiddle, (label, key_tail.clone(), tail_len)));
+
+                    if label == self.joker || label > node.label() {
+
+                        if let Some(child) = node.right() {
+
+                            self.todo_j.push((child, GoRight, (label, key_tail, tail_len)));
+                        }
+                    }
+                }
+
+                Visit => {
+
+                    if met(&node, &self.last_i) {
+
+                        self.todo_i.clear();
+                        self.todo_j.clear();
+                        return None;
+                    }
+
+                    self.todo_j.push((node, GoLeft, (label, key_tail.clone(), tail_len)));
+
+                    if let Some(value) = node.value() {
+
+                        if self.ends_at(&node, label, key_tail, tail_len) {
+
+                            self.last_j = Some(node);
+                            return Some(value);
+                        }
+                    }
+                }
+
+                GoMiddle => {
+
+                    self.todo_j.push((node, Visit, (label, key_tail.clone(), tail_len)));
+
+                    if let Some(child) = node.middle() {
+
+                        if let Some(state) = self.middle_state(&node, label, key_tail, tail_len) {
+
+                            self.todo_j.push((child, GoRight, state));
+                        }
+                    }
+                }
+
+                GoLeft => {
+
+                    if label == self.joker || label < node.label() {
+
+                        if let Some(child) = node.left() {
+
+                            self.todo_j.push((child, GoRight, (label, key_tail, tail_len)));
+                        }
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
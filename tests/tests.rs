@@ -1,5 +1,22 @@
 extern crate ternary_tree;
 use ternary_tree::Tst;
+use ternary_tree::TstView;
+use ternary_tree::ValueCodec;
+use ternary_tree::TextError;
+use ternary_tree::PersistentTst;
+use ternary_tree::ConcurrentTst;
+use ternary_tree::ScoredTst;
+use ternary_tree::TstDiff;
+use std::ops::Bound;
+
+#[cfg(feature = "serde")]
+extern crate serde_json;
+
+#[cfg(feature = "rayon")]
+extern crate rayon;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 
 #[test]
@@ -139,6 +156,7 @@ fn get_sample_map_abc_abc_with_unicode() -> Tst<std::string::String> {
 
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn tst_insert_and_get_more_key_value() {
 
     let map = get_sample_map_abc_count();
@@ -168,6 +186,7 @@ fn tst_iterate_over_empty_tree() {
 
 
 #[test]
+#[allow(clippy::while_let_on_iterator)]
 fn tst_iterate_over_values() {
 
     let map = get_sample_map_abc_abc();
@@ -270,6 +289,7 @@ fn tst_iterate_over_values_backward() {
 
 
 #[test]
+#[allow(clippy::while_let_on_iterator)]
 fn tst_iterate_over_values_backward_with_rev() {
 
     let map = get_sample_map_abc_abc();
@@ -301,6 +321,7 @@ fn tst_iterate_over_values_backward_with_rev() {
 
 
 #[test]
+#[allow(clippy::while_let_on_iterator)]
 fn tst_iterate_over_values_from_both_end() {
 
     let map = get_sample_map_abc_abc();
@@ -366,6 +387,7 @@ fn tst_iterate_over_values_from_both_end() {
 
 
 #[test]
+#[allow(suspicious_double_ref_op)]
 fn tst_visit_values() {
 
     let map = get_sample_map_abc_abc();
@@ -380,6 +402,7 @@ fn tst_visit_values() {
 
 
 #[test]
+#[allow(suspicious_double_ref_op)]
 fn tst_visit_complete_values() {
 
     let map = get_sample_map_abc_abc();
@@ -401,6 +424,7 @@ fn tst_visit_complete_values() {
 
 
 #[test]
+#[allow(clippy::bool_assert_comparison, suspicious_double_ref_op)]
 fn tst_visit_neighbor_values() {
 
     let map = get_sample_map_abc_abc();
@@ -450,6 +474,7 @@ fn tst_visit_neighbor_values() {
 
 
 #[test]
+#[allow(clippy::bool_assert_comparison, suspicious_double_ref_op)]
 fn tst_visit_crossword_values() {
 
     let map = get_sample_map_abc_abc();
@@ -491,6 +516,7 @@ fn tst_visit_crossword_values() {
 
 
 #[test]
+#[allow(clippy::while_let_on_iterator)]
 fn tst_iterate_with_complete() {
 
     let empty_map: Tst<bool> = Tst::new();
@@ -557,6 +583,7 @@ fn tst_iterate_with_complete() {
 
 
 #[test]
+#[allow(clippy::bool_assert_comparison, clippy::while_let_on_iterator)]
 fn tst_iterate_with_neighbor() {
 
     let map = get_sample_map_abc_abc();
@@ -707,6 +734,7 @@ fn tst_iterate_with_neighbor() {
 
 
 #[test]
+#[allow(clippy::while_let_on_iterator)]
 fn tst_fix_unicode_chars_in_key_bug() {
 
     let map = get_sample_map_abc_abc_with_unicode();
@@ -787,6 +815,7 @@ fn tst_fix_unicode_chars_in_key_bug() {
 
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn tst_iterate_with_neighbor_backward() {
 
     let map = get_sample_map_abc_abc();
@@ -967,6 +996,7 @@ fn tst_iterate_with_neighbor_backward() {
 
 
 #[test]
+#[allow(clippy::while_let_on_iterator)]
 fn tst_iterate_with_neighbor_from_both_end() {
 
     let map = get_sample_map_abc_abc();
@@ -1036,6 +1066,7 @@ fn tst_iterate_with_neighbor_from_both_end() {
 
 
 #[test]
+#[allow(clippy::bool_assert_comparison, clippy::while_let_on_iterator)]
 fn tst_iterate_with_crossword() {
 
     let map = get_sample_map_abc_abc();
@@ -1126,6 +1157,7 @@ fn tst_iterate_with_crossword() {
 
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn tst_iterate_with_crossword_backward() {
 
     let map = get_sample_map_abc_abc();
@@ -1230,6 +1262,7 @@ fn tst_iterate_with_crossword_backward() {
 
 
 #[test]
+#[allow(clippy::while_let_on_iterator)]
 fn tst_iterate_with_crossword_from_both_end() {
 
     let map = get_sample_map_abc_abc();
@@ -1299,6 +1332,7 @@ fn tst_iterate_with_crossword_from_both_end() {
 
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn tst_insert_and_remove_some_key_value() {
 
     let mut empty_map: Tst<bool> = Tst::new();
@@ -1399,6 +1433,7 @@ fn tst_insert_and_remove_more_key_value() {
 
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn tst_stats_on_insert_and_remove() {
 
     let empty_map: Tst<bool> = Tst::new();
@@ -1427,20 +1462,23 @@ fn tst_stats_on_insert_and_remove() {
     assert_eq!(s2.dist.is_empty(), false);
     assert_eq!(s2.key_len.min, 1);
     assert_eq!(s2.key_len.max, 3);
-    assert_eq!(s2.count.nodes, 20);
+    assert_eq!(s2.count.nodes, 16);
     assert_eq!(s2.count.values, 16);
     assert_eq!(s2.count.values, map.len());
 
-    //node struct size should be around 48 bytes on x64
-    assert_eq!(s2.bytes.node >= 24, true);
-    assert_eq!(s2.bytes.node <= 96, true);
+    //node struct size should be around 64 bytes on x64
+    assert!(s2.bytes.node >= 24);
+    assert!(s2.bytes.node <= 96);
 
-    //total size should be around 976 bytes on x64
-    assert_eq!(s2.bytes.total >= 488, true);
-    assert_eq!(s2.bytes.total <= 16+20*48, true);
+    //total size should be around 1116 bytes on x64 (including 4 runs of one character, of 17 bytes each)
+    assert!(s2.bytes.total >= 488);
+    assert!(s2.bytes.total <= 24+16*64+4*17);
 
-    assert_eq!(s1.bytes.node < s2.bytes.node, true);
-    assert_eq!(s1.bytes.total < s2.bytes.total, true);
+    //each node counts the values of its subtree
+    assert_eq!(s2.bytes.counts, 16*std::mem::size_of::<usize>());
+
+    assert!(s1.bytes.node < s2.bytes.node);
+    assert!(s1.bytes.total < s2.bytes.total);
 
     use ternary_tree::DistStat;
 
@@ -1509,7 +1547,7 @@ fn tst_clear_some_map() {
 
     let s = map.stat();
 
-    assert_eq!(s.count.nodes, 20);
+    assert_eq!(s.count.nodes, 16);
     assert_eq!(s.count.values, 16);
     assert_eq!(s.count.values, map.len());
 
@@ -1659,6 +1697,7 @@ fn tst_current_key_iterators() {
 
 
 #[test]
+#[allow(clippy::bool_assert_comparison, clippy::clone_on_copy)]
 fn tst_update_some_values() {
 
     let mut map = get_sample_map_abc_count();
@@ -1719,6 +1758,7 @@ fn tst_update_some_values() {
 
 
 #[test]
+#[allow(clippy::bool_assert_comparison, suspicious_double_ref_op)]
 fn tst_create_with_macro() {
 
     use ternary_tree::tst;
@@ -1733,17 +1773,17 @@ fn tst_create_with_macro() {
     assert_eq!(stat.dist.is_empty(), false);
     assert_eq!(stat.key_len.min, 1);
     assert_eq!(stat.key_len.max, 3);
-    assert_eq!(stat.count.nodes, 20);
+    assert_eq!(stat.count.nodes, 16);
     assert_eq!(stat.count.values, 16);
     assert_eq!(stat.count.values, map.len());
 
-    //node struct size should be around 48 bytes on x64
-    assert_eq!(stat.bytes.node >= 24, true);
-    assert_eq!(stat.bytes.node <= 96, true);
+    //node struct size should be around 64 bytes on x64
+    assert!(stat.bytes.node >= 24);
+    assert!(stat.bytes.node <= 96);
 
-    //total size should be around 976 bytes on x64
-    assert_eq!(stat.bytes.total >= 488, true);
-    assert_eq!(stat.bytes.total <= 16+20*48, true);
+    //total size should be around 1116 bytes on x64 (including 4 runs of one character, of 17 bytes each)
+    assert!(stat.bytes.total >= 488);
+    assert!(stat.bytes.total <= 24+16*64+4*17);
 
     use ternary_tree::DistStat;
 
@@ -1783,3 +1823,2898 @@ fn tst_pretty_print() {
 
     assert_eq!(s, r);
 }
+
+
+fn get_sorted_keys_abcdef() -> Vec<String> {
+
+    let mut keys = Vec::new();
+
+    for a in "abcdef".chars() {
+
+        keys.push(a.to_string());
+
+        for b in "abcdef".chars() {
+
+            keys.push(format!("{}{}", a, b));
+
+            for c in "abcdef".chars() {
+
+                keys.push(format!("{}{}{}", a, b, c));
+            }
+        }
+    }
+
+    keys
+}
+
+
+#[test]
+fn tst_balanced_insert_sorted_keys() {
+
+    let keys = get_sorted_keys_abcdef();
+
+    let mut map = Tst::new();
+    let mut balanced_map = Tst::new_balanced();
+
+    assert!(!map.is_balanced());
+    assert!(balanced_map.is_balanced());
+
+    for (i, k) in keys.iter().enumerate() {
+
+        assert_eq!(map.insert(k, i), None);
+        assert_eq!(balanced_map.insert(k, i), None);
+    }
+
+    assert_eq!(balanced_map.insert("abc", 0), map.insert("abc", 0));
+    assert_eq!(balanced_map.len(), keys.len());
+
+    let s = map.stat();
+    let balanced_s = balanced_map.stat();
+
+    assert_eq!(balanced_s.count.nodes, s.count.nodes);
+    assert_eq!(balanced_s.count.values, s.count.values);
+    assert_eq!(s.dist.len(), 19);
+    assert_eq!(balanced_s.dist.len(), 10);
+
+    for k in keys.iter() {
+
+        assert_eq!(balanced_map.get(k), map.get(k));
+    }
+
+    let v1: Vec<usize> = map.iter().cloned().collect();
+    let v2: Vec<usize> = balanced_map.iter().cloned().collect();
+    assert_eq!(v1, v2);
+
+    let v1: Vec<usize> = map.iter_complete("c").cloned().collect();
+    let v2: Vec<usize> = balanced_map.iter_complete("c").cloned().collect();
+    assert_eq!(v1, v2);
+
+    let v1: Vec<usize> = map.iter_neighbor("cab", 1).cloned().collect();
+    let v2: Vec<usize> = balanced_map.iter_neighbor("cab", 1).cloned().collect();
+    assert_eq!(v1, v2);
+
+    let v1: Vec<usize> = map.iter_crossword("?a?", '?').rev().cloned().collect();
+    let v2: Vec<usize> = balanced_map.iter_crossword("?a?", '?').rev().cloned().collect();
+    assert_eq!(v1, v2);
+}
+
+
+#[test]
+fn tst_balanced_remove_keys() {
+
+    let keys = get_sorted_keys_abcdef();
+
+    let mut map = Tst::new_balanced();
+
+    for k in keys.iter() {
+
+        map.insert(k, k.clone());
+    }
+
+    for (i, k) in keys.iter().enumerate() {
+
+        if i % 3 == 0 {
+
+            assert_eq!(map.remove(k), Some(k.clone()));
+            assert_eq!(map.remove(k), None);
+        }
+    }
+
+    for (i, k) in keys.iter().enumerate() {
+
+        if i % 3 == 0 {
+
+            assert_eq!(map.get(k), None);
+
+        } else {
+
+            assert_eq!(map.get(k), Some(k));
+        }
+    }
+
+    let s = map.stat();
+    assert_eq!(s.count.values, map.len());
+    assert!(s.dist.len() <= 10);
+
+    let mut it = map.iter();
+
+    while let Some(value) = it.next() {
+
+        assert_eq!(it.current_key(), *value);
+    }
+
+    for k in keys.iter().rev() {
+
+        map.remove(k);
+    }
+
+    assert_eq!(map.len(), 0);
+
+    let s = map.stat();
+    assert_eq!(s.count.nodes, 0);
+    assert_eq!(s.count.values, 0);
+}
+
+
+#[test]
+fn tst_arena_behaves_like_tst() {
+
+    use ternary_tree::ArenaTst;
+
+    let mut map = Tst::new();
+    let mut arena_map = ArenaTst::new();
+
+    for k in RANDOM_VEC_123.iter() {
+
+        assert_eq!(arena_map.insert(k, *k), map.insert(k, *k));
+    }
+
+    assert_eq!(arena_map.insert("", "woups"), Some("woups"));
+    assert_eq!(arena_map.len(), map.len());
+
+    for k in SORTED_VEC_123.iter() {
+
+        assert_eq!(arena_map.get(k), map.get(k));
+    }
+
+    assert_eq!(arena_map.get("abcd"), None);
+    assert_eq!(arena_map.get(""), None);
+
+    let s1 = map.stat();
+    let s2 = arena_map.stat();
+
+    assert_eq!(s2.dist, s1.dist);
+    assert_eq!(s2.key_len, s1.key_len);
+    assert_eq!(s2.count.values, s1.count.values);
+    assert_eq!(s2.count.nodes, 20);
+
+    let mut v = Vec::new();
+    arena_map.visit_values(|s| v.push(*s));
+    assert_eq!(v, SORTED_VEC_123);
+
+    let v1: Vec<&str> = map.iter().rev().cloned().collect();
+    let v2: Vec<&str> = arena_map.iter().rev().cloned().collect();
+    assert_eq!(v1, v2);
+
+    for prefix in ["", "a", "ab", "c", "abc", "d"].iter() {
+
+        let mut v1 = Vec::new();
+        map.visit_complete_values(prefix, |s| v1.push(*s));
+
+        let mut v2 = Vec::new();
+        arena_map.visit_complete_values(prefix, |s| v2.push(*s));
+
+        assert_eq!(v1, v2);
+
+        let v1: Vec<&str> = map.iter_complete(prefix).cloned().collect();
+        let v2: Vec<&str> = arena_map.iter_complete(prefix).cloned().collect();
+        assert_eq!(v1, v2);
+    }
+
+    for range in 0..4 {
+
+        for key in ["", "a", "abc", "bbb", "cca"].iter() {
+
+            let mut v1 = Vec::new();
+            map.visit_neighbor_values(key, range, |s| v1.push(*s));
+
+            let mut v2 = Vec::new();
+            arena_map.visit_neighbor_values(key, range, |s| v2.push(*s));
+
+            assert_eq!(v1, v2);
+
+            let v1: Vec<&str> = map.iter_neighbor(key, range).rev().cloned().collect();
+            let v2: Vec<&str> = arena_map.iter_neighbor(key, range).rev().cloned().collect();
+            assert_eq!(v1, v2);
+        }
+    }
+
+    for pattern in ["?", "a?", "?a?", "??c", "???", "????"].iter() {
+
+        let mut v1 = Vec::new();
+        map.visit_crossword_values(pattern, '?', |s| v1.push(*s));
+
+        let mut v2 = Vec::new();
+        arena_map.visit_crossword_values(pattern, '?', |s| v2.push(*s));
+
+        assert_eq!(v1, v2);
+
+        let v1: Vec<&str> = map.iter_crossword(pattern, '?').cloned().collect();
+        let v2: Vec<&str> = arena_map.iter_crossword(pattern, '?').cloned().collect();
+        assert_eq!(v1, v2);
+    }
+}
+
+
+#[test]
+fn tst_arena_iterate_and_read_current_key() {
+
+    use ternary_tree::ArenaTst;
+
+    let mut map = ArenaTst::new();
+
+    for k in RANDOM_VEC_123.iter() {
+
+        map.insert(k, *k);
+    }
+
+    let mut it = map.iter();
+
+    while let Some(value) = it.next() {
+
+        assert_eq!(it.current_key(), *value);
+
+        if let Some(value) = it.next_back() {
+
+            assert_eq!(it.current_key_back(), *value);
+        }
+    }
+
+    let mut it = map.iter_complete("ab");
+
+    while let Some(value) = it.next_back() {
+
+        assert_eq!(it.current_key_back(), *value);
+    }
+
+    let mut it = map.iter_neighbor("abc", 1);
+
+    while let Some(value) = it.next() {
+
+        assert_eq!(it.current_key(), *value);
+    }
+
+    let mut it = map.iter_crossword("?b?", '?');
+
+    while let Some(value) = it.next_back() {
+
+        assert_eq!(it.current_key_back(), *value);
+    }
+}
+
+
+#[test]
+fn tst_arena_update_and_remove_values() {
+
+    use ternary_tree::ArenaTst;
+
+    let mut map = ArenaTst::new();
+
+    for (i, k) in RANDOM_VEC_123.iter().enumerate() {
+
+        map.insert(k, i);
+    }
+
+    if let Some(v) = map.get_mut("abc") {
+
+        *v = 100;
+    }
+
+    assert_eq!(map.get("abc"), Some(&100));
+
+    map.visit_values_mut(|v| *v += 1000);
+    map.visit_complete_values_mut("c", |v| *v = 0);
+    map.visit_neighbor_values_mut("aaa", 1, |v| *v = 1);
+    map.visit_crossword_values_mut("b??", '?', |v| *v = 2);
+
+    assert_eq!(map.get("abc"), Some(&1100));
+    assert_eq!(map.get("cca"), Some(&0));
+    assert_eq!(map.get("aab"), Some(&1));
+    assert_eq!(map.get("bac"), Some(&2));
+
+    let capacity = map.stat().bytes.total;
+
+    for k in RANDOM_VEC_123_BIS.iter() {
+
+        assert!(map.remove(k).is_some());
+        assert_eq!(map.remove(k), None);
+        assert_eq!(map.stat().count.values, map.len());
+    }
+
+    assert_eq!(map.len(), 0);
+    assert_eq!(map.stat().count.nodes, 0);
+
+    for k in RANDOM_VEC_123.iter() {
+
+        map.insert(k, 0);
+    }
+
+    assert_eq!(map.stat().count.nodes, 20);
+    assert_eq!(map.stat().bytes.total, capacity);
+
+    map.clear();
+
+    assert_eq!(map.len(), 0);
+    assert_eq!(map.iter().next(), None);
+}
+
+
+#[test]
+fn tst_frozen_behaves_like_tst() {
+
+    let map = get_sample_map_abc_abc();
+    let frozen = get_sample_map_abc_abc().freeze();
+
+    assert_eq!(frozen.len(), map.len());
+
+    for k in SORTED_VEC_123.iter() {
+
+        assert_eq!(frozen.get(k), map.get(k));
+    }
+
+    assert_eq!(frozen.get("abcd"), None);
+    assert_eq!(frozen.get("d"), None);
+    assert_eq!(frozen.get(""), None);
+
+    let s1 = map.stat();
+    let s2 = frozen.stat();
+
+    assert_eq!(s2.dist, s1.dist);
+    assert_eq!(s2.key_len, s1.key_len);
+    assert_eq!(s2.count.values, s1.count.values);
+    assert_eq!(s2.count.nodes, 20);
+    assert!(s2.bytes.total < s1.bytes.total);
+
+    let mut v = Vec::new();
+    frozen.visit_values(|s| v.push(*s));
+    assert_eq!(v, SORTED_VEC_123);
+
+    let v1: Vec<&str> = map.iter().rev().cloned().collect();
+    let v2: Vec<&str> = frozen.iter().rev().cloned().collect();
+    assert_eq!(v1, v2);
+
+    for prefix in ["", "a", "ab", "c", "abc", "d"].iter() {
+
+        let mut v1 = Vec::new();
+        map.visit_complete_values(prefix, |s| v1.push(*s));
+
+        let mut v2 = Vec::new();
+        frozen.visit_complete_values(prefix, |s| v2.push(*s));
+
+        assert_eq!(v1, v2);
+
+        let v1: Vec<&str> = map.iter_complete(prefix).cloned().collect();
+        let v2: Vec<&str> = frozen.iter_complete(prefix).cloned().collect();
+        assert_eq!(v1, v2);
+    }
+
+    for range in 0..4 {
+
+        for key in ["", "a", "abc", "bbb", "cca"].iter() {
+
+            let mut v1 = Vec::new();
+            map.visit_neighbor_values(key, range, |s| v1.push(*s));
+
+            let mut v2 = Vec::new();
+            frozen.visit_neighbor_values(key, range, |s| v2.push(*s));
+
+            assert_eq!(v1, v2);
+
+            let v1: Vec<&str> = map.iter_neighbor(key, range).rev().cloned().collect();
+            let v2: Vec<&str> = frozen.iter_neighbor(key, range).rev().cloned().collect();
+            assert_eq!(v1, v2);
+        }
+    }
+
+    for pattern in ["?", "a?", "?a?", "??c", "???", "????"].iter() {
+
+        let mut v1 = Vec::new();
+        map.visit_crossword_values(pattern, '?', |s| v1.push(*s));
+
+        let mut v2 = Vec::new();
+        frozen.visit_crossword_values(pattern, '?', |s| v2.push(*s));
+
+        assert_eq!(v1, v2);
+
+        let v1: Vec<&str> = map.iter_crossword(pattern, '?').cloned().collect();
+        let v2: Vec<&str> = frozen.iter_crossword(pattern, '?').cloned().collect();
+        assert_eq!(v1, v2);
+    }
+}
+
+
+#[test]
+fn tst_frozen_iterate_and_read_current_key() {
+
+    let map = get_sample_map_abc_abc_with_unicode();
+    let frozen = map.freeze();
+
+    let mut it = frozen.iter();
+
+    while let Some(value) = it.next() {
+
+        assert_eq!(it.current_key().replace("🗝", "📦"), *value);
+
+        if let Some(value) = it.next_back() {
+
+            assert_eq!(it.current_key_back().replace("🗝", "📦"), *value);
+        }
+    }
+
+    let mut it = frozen.iter_complete("🗝ab");
+    let mut count = 0;
+
+    while let Some(value) = it.next_back() {
+
+        assert_eq!(it.current_key_back().replace("🗝", "📦"), *value);
+        count += 1;
+    }
+
+    assert_eq!(count, 3);
+
+    let mut it = frozen.iter_neighbor("🗝abc", 1);
+
+    while let Some(value) = it.next() {
+
+        assert_eq!(it.current_key().replace("🗝", "📦"), *value);
+    }
+
+    let mut it = frozen.iter_crossword("??b?", '?');
+
+    while let Some(value) = it.next_back() {
+
+        assert_eq!(it.current_key_back().replace("🗝", "📦"), *value);
+    }
+
+    let empty: Tst<bool> = Tst::new();
+    let frozen = empty.freeze();
+
+    assert_eq!(frozen.len(), 0);
+    assert_eq!(frozen.get("a"), None);
+    assert_eq!(frozen.iter().next(), None);
+    assert_eq!(frozen.iter_complete("a").next_back(), None);
+    assert_eq!(frozen.stat().count.nodes, 0);
+}
+
+
+#[test]
+fn tst_compressed_insert_and_remove_long_keys() {
+
+    let mut map = Tst::new();
+
+    map.insert("https://example.com/some/long/path", 1);
+
+    assert_eq!(map.stat().count.nodes, 1);
+    assert_eq!(map.get("https://example.com/some/long/path"), Some(&1));
+    assert_eq!(map.get("https://example.com/some/long"), None);
+    assert_eq!(map.get("https://example.com/some/long/path/"), None);
+    assert_eq!(map.get("https://example.org"), None);
+
+    //branching in the middle of the run splits it
+    map.insert("https://example.org", 2);
+
+    assert_eq!(map.stat().count.nodes, 3);
+    assert_eq!(map.get("https://example.com/some/long/path"), Some(&1));
+    assert_eq!(map.get("https://example.org"), Some(&2));
+
+    //a value in the middle of the run splits it too
+    map.insert("https://example.com/some", 3);
+
+    assert_eq!(map.stat().count.nodes, 4);
+    assert_eq!(map.get("https://example.com/some"), Some(&3));
+    assert_eq!(map.stat().key_len.max, 34);
+
+    //removing keys merges chains back
+    assert_eq!(map.remove("https://example.com/some"), Some(3));
+    assert_eq!(map.stat().count.nodes, 3);
+
+    assert_eq!(map.remove("https://example.org"), Some(2));
+    assert_eq!(map.stat().count.nodes, 1);
+    assert_eq!(map.get("https://example.com/some/long/path"), Some(&1));
+
+    assert_eq!(map.remove("https://example.com/some/long/path"), Some(1));
+    assert_eq!(map.stat().count.nodes, 0);
+    assert_eq!(map.len(), 0);
+}
+
+
+#[test]
+fn tst_compressed_search_inside_runs() {
+
+    let mut map = Tst::new();
+
+    for key in &["abcdefgh", "abcdxyz", "abcdefij", "zz"] {
+
+        map.insert(key, key.to_string());
+    }
+
+    let mut v = Vec::new();
+    map.visit_complete_values("abcde", |s| v.push(s.clone()));
+    assert_eq!(v, ["abcdefgh", "abcdefij"]);
+
+    let mut it = map.iter_complete("abcdef");
+    let mut count = 0;
+
+    while let Some(value) = it.next() {
+
+        assert_eq!(it.current_key(), *value);
+        count += 1;
+    }
+
+    assert_eq!(count, 2);
+
+    let mut it = map.iter_complete("ab");
+
+    while let Some(value) = it.next_back() {
+
+        assert_eq!(it.current_key_back(), *value);
+    }
+
+    assert_eq!(map.iter_complete("abcdeX").next(), None);
+
+    let v: Vec<&String> = map.iter_neighbor("abcdefXh", 1).collect();
+    assert_eq!(v, ["abcdefgh"]);
+
+    let mut it = map.iter_neighbor("abcdyz", 1);
+
+    while let Some(value) = it.next() {
+
+        assert_eq!(it.current_key(), *value);
+    }
+
+    let v: Vec<&String> = map.iter_crossword("abcd??z", '?').collect();
+    assert_eq!(v, ["abcdxyz"]);
+
+    let mut it = map.iter_crossword("a?c?ef??", '?');
+    let mut count = 0;
+
+    while let Some(value) = it.next_back() {
+
+        assert_eq!(it.current_key_back(), *value);
+        count += 1;
+    }
+
+    assert_eq!(count, 2);
+
+    map.visit_complete_values_mut("abcdefg", |s| s.push('!'));
+    assert_eq!(map.get("abcdefgh"), Some(&"abcdefgh!".to_string()));
+
+    //the frozen tree expands runs back to one char per node
+    let frozen = map.freeze();
+    let v: Vec<&String> = frozen.iter_complete("abcdef").collect();
+    assert_eq!(v, ["abcdefgh!", "abcdefij"]);
+}
+
+
+#[cfg(feature = "serde")]
+#[test]
+fn tst_serde_round_trip() {
+
+    let map = get_sample_map_abc_abc();
+
+    let json = serde_json::to_string(&map).unwrap();
+    let loaded: Tst<String> = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(loaded.len(), map.len());
+
+    let mut it = map.iter();
+
+    while let Some(value) = it.next() {
+
+        assert_eq!(loaded.get(&it.current_key()).map(|s| s.as_str()), Some(*value));
+    }
+
+    let map = ternary_tree::tst!["foo" => 1, "bar" => 2, "baz" => 3];
+    assert_eq!(serde_json::to_string(&map).unwrap(), r#"{"bar":2,"baz":3,"foo":1}"#);
+
+    let empty: Tst<u8> = Tst::new();
+    assert_eq!(serde_json::to_string(&empty).unwrap(), "{}");
+
+    let loaded: Tst<u8> = serde_json::from_str("{}").unwrap();
+    assert_eq!(loaded.len(), 0);
+
+    //last value wins for duplicated keys, as with successive inserts
+    let loaded: Tst<u8> = serde_json::from_str(r#"{"b":1,"a":2,"b":3}"#).unwrap();
+    assert_eq!(loaded.len(), 2);
+    assert_eq!(loaded.get("a"), Some(&2));
+    assert_eq!(loaded.get("b"), Some(&3));
+
+    assert!(serde_json::from_str::<Tst<u8>>("[1, 2]").is_err());
+    assert!(serde_json::from_str::<Tst<u8>>(r#"{"a":"b"}"#).is_err());
+}
+
+
+#[cfg(feature = "serde")]
+#[test]
+fn tst_serde_deserialize_is_balanced() {
+
+    let keys = get_sorted_keys_abcdef();
+
+    let mut map = Tst::new();
+
+    for (i, k) in keys.iter().enumerate() {
+
+        map.insert(k, i);
+    }
+
+    assert_eq!(map.stat().dist.len(), 19);
+
+    let json = serde_json::to_string(&map).unwrap();
+    let loaded: Tst<usize> = serde_json::from_str(&json).unwrap();
+
+    let mut balanced_map = Tst::new_balanced();
+
+    for (i, k) in keys.iter().enumerate() {
+
+        balanced_map.insert(k, i);
+    }
+
+    assert_eq!(loaded.len(), keys.len());
+    assert!(!loaded.is_balanced());
+    assert_eq!(loaded.stat().count.nodes, map.stat().count.nodes);
+    assert_eq!(loaded.stat().dist.len(), 10);
+    assert!(loaded.stat().dist.len() <= balanced_map.stat().dist.len());
+
+    let v1: Vec<usize> = map.iter().cloned().collect();
+    let v2: Vec<usize> = loaded.iter().cloned().collect();
+    assert_eq!(v1, v2);
+
+    //a balanced tree is loaded back as a plain one, with the same shape as any other tree with these keys
+    let json = serde_json::to_string(&balanced_map).unwrap();
+    let loaded_balanced: Tst<usize> = serde_json::from_str(&json).unwrap();
+
+    assert!(!loaded_balanced.is_balanced());
+    assert_eq!(loaded_balanced.stat().dist, loaded.stat().dist);
+}
+
+
+#[test]
+fn tst_binary_round_trip() {
+
+    let mut map = Tst::new();
+
+    for (i, k) in get_sorted_keys_abcdef().iter().enumerate() {
+
+        map.insert(k, format!("{}-{}", k, i));
+    }
+
+    map.insert("https://example.com/some/long/path", "🗝".to_string());
+
+    let mut bytes = Vec::new();
+    map.write_to(&mut bytes).unwrap();
+
+    let loaded: Tst<String> = Tst::read_from(&mut bytes.as_slice()).unwrap();
+
+    assert_eq!(loaded.len(), map.len());
+    assert!(!loaded.is_balanced());
+    assert_eq!(loaded.stat().count, map.stat().count);
+    assert_eq!(loaded.stat().dist, map.stat().dist);
+    assert_eq!(loaded.get("https://example.com/some/long/path"), Some(&"🗝".to_string()));
+
+    let mut it = map.iter();
+    let mut loaded_it = loaded.iter();
+
+    while let Some(value) = it.next() {
+
+        assert_eq!(loaded_it.next(), Some(value));
+        assert_eq!(loaded_it.current_key(), it.current_key());
+    }
+
+    assert_eq!(loaded_it.next(), None);
+
+    let mut balanced_map = Tst::new_balanced();
+
+    for (i, k) in get_sorted_keys_abcdef().iter().enumerate() {
+
+        balanced_map.insert(k, i);
+    }
+
+    let mut bytes = Vec::new();
+    balanced_map.write_to(&mut bytes).unwrap();
+
+    let mut loaded: Tst<usize> = Tst::read_from(&mut bytes.as_slice()).unwrap();
+
+    assert!(loaded.is_balanced());
+    assert_eq!(loaded.stat().dist, balanced_map.stat().dist);
+
+    //heights are rebuilt, so that the loaded tree keeps on balancing itself
+    for k in get_sorted_keys_abcdef().iter() {
+
+        loaded.insert(&format!("{}{}", k, k), 0);
+        balanced_map.insert(&format!("{}{}", k, k), 0);
+    }
+
+    assert_eq!(loaded.stat().dist, balanced_map.stat().dist);
+
+    let empty: Tst<()> = Tst::new();
+    let mut bytes = Vec::new();
+    empty.write_to(&mut bytes).unwrap();
+
+    let loaded: Tst<()> = Tst::read_from(&mut bytes.as_slice()).unwrap();
+    assert_eq!(loaded.len(), 0);
+    assert_eq!(loaded.iter().next(), None);
+}
+
+
+#[test]
+fn tst_binary_reject_bad_input() {
+
+    use std::io::ErrorKind;
+
+    let map = get_sample_map_abc_abc();
+
+    let mut values: Tst<String> = Tst::new();
+    map.visit_values(|v| { values.insert(v, v.to_string()); });
+
+    let mut bytes = Vec::new();
+    values.write_to(&mut bytes).unwrap();
+
+    assert!(Tst::<String>::read_from(&mut bytes.as_slice()).is_ok());
+
+    //truncated file
+    let err = Tst::<String>::read_from(&mut &bytes[..bytes.len() - 1]).err().unwrap();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    let err = Tst::<String>::read_from(&mut &bytes[..10]).err().unwrap();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+    //wrong magic, version and checksum
+    for i in &[0, 4, 40] {
+
+        let mut bad = bytes.clone();
+        bad[*i] ^= 0xff;
+
+        let err = Tst::<String>::read_from(&mut bad.as_slice()).err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    //values which do not match the codec
+    let err = Tst::<u32>::read_from(&mut bytes.as_slice()).err().unwrap();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+
+#[test]
+fn tst_view_behaves_like_tst() {
+
+    let mut map = get_sample_map_abc_abc_with_unicode();
+    map.insert("🗝https://example.com/some/long/path", "📦https://example.com/some/long/path".to_string());
+
+    let mut bytes = Vec::new();
+    map.write_to(&mut bytes).unwrap();
+
+    let view = TstView::open(&bytes).unwrap();
+
+    assert_eq!(view.len(), map.len());
+    assert!(!view.is_empty());
+
+    let decode = |v: &[u8]| String::decode(v).unwrap();
+
+    let mut it = map.iter();
+
+    while let Some(value) = it.next() {
+
+        assert_eq!(view.get(&it.current_key()).map(decode).as_ref(), Some(value));
+    }
+
+    assert_eq!(view.get(""), None);
+    assert_eq!(view.get("🗝"), None);
+    assert_eq!(view.get("🗝https://example.com"), None);
+
+    let v1: Vec<&String> = map.iter().collect();
+    let v2: Vec<String> = view.iter().map(decode).collect();
+    assert_eq!(v1, v2.iter().collect::<Vec<_>>());
+
+    for prefix in &["", "🗝", "🗝a", "🗝ab", "🗝abc", "🗝ht", "🗝https://example.com/", "🗝d", "x"] {
+
+        let v1: Vec<&String> = map.iter_complete(prefix).collect();
+        let v2: Vec<String> = view.iter_complete(prefix).map(decode).collect();
+        assert_eq!(v1, v2.iter().collect::<Vec<_>>());
+
+        let mut it = view.iter_complete(prefix);
+
+        while let Some(value) = it.next() {
+
+            assert_eq!(it.current_key().replace("🗝", "📦"), decode(value));
+
+            if let Some(value) = it.next_back() {
+
+                assert_eq!(it.current_key_back().replace("🗝", "📦"), decode(value));
+            }
+        }
+    }
+
+    for (key, range) in &[("🗝abc", 1), ("🗝ab", 2), ("🗝https://example.com/some/lung/path", 1), ("", 2)] {
+
+        let v1: Vec<&String> = map.iter_neighbor(key, *range).rev().collect();
+        let v2: Vec<String> = view.iter_neighbor(key, *range).rev().map(decode).collect();
+        assert_eq!(v1, v2.iter().collect::<Vec<_>>());
+
+        let mut it = view.iter_neighbor(key, *range);
+
+        while let Some(value) = it.next() {
+
+            assert_eq!(it.current_key().replace("🗝", "📦"), decode(value));
+        }
+    }
+
+    for pattern in &["??b?", "🗝a?", "????", "🗝https://??????????????????????????"] {
+
+        let v1: Vec<&String> = map.iter_crossword(pattern, '?').collect();
+        let v2: Vec<String> = view.iter_crossword(pattern, '?').map(decode).collect();
+        assert_eq!(v1, v2.iter().collect::<Vec<_>>());
+
+        let mut it = view.iter_crossword(pattern, '?');
+
+        while let Some(value) = it.next_back() {
+
+            assert_eq!(it.current_key_back().replace("🗝", "📦"), decode(value));
+        }
+    }
+}
+
+
+#[test]
+fn tst_view_reject_bad_input() {
+
+    let map = ternary_tree::tst!["foo" => 1u16, "bar" => 2, "baz" => 3];
+
+    let mut bytes = Vec::new();
+    map.write_to(&mut bytes).unwrap();
+
+    assert!(TstView::open(&bytes).is_ok());
+
+    //any truncation or damaged byte is caught when the view is opened
+    for len in 0..bytes.len() {
+
+        assert!(TstView::open(&bytes[..len]).is_err());
+    }
+
+    for i in 0..bytes.len() {
+
+        let mut bad = bytes.clone();
+        bad[i] ^= 0x5a;
+
+        assert!(TstView::open(&bad).is_err());
+    }
+
+    let empty: Tst<u16> = Tst::new();
+    let mut bytes = Vec::new();
+    empty.write_to(&mut bytes).unwrap();
+
+    let view = TstView::open(&bytes).unwrap();
+
+    assert_eq!(view.len(), 0);
+    assert!(view.is_empty());
+    assert_eq!(view.get("foo"), None);
+    assert_eq!(view.iter().next(), None);
+    assert_eq!(view.iter_complete("").next_back(), None);
+    assert_eq!(view.iter_neighbor("foo", 3).next(), None);
+    assert_eq!(view.iter_crossword("f??", '?').next(), None);
+}
+
+
+fn get_text_error_position(err: TextError) -> (usize, usize) {
+
+    match err {
+
+        TextError::Parse { line, column, .. } => (line, column),
+
+        TextError::Io(err) => panic!("unexpected io error {}", err)
+    }
+}
+
+
+#[test]
+fn tst_text_round_trip() {
+
+    let map = get_sample_map_abc_abc_with_unicode();
+
+    let mut words = Vec::new();
+    map.write_words(&mut words).unwrap();
+
+    let mut loaded = Tst::new();
+    assert_eq!(loaded.read_words(&mut words.as_slice(), |line| line).unwrap(), map.len());
+
+    let v: Vec<usize> = loaded.iter().cloned().collect();
+    assert_eq!(v, (1..map.len() + 1).collect::<Vec<usize>>());
+
+    let mut tsv = Vec::new();
+    map.write_tsv(&mut tsv, |v| v.clone()).unwrap();
+
+    let mut loaded: Tst<String> = Tst::new();
+    loaded.read_tsv(&mut tsv.as_slice(), |s| Ok::<_, String>(s.to_string())).unwrap();
+
+    let v1: Vec<&String> = map.iter().collect();
+    let v2: Vec<&String> = loaded.iter().collect();
+    assert_eq!(v1, v2);
+
+    let mut map = Tst::new();
+    map.insert("tab\tin \"key\" \\ 🗝", 1);
+    map.insert("plain", 2);
+
+    let mut json = Vec::new();
+    map.write_json_lines(&mut json, |v| format!("{{\"n\": [{}, true]}}", v)).unwrap();
+
+    let mut loaded: Tst<String> = Tst::new();
+    loaded.read_json_lines(&mut json.as_slice(), |s| Ok::<_, String>(s.to_string())).unwrap();
+
+    assert_eq!(loaded.len(), 2);
+    assert_eq!(loaded.get("tab\tin \"key\" \\ 🗝"), Some(&"{\"n\": [1, true]}".to_string()));
+    assert_eq!(loaded.get("plain"), Some(&"{\"n\": [2, true]}".to_string()));
+
+    //keys which would break lines are rejected
+    let mut out = Vec::new();
+    assert!(map.write_tsv(&mut out, |v| v.to_string()).is_err());
+
+    let map = ternary_tree::tst!["line\nbreak" => 0];
+    assert!(map.write_words(&mut Vec::new()).is_err());
+    assert!(map.write_json_lines(&mut Vec::new(), |v| v.to_string()).is_ok());
+}
+
+
+#[test]
+fn tst_text_report_errors() {
+
+    let mut map: Tst<u32> = Tst::new();
+
+    let parse = |s: &str| s.parse::<u32>();
+
+    let err = map.read_tsv(&mut "a\t1\n\nb 2\n".as_bytes(), parse).unwrap_err();
+    assert_eq!(get_text_error_position(err), (3, 4));
+
+    let err = map.read_tsv(&mut "\t1\n".as_bytes(), parse).unwrap_err();
+    assert_eq!(get_text_error_position(err), (1, 1));
+
+    let err = map.read_tsv(&mut "🗝🗝\tx\n".as_bytes(), parse).unwrap_err();
+    assert_eq!(get_text_error_position(err), (1, 4));
+
+    let err = map.read_words(&mut &b"ok\nab\xffc\n"[..], |_| 0).unwrap_err();
+    assert_eq!(get_text_error_position(err), (2, 3));
+
+    let json = |s: &str| s.parse::<u32>();
+
+    let lines = [
+        (r#"{"key": "a", "value": 1"#, (1, 24)),
+        (r#"{"key": "a"}"#, (1, 13)),
+        (r#"{"key": 1, "value": 1}"#, (1, 9)),
+        (r#"{"key": "a", "value": tru}"#, (1, 23)),
+        (r#"{"key": "a\x", "value": 1}"#, (1, 11)),
+        (r#"{"key": "", "value": 1}"#, (1, 9)),
+        (r#"{"key": "a", "value": "1"}"#, (1, 23)),
+        (r#"{"key": "a", "value": 1} x"#, (1, 26)),
+        (r#"{"key": "a", "value": [1, {"b": 2]}"#, (1, 34)),
+    ];
+
+    for (text, position) in lines.iter() {
+
+        let input = format!("{{\"key\": \"ok\", \"value\": 0}}\n\n{}\n", text);
+        let err = map.read_json_lines(&mut input.as_bytes(), json).unwrap_err();
+        assert_eq!(get_text_error_position(err), (3, position.1), "{}", text);
+    }
+
+    //lines before a bad one are kept
+    assert_eq!(map.get("ok"), Some(&0));
+    assert_eq!(map.get("a"), Some(&1));
+}
+
+
+#[test]
+fn tst_read_json_lines_deeply_nested_value() {
+
+    let mut map = Tst::new();
+
+    let input = format!("{{\"key\": \"a\", \"value\": {}", "[".repeat(1_000_000));
+    let err = map.read_json_lines(&mut input.as_bytes(), |s| Ok::<_, String>(s.to_string())).unwrap_err();
+
+    //the 129th bracket is one too many
+    assert_eq!(get_text_error_position(err), (1, 23+128));
+    assert_eq!(map.len(), 0);
+
+    let nested = format!("{}0{}", "[{\"a\": ".repeat(64), "}]".repeat(64));
+    let input = format!("{{\"key\": \"a\", \"value\": {}}}\n", nested);
+
+    assert_eq!(map.read_json_lines(&mut input.as_bytes(), |s| Ok::<_, String>(s.to_string())).unwrap(), 1);
+    assert_eq!(map.get("a"), Some(&nested));
+}
+
+
+#[test]
+fn tst_persistent_behaves_like_tst() {
+
+    let mut map = Tst::new();
+    let mut persistent_map = PersistentTst::new();
+
+    for k in RANDOM_VEC_123.iter() {
+
+        assert_eq!(persistent_map.insert(k, *k).map(|v| *v), map.insert(k, *k));
+    }
+
+    assert_eq!(persistent_map.insert("", "woups").map(|v| *v), Some("woups"));
+    assert_eq!(persistent_map.len(), map.len());
+
+    for k in SORTED_VEC_123.iter() {
+
+        assert_eq!(persistent_map.get(k), map.get(k));
+    }
+
+    assert_eq!(persistent_map.get("abcd"), None);
+
+    let s1 = map.stat();
+    let s2 = persistent_map.stat();
+
+    assert_eq!(s2.dist, s1.dist);
+    assert_eq!(s2.key_len, s1.key_len);
+    assert_eq!(s2.count.values, s1.count.values);
+    assert_eq!(s2.count.nodes, 20);
+
+    let mut v = Vec::new();
+    persistent_map.visit_values(|s| v.push(*s));
+    assert_eq!(v, SORTED_VEC_123);
+
+    let v1: Vec<&str> = map.iter().rev().cloned().collect();
+    let v2: Vec<&str> = persistent_map.iter().rev().cloned().collect();
+    assert_eq!(v1, v2);
+
+    for prefix in ["", "a", "ab", "c", "abc", "d"].iter() {
+
+        let mut v1 = Vec::new();
+        map.visit_complete_values(prefix, |s| v1.push(*s));
+
+        let mut v2 = Vec::new();
+        persistent_map.visit_complete_values(prefix, |s| v2.push(*s));
+
+        assert_eq!(v1, v2);
+
+        let mut it = persistent_map.iter_complete(prefix);
+        let mut v2 = Vec::new();
+
+        while let Some(value) = it.next_back() {
+
+            assert_eq!(it.current_key_back(), *value);
+            v2.push(*value);
+        }
+
+        let v1: Vec<&str> = map.iter_complete(prefix).rev().cloned().collect();
+        assert_eq!(v1, v2);
+    }
+
+    for range in 0..4 {
+
+        for key in ["", "a", "abc", "bbb", "cca"].iter() {
+
+            let mut v1 = Vec::new();
+            map.visit_neighbor_values(key, range, |s| v1.push(*s));
+
+            let mut v2 = Vec::new();
+            persistent_map.visit_neighbor_values(key, range, |s| v2.push(*s));
+
+            assert_eq!(v1, v2);
+
+            let v1: Vec<&str> = map.iter_neighbor(key, range).rev().cloned().collect();
+            let v2: Vec<&str> = persistent_map.iter_neighbor(key, range).rev().cloned().collect();
+            assert_eq!(v1, v2);
+        }
+    }
+
+    for pattern in ["?", "a?", "?a?", "??c", "???", "????"].iter() {
+
+        let mut v1 = Vec::new();
+        map.visit_crossword_values(pattern, '?', |s| v1.push(*s));
+
+        let mut v2 = Vec::new();
+        persistent_map.visit_crossword_values(pattern, '?', |s| v2.push(*s));
+
+        assert_eq!(v1, v2);
+
+        let mut it = persistent_map.iter_crossword(pattern, '?');
+
+        while let Some(value) = it.next() {
+
+            assert_eq!(it.current_key(), *value);
+        }
+    }
+}
+
+
+#[test]
+fn tst_persistent_counts_like_tst() {
+
+    let mut map = Tst::new();
+    let mut persistent_map = PersistentTst::new();
+
+    for k in RANDOM_VEC_123.iter() {
+
+        map.insert(k, *k);
+        persistent_map.insert(k, *k);
+    }
+
+    let snapshot = persistent_map.clone();
+
+    for k in SORTED_VEC_123.iter().step_by(3) {
+
+        map.remove(k);
+        persistent_map.remove(k);
+    }
+
+    persistent_map.insert("abcd", "abcd");
+    map.insert("abcd", "abcd");
+
+    for prefix in ["", "a", "ab", "abc", "abcd", "b", "cc", "d"].iter() {
+
+        assert_eq!(persistent_map.count_prefix(prefix), map.count_prefix(prefix));
+        assert_eq!(snapshot.count_prefix(prefix), snapshot.iter_complete(prefix).count());
+    }
+
+    for key in ["", "a", "aab", "abb", "abcd", "b", "bz", "cc", "zzz"].iter() {
+
+        assert_eq!(persistent_map.rank(key), map.rank(key));
+        assert_eq!(snapshot.rank(key), SORTED_VEC_123.iter().filter(|k| *k < key).count());
+    }
+
+    for n in 0..map.len() + 1 {
+
+        assert_eq!(persistent_map.select(n), map.select(n));
+    }
+
+    for (n, k) in SORTED_VEC_123.iter().enumerate() {
+
+        assert_eq!(snapshot.select(n), Some((k.to_string(), k)));
+    }
+
+    assert_eq!(snapshot.select(SORTED_VEC_123.len()), None);
+
+    let stat = persistent_map.stat();
+    assert_eq!(stat.bytes.counts, stat.count.nodes*std::mem::size_of::<usize>());
+}
+
+
+#[test]
+fn tst_persistent_snapshots_stay_unchanged() {
+
+    let mut map = PersistentTst::new();
+
+    for k in RANDOM_VEC_123.iter() {
+
+        map.insert(k, k.to_string());
+    }
+
+    let snapshot = map.clone();
+
+    assert!(snapshot.ptr_eq(&map));
+    assert_eq!(snapshot.len(), map.len());
+
+    //removing a missing key copies nothing
+    assert_eq!(map.remove("xyz"), None);
+    assert!(snapshot.ptr_eq(&map));
+
+    for k in SORTED_VEC_123.iter() {
+
+        map.insert(k, k.to_uppercase());
+    }
+
+    map.insert("abcd", "ABCD".to_string());
+
+    let second_snapshot = map.clone();
+
+    for k in SORTED_VEC_123.iter().step_by(2) {
+
+        assert_eq!(map.remove(k).map(|v| v.to_string()), Some(k.to_uppercase()));
+    }
+
+    map.clear();
+    assert_eq!(map.len(), 0);
+    assert_eq!(map.iter().next(), None);
+
+    let v: Vec<&String> = snapshot.iter().collect();
+    assert_eq!(v, SORTED_VEC_123.iter().map(|k| k.to_string()).collect::<Vec<_>>().iter().collect::<Vec<_>>());
+    assert_eq!(snapshot.get("abcd"), None);
+
+    assert_eq!(second_snapshot.len(), SORTED_VEC_123.len() + 1);
+    assert_eq!(second_snapshot.get("abcd"), Some(&"ABCD".to_string()));
+
+    for k in SORTED_VEC_123.iter() {
+
+        assert_eq!(second_snapshot.get(k), Some(&k.to_uppercase()));
+    }
+
+    //snapshots can be read from other threads
+    let handle = std::thread::spawn(move || second_snapshot.iter_complete("ab").count());
+    assert_eq!(handle.join().unwrap(), 4);
+}
+
+
+#[test]
+fn tst_concurrent_behaves_like_tst() {
+
+    let map = get_sample_map_abc_abc();
+
+    for shards in 0..4 {
+
+        let concurrent_map = ConcurrentTst::with_shards(shards);
+
+        for k in RANDOM_VEC_123.iter() {
+
+            assert_eq!(concurrent_map.insert(k, *k), None);
+        }
+
+        assert_eq!(concurrent_map.insert("", "woups"), Some("woups"));
+        assert_eq!(concurrent_map.len(), map.len());
+
+        for k in SORTED_VEC_123.iter() {
+
+            assert_eq!(concurrent_map.get(k).as_ref(), map.get(k));
+        }
+
+        assert_eq!(concurrent_map.get(""), None);
+        assert_eq!(concurrent_map.get_with("abc", |v| v.len()), Some(3));
+
+        let sorted = |mut v: Vec<&'static str>| { v.sort(); v };
+
+        let mut v = Vec::new();
+        concurrent_map.visit_values(|s| v.push(*s));
+        assert_eq!(sorted(v), SORTED_VEC_123);
+
+        for prefix in ["", "a", "ab", "c", "abc", "d"].iter() {
+
+            let mut v1 = Vec::new();
+            map.visit_complete_values(prefix, |s| v1.push(*s));
+
+            let mut v2 = Vec::new();
+            concurrent_map.visit_complete_values(prefix, |s| v2.push(*s));
+
+            assert_eq!(v1, sorted(v2));
+        }
+
+        for range in 0..4 {
+
+            for key in ["", "a", "abc", "bbb", "cca"].iter() {
+
+                let mut v1 = Vec::new();
+                map.visit_neighbor_values(key, range, |s| v1.push(*s));
+
+                let mut v2 = Vec::new();
+                concurrent_map.visit_neighbor_values(key, range, |s| v2.push(*s));
+
+                assert_eq!(v1, sorted(v2));
+            }
+        }
+
+        for pattern in ["", "?", "a?", "?a?", "??c", "???", "????"].iter() {
+
+            let mut v1 = Vec::new();
+            map.visit_crossword_values(pattern, '?', |s| v1.push(*s));
+
+            let mut v2 = Vec::new();
+            concurrent_map.visit_crossword_values(pattern, '?', |s| v2.push(*s));
+
+            assert_eq!(v1, sorted(v2));
+        }
+
+        assert_eq!(concurrent_map.remove("abc"), Some("abc"));
+        assert_eq!(concurrent_map.remove("abc"), None);
+        assert_eq!(concurrent_map.len(), map.len() - 1);
+
+        concurrent_map.clear();
+        assert_eq!(concurrent_map.len(), 0);
+    }
+}
+
+
+#[test]
+fn tst_concurrent_insert_and_read_from_threads() {
+
+    use std::sync::Arc;
+    use std::thread;
+
+    let map = Arc::new(ConcurrentTst::new());
+
+    let keys = Arc::new(get_sorted_keys_abcdef());
+
+    let mut handles = Vec::new();
+
+    for t in 0..4 {
+
+        let writer_map = map.clone();
+        let keys = keys.clone();
+
+        handles.push(thread::spawn(move || {
+
+            for k in keys.iter().skip(t).step_by(4) {
+
+                writer_map.insert(k, k.len());
+            }
+        }));
+
+        let reader_map = map.clone();
+
+        handles.push(thread::spawn(move || {
+
+            for _ in 0..10 {
+
+                reader_map.visit_complete_values("ab", |len| assert_eq!(*len, 3));
+                reader_map.visit_crossword_values("?b", '?', |len| assert_eq!(*len, 2));
+            }
+        }));
+    }
+
+    for handle in handles {
+
+        handle.join().unwrap();
+    }
+
+    assert_eq!(map.len(), keys.len());
+
+    for k in keys.iter() {
+
+        assert_eq!(map.get(k), Some(k.len()));
+    }
+}
+
+
+#[cfg(feature = "rayon")]
+fn get_many_keys() -> Vec<String> {
+
+    let suffixes = ["", "a", "é", "ab", "🐟", "zz"];
+
+    (0..6000).map(|i| format!("{:x}{}", (i * 7919) % 10007, suffixes[i % suffixes.len()])).collect()
+}
+
+
+#[cfg(feature = "rayon")]
+#[test]
+fn tst_parallel_visits_and_stats() {
+
+    let mut map = Tst::new();
+
+    for (i, k) in get_many_keys().iter().enumerate() {
+
+        map.insert(k, i);
+    }
+
+    assert_eq!(map.par_stat(), map.stat());
+
+    let mut v1: Vec<usize> = map.iter().cloned().collect();
+    let mut v2: Vec<usize> = map.par_iter().cloned().collect();
+
+    v1.sort();
+    v2.sort();
+    assert_eq!(v1, v2);
+
+    map.par_visit_values_mut(|v| *v *= 2);
+
+    let mut v3: Vec<usize> = map.par_iter().map(|v| v / 2).collect();
+    v3.sort();
+    assert_eq!(v1, v3);
+
+    let empty: Tst<usize> = Tst::new();
+    assert_eq!(empty.par_iter().count(), 0);
+    assert_eq!(empty.par_stat(), empty.stat());
+}
+
+
+#[cfg(feature = "rayon")]
+#[test]
+fn tst_parallel_build_from_sorted() {
+
+    let keys = get_many_keys();
+
+    let mut map = Tst::new();
+    let mut entries = Vec::new();
+
+    for (i, k) in keys.iter().enumerate() {
+
+        map.insert(k, i);
+        entries.push((k.clone(), i));
+    }
+
+    entries.push((String::new(), 0));
+    entries.push((keys[42].clone(), 1234));
+    map.insert(&keys[42], 1234);
+
+    let unsorted = Tst::par_from_sorted(entries.clone());
+
+    let mut entries = Vec::new();
+    let mut it = map.iter();
+
+    while let Some(value) = it.next() {
+
+        entries.push((it.current_key(), *value));
+    }
+
+    let sorted = Tst::par_from_sorted(entries);
+
+    for built in [unsorted, sorted].iter() {
+
+        assert_eq!(built.len(), map.len());
+        assert_eq!(built.get(&keys[42]), Some(&1234));
+        assert_eq!(built.get(""), None);
+        assert_eq!(built.count_prefix("1"), map.count_prefix("1"));
+
+        let mut it1 = map.iter();
+        let mut it2 = built.iter();
+
+        while let Some(value) = it1.next() {
+
+            assert_eq!(it2.next(), Some(value));
+            assert_eq!(it2.current_key(), it1.current_key());
+        }
+
+        assert_eq!(it2.next(), None);
+
+        let stats1 = map.stat();
+        let stats2 = built.stat();
+
+        assert_eq!(stats2.count, stats1.count);
+        assert_eq!(stats2.key_len, stats1.key_len);
+        assert!(stats2.dist.len() <= stats1.dist.len());
+    }
+}
+
+
+fn get_top_k_by_brute_force(map: &Tst<u64>, prefix: &str, k: usize) -> Vec<(String, u64)> {
+
+    let mut best = Vec::new();
+    let mut it = map.iter_complete(prefix);
+
+    while let Some(score) = it.next() {
+
+        best.push((it.current_key(), *score));
+    }
+
+    best.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    best.truncate(k);
+
+    best
+}
+
+
+#[test]
+fn tst_scored_top_k_complete() {
+
+    let mut map = Tst::new();
+    let mut scored_map = ScoredTst::new();
+
+    for (i, k) in SORTED_VEC_123.iter().enumerate() {
+
+        let score = ((i * 37) % 11) as u64;
+
+        map.insert(k, score);
+        assert_eq!(scored_map.insert(k, i, score), None);
+    }
+
+    assert_eq!(scored_map.len(), map.len());
+    assert_eq!(scored_map.insert("", 0, 0), Some((0, 0)));
+    assert_eq!(scored_map.get("abc"), Some(&SORTED_VEC_123.iter().position(|k| *k == "abc").unwrap()));
+    assert_eq!(scored_map.get_score("abc"), map.get("abc").cloned());
+    assert_eq!(scored_map.get_score("abcd"), None);
+
+    let check = |map: &Tst<u64>, scored_map: &ScoredTst<usize>| {
+
+        for prefix in ["", "a", "ab", "abc", "b", "c", "cc", "d"].iter() {
+
+            for k in 0..6 {
+
+                let best: Vec<(String, u64)> = scored_map.top_k_complete(prefix, k).into_iter()
+                    .map(|(key, _, score)| (key, score)).collect();
+
+                assert_eq!(best, get_top_k_by_brute_force(map, prefix, k));
+            }
+        }
+    };
+
+    check(&map, &scored_map);
+
+    for k in ["abc", "c", "aaa", "ccc"].iter() {
+
+        assert_eq!(scored_map.remove(k).map(|(_, score)| score), map.remove(k));
+    }
+
+    assert_eq!(scored_map.remove("abc"), None);
+    assert_eq!(scored_map.len(), map.len());
+
+    check(&map, &scored_map);
+}
+
+
+#[test]
+fn tst_scored_update_scores() {
+
+    let mut map = ScoredTst::new();
+
+    map.insert("python", "🐍", 50);
+    map.insert("python3", "🐍3", 80);
+    map.insert("pythonic", "✨", 20);
+
+    assert_eq!(map.top_k_complete("pyt", 1), vec![("python3".to_string(), &"🐍3", 80)]);
+
+    assert_eq!(map.insert("pythonic", "✨✨", 90), Some(("✨", 20)));
+    assert_eq!(map.top_k_complete("pyt", 1), vec![("pythonic".to_string(), &"✨✨", 90)]);
+
+    assert_eq!(map.remove("pythonic"), Some(("✨✨", 90)));
+    assert_eq!(map.top_k_complete("pyt", 1), vec![("python3".to_string(), &"🐍3", 80)]);
+
+    assert_eq!(map.insert("python3", "🐍3", 10), Some(("🐍3", 80)));
+    assert_eq!(map.top_k_complete("pyt", 5), vec![("python".to_string(), &"🐍", 50),
+                                                  ("python3".to_string(), &"🐍3", 10)]);
+    assert_eq!(map.top_k_complete("python", 5), vec![("python3".to_string(), &"🐍3", 10)]);
+    assert_eq!(map.top_k_complete("java", 5), vec![]);
+
+    map.clear();
+    assert_eq!(map.len(), 0);
+    assert_eq!(map.top_k_complete("", 5), vec![]);
+}
+
+
+fn get_edit_distance(a: &str, b: &str) -> usize {
+
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..b.len()+1).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+
+        let mut next = vec![i+1];
+
+        for (j, cb) in b.iter().enumerate() {
+
+            let cost = if ca == *cb { 0 } else { 1 };
+            next.push((row[j] + cost).min(row[j+1] + 1).min(next[j] + 1));
+        }
+
+        row = next;
+    }
+
+    row[b.len()]
+}
+
+
+fn get_fuzzy_complete_by_brute_force(keys: &[String], prefix: &str, max_edits: usize) -> Vec<(String, usize)> {
+
+    let mut hits = Vec::new();
+
+    for key in keys.iter() {
+
+        let distance = key.char_indices().map(|(i, _)| i).skip(1).chain(Some(key.len()))
+            .map(|end| get_edit_distance(prefix, &key[..end]))
+            .chain(Some(prefix.chars().count()))
+            .min().unwrap();
+
+        if distance <= max_edits {
+
+            hits.push((key.clone(), distance));
+        }
+    }
+
+    hits.sort();
+    hits
+}
+
+
+#[test]
+fn tst_fuzzy_complete_like_brute_force() {
+
+    let mut keys: Vec<String> = SORTED_VEC_123.iter().map(|k| k.to_string()).collect();
+
+    keys.extend(RANDOM_VEC_123.iter().map(|k| "🗝".to_owned()+k));
+    keys.extend(["python", "python3", "pythonic", "pyramid", "http://www.example.org/some/long/path"]
+        .iter().map(|k| k.to_string()));
+
+    let mut map = Tst::new();
+
+    for k in keys.iter() {
+
+        map.insert(k, k.clone());
+    }
+
+    for prefix in ["", "a", "ab", "abc", "🗝b", "b🗝", "pyhton", "pytohn3", "htp://www.exmaple.org/some"].iter() {
+
+        for max_edits in 0..4 {
+
+            let mut hits = Vec::new();
+            let mut it = map.iter_fuzzy_complete(prefix, max_edits);
+
+            while let Some(value) = it.next() {
+
+                assert_eq!(*value, it.current_key());
+                hits.push((it.current_key(), it.current_distance()));
+            }
+
+            assert_eq!(hits, get_fuzzy_complete_by_brute_force(&keys, prefix, max_edits));
+        }
+    }
+}
+
+
+#[test]
+fn tst_fuzzy_complete_each_key_once() {
+
+    let mut map = Tst::new();
+
+    for (i, k) in ["abc", "abcd", "abd", "xbc", "b"].iter().enumerate() {
+
+        map.insert(k, i+1);
+    }
+
+    let mut it = map.iter_fuzzy_complete("abc", 1);
+    let mut hits = Vec::new();
+
+    while let Some(value) = it.next() {
+
+        hits.push((*value, it.current_distance()));
+    }
+
+    assert_eq!(hits, vec![(1, 0), (2, 0), (3, 1), (4, 1)]);
+
+    assert_eq!(map.iter_fuzzy_complete("abc", 0).count(), 2);
+    assert_eq!(map.iter_fuzzy_complete("", 0).count(), 5);
+    assert_eq!(Tst::<usize>::new().iter_fuzzy_complete("abc", 3).next(), None);
+}
+
+
+fn check_count_prefix(map: &Tst<String>, keys: &[String]) {
+
+    assert_eq!(map.count_prefix(""), map.len());
+
+    for key in keys.iter() {
+
+        for (end, _) in key.char_indices().skip(1).chain(Some((key.len(), ' '))) {
+
+            let prefix = &key[..end];
+            assert_eq!(map.count_prefix(prefix), map.iter_complete(prefix).count());
+        }
+
+        let missing = key.clone() + "?";
+        assert_eq!(map.count_prefix(&missing), 0);
+    }
+}
+
+
+#[test]
+fn tst_count_prefix_like_iter_complete() {
+
+    let mut keys: Vec<String> = RANDOM_VEC_123.iter().map(|k| k.to_string()).collect();
+
+    keys.extend(RANDOM_VEC_123.iter().map(|k| "🗝".to_owned()+k));
+    keys.extend(["http://www.example.org/some/long/path", "http://www.example.org/some/other/path",
+                 "http://www.example.com"].iter().map(|k| k.to_string()));
+
+    for balanced in [false, true].iter() {
+
+        let mut map = if *balanced { Tst::new_balanced() } else { Tst::new() };
+
+        for k in keys.iter() {
+
+            map.insert(k, k.clone());
+            check_count_prefix(&map, &keys);
+        }
+
+        for k in keys.iter().step_by(3) {
+
+            map.remove(k);
+            map.remove(k);
+            check_count_prefix(&map, &keys);
+        }
+
+        map.clear();
+        check_count_prefix(&map, &keys);
+    }
+}
+
+
+#[test]
+fn tst_count_prefix_after_reload() {
+
+    let map = get_sample_map_abc_abc_with_unicode();
+    let keys: Vec<String> = RANDOM_VEC_123.iter().map(|k| "🗝".to_owned()+k).collect();
+
+    let mut bytes = Vec::new();
+    map.write_to(&mut bytes).unwrap();
+
+    let loaded: Tst<String> = Tst::read_from(&mut bytes.as_slice()).unwrap();
+
+    check_count_prefix(&loaded, &keys);
+    assert_eq!(loaded.count_prefix("🗝a"), 8);
+    assert_eq!(loaded.count_prefix("🗝ab"), 3);
+    assert_eq!(loaded.count_prefix("🗝d"), 0);
+}
+
+
+#[test]
+fn tst_rank_and_select_like_iter() {
+
+    for balanced in [false, true].iter() {
+
+        let mut map = if *balanced { Tst::new_balanced() } else { Tst::new() };
+
+        for k in RANDOM_VEC_123.iter() {
+
+            map.insert(&("🗝".to_owned()+k), k.len());
+            map.insert(&("http://www.example.org/".to_owned()+k), k.len());
+            map.insert(k, k.len());
+        }
+
+        map.remove("abc");
+        map.remove("🗝b");
+
+        let mut entries = Vec::new();
+        let mut it = map.iter();
+
+        while let Some(value) = it.next() {
+
+            entries.push((it.current_key(), value));
+        }
+
+        for (n, &(ref key, value)) in entries.iter().enumerate() {
+
+            assert_eq!(map.rank(key), n);
+            assert_eq!(map.select(n), Some((key.clone(), value)));
+        }
+
+        assert_eq!(map.select(entries.len()), None);
+        assert_eq!(map.rank(""), 0);
+        assert_eq!(map.rank("\u{10ffff}"), entries.len());
+    }
+}
+
+
+#[test]
+fn tst_rank_of_missing_keys() {
+
+    let map = get_sample_map_abc_abc();
+
+    for missing in ["0", "aaa", "aac", "abca", "ad", "b0", "ba", "bc0", "bd", "cb", "ccb", "d", "http"].iter() {
+
+        assert_eq!(map.get(missing), None);
+        assert_eq!(map.rank(missing), SORTED_VEC_123.iter().filter(|k| *k < missing).count());
+    }
+
+    let empty: Tst<i32> = Tst::new();
+
+    assert_eq!(empty.rank("abc"), 0);
+    assert_eq!(empty.select(0), None);
+}
+
+
+fn get_range_by_brute_force(map: &Tst<String>, start: Bound<&str>, end: Bound<&str>) -> Vec<String> {
+
+    let mut keys = Vec::new();
+    let mut it = map.iter();
+
+    while let Some(value) = it.next() {
+
+        let key = it.current_key();
+
+        assert_eq!(*value, key);
+
+        let after_start = match start {
+
+            Bound::Included(s) => key.as_str() >= s,
+
+            Bound::Excluded(s) => key.as_str() > s,
+
+            Bound::Unbounded => true
+        };
+
+        let before_end = match end {
+
+            Bound::Included(e) => key.as_str() <= e,
+
+            Bound::Excluded(e) => key.as_str() < e,
+
+            Bound::Unbounded => true
+        };
+
+        if after_start && before_end {
+
+            keys.push(key);
+        }
+    }
+
+    keys
+}
+
+
+#[test]
+fn tst_range_like_brute_force() {
+
+    let mut map = Tst::new();
+
+    for k in RANDOM_VEC_123.iter() {
+
+        map.insert(k, k.to_string());
+        map.insert(&("http://www.example.org/".to_owned()+k), "http://www.example.org/".to_owned()+k);
+    }
+
+    let probes = ["", "a", "aa", "aaa", "ab", "abc", "abcd", "b", "ba", "c", "cc", "ccaa", "d", "http://www", "http://www.example.org/b",
+                  "http://www.example.org/bac", "http://www.example.org/z", "🗝"];
+
+    let mut bounds = vec![Bound::Unbounded];
+
+    for p in probes.iter() {
+
+        bounds.push(Bound::Included(*p));
+        bounds.push(Bound::Excluded(*p));
+    }
+
+    for start in bounds.iter() {
+
+        for end in bounds.iter() {
+
+            let expected = get_range_by_brute_force(&map, *start, *end);
+
+            let mut forward = Vec::new();
+            let mut it = map.range((*start, *end));
+
+            while let Some(value) = it.next() {
+
+                assert_eq!(*value, it.current_key());
+                forward.push(value.clone());
+            }
+
+            assert_eq!(forward, expected);
+
+            let mut backward = Vec::new();
+            let mut it = map.range((*start, *end));
+
+            while let Some(value) = it.next_back() {
+
+                assert_eq!(*value, it.current_key_back());
+                backward.push(value.clone());
+            }
+
+            backward.reverse();
+            assert_eq!(backward, expected);
+
+            let mut both = Vec::new();
+            let mut it = map.range((*start, *end));
+
+            while let Some(value) = it.next() {
+
+                both.push(value.clone());
+
+                if let Some(value) = it.next_back() {
+
+                    both.push(value.clone());
+                }
+            }
+
+            both.sort();
+            assert_eq!(both, expected);
+        }
+    }
+}
+
+
+#[test]
+fn tst_range_with_std_ranges() {
+
+    let map = get_sample_map_abc_abc();
+
+    let keys = |mut it: ternary_tree::TstRangeIterator<&str>| {
+
+        let mut v = Vec::new();
+
+        while it.next().is_some() {
+
+            v.push(it.current_key());
+        }
+
+        v
+    };
+
+    assert_eq!(keys(map.range("ab".."b")), ["ab", "aba", "abb", "abc", "ac", "aca"]);
+    assert_eq!(keys(map.range("bac"..="c")), ["bac", "bc", "c"]);
+    assert_eq!(keys(map.range("cb"..)), ["cbc", "cca"]);
+    assert_eq!(keys(map.range(.."aab")), ["a", "aa"]);
+    assert_eq!(keys(map.range(..="aab")), ["a", "aa", "aab"]);
+    assert_eq!(keys(map.range::<std::ops::RangeFull>(..)).len(), 16);
+    assert_eq!(keys(map.range("c".."b")).len(), 0);
+    assert_eq!(keys(map.range("d"..)).len(), 0);
+
+    let empty: Tst<i32> = Tst::new();
+    assert_eq!(empty.range("a".."z").next(), None);
+}
+
+
+#[test]
+fn tst_ceiling_floor_successor_predecessor() {
+
+    let mut map = Tst::new();
+    let mut keys = Vec::new();
+
+    for k in RANDOM_VEC_123.iter() {
+
+        let long_key = "http://www.example.org/".to_owned()+k;
+
+        map.insert(k, k.len());
+        map.insert(&long_key, long_key.len());
+
+        keys.push(k.to_string());
+        keys.push(long_key);
+    }
+
+    keys.sort();
+
+    let probes = ["", "0", "a", "aaa", "ab", "abcd", "b", "bb", "cca", "ccb", "http", "http://www.example.org/",
+                  "http://www.example.org/ab", "http://www.example.org/abz", "z", "🗝"];
+
+    let with_value = |key: Option<&String>| key.map(|key| (key.clone(), map.get(key).unwrap()));
+
+    for probe in probes.iter().map(|p| p.to_string()).chain(keys.iter().cloned()) {
+
+        let probe = probe.as_str();
+
+        assert_eq!(map.ceiling(probe), with_value(keys.iter().find(|k| k.as_str() >= probe)));
+        assert_eq!(map.successor(probe), with_value(keys.iter().find(|k| k.as_str() > probe)));
+        assert_eq!(map.floor(probe), with_value(keys.iter().rev().find(|k| k.as_str() <= probe)));
+        assert_eq!(map.predecessor(probe), with_value(keys.iter().rev().find(|k| k.as_str() < probe)));
+    }
+}
+
+
+#[test]
+fn tst_ceiling_floor_on_small_trees() {
+
+    let empty: Tst<i32> = Tst::new();
+
+    assert_eq!(empty.ceiling("a"), None);
+    assert_eq!(empty.floor("a"), None);
+    assert_eq!(empty.successor(""), None);
+    assert_eq!(empty.predecessor("z"), None);
+
+    let mut map = Tst::new_balanced();
+
+    map.insert("🐟", 1);
+
+    assert_eq!(map.ceiling(""), Some(("🐟".to_string(), &1)));
+    assert_eq!(map.floor("🐟🐟"), Some(("🐟".to_string(), &1)));
+    assert_eq!(map.successor("🐟"), None);
+    assert_eq!(map.predecessor("🐟"), None);
+    assert_eq!(map.floor(""), None);
+}
+
+
+fn get_seek_sample() -> (Tst<String>, Vec<String>) {
+
+    let mut map = Tst::new();
+
+    for k in RANDOM_VEC_123.iter() {
+
+        map.insert(k, k.to_string());
+        map.insert(&("🗝".to_owned()+k), "🗝".to_owned()+k);
+        map.insert(&("http://www.example.org/".to_owned()+k), "http://www.example.org/".to_owned()+k);
+    }
+
+    let mut probes: Vec<String> = ["", "0", "aaa", "abcd", "bb", "ccb", "http", "http://www.example.org/",
+                                   "http://www.example.org/abz", "z", "🗝", "🗝bb", "🗝🗝"].iter().map(|p| p.to_string()).collect();
+
+    let it = map.iter();
+
+    for value in it {
+
+        probes.push(value.clone());
+    }
+
+    (map, probes)
+}
+
+
+#[test]
+fn tst_iterator_seek() {
+
+    let (map, probes) = get_seek_sample();
+
+    let mut keys: Vec<String> = Vec::new();
+    map.visit_values(|k| keys.push(k.clone()));
+
+    let mut it = map.iter();
+
+    for probe in probes.iter() {
+
+        it.seek(probe);
+
+        let mut v = Vec::new();
+
+        while let Some(value) = it.next() {
+
+            assert_eq!(*value, it.current_key());
+            v.push(value.clone());
+        }
+
+        let expected: Vec<String> = keys.iter().filter(|k| *k >= probe).cloned().collect();
+        assert_eq!(v, expected);
+
+        it.seek(probe);
+
+        let mut v = Vec::new();
+
+        while let Some(value) = it.next_back() {
+
+            v.push(value.clone());
+        }
+
+        v.reverse();
+        assert_eq!(v, expected);
+
+        it.seek_back(probe);
+
+        let mut v = Vec::new();
+
+        while let Some(value) = it.next_back() {
+
+            assert_eq!(*value, it.current_key_back());
+            v.push(value.clone());
+        }
+
+        v.reverse();
+
+        let expected: Vec<String> = keys.iter().filter(|k| *k <= probe).cloned().collect();
+        assert_eq!(v, expected);
+
+        it.seek_back(probe);
+        assert_eq!(it.by_ref().cloned().collect::<Vec<String>>(), expected);
+    }
+}
+
+
+#[test]
+fn tst_complete_iterator_seek() {
+
+    let (map, probes) = get_seek_sample();
+
+    for prefix in ["", "a", "ab", "🗝", "🗝a", "http://www.exa", "http://www.example.org/", "http://www.example.org/a",
+                   "d"].iter() {
+
+        let mut keys: Vec<String> = Vec::new();
+        map.visit_complete_values(prefix, |k| keys.push(k.clone()));
+
+        let mut it = map.iter_complete(prefix);
+
+        for probe in probes.iter() {
+
+            it.seek(probe);
+
+            let mut v = Vec::new();
+
+            while let Some(value) = it.next() {
+
+                assert_eq!(*value, it.current_key());
+                v.push(value.clone());
+            }
+
+            let expected: Vec<String> = keys.iter().filter(|k| *k >= probe).cloned().collect();
+            assert_eq!(v, expected);
+
+            it.seek_back(probe);
+
+            let mut v = Vec::new();
+
+            while let Some(value) = it.next_back() {
+
+                assert_eq!(*value, it.current_key_back());
+                v.push(value.clone());
+            }
+
+            v.reverse();
+
+            let expected: Vec<String> = keys.iter().filter(|k| *k <= probe).cloned().collect();
+            assert_eq!(v, expected);
+        }
+    }
+}
+
+
+#[test]
+fn tst_first_and_last_key_value() {
+
+    let map = get_sample_map_abc_abc_with_unicode();
+
+    assert_eq!(map.first_key_value(), Some(("🗝a".to_string(), &"📦a".to_string())));
+    assert_eq!(map.last_key_value(), Some(("🗝cca".to_string(), &"📦cca".to_string())));
+
+    let empty: Tst<i32> = Tst::new();
+
+    assert_eq!(empty.first_key_value(), None);
+    assert_eq!(empty.last_key_value(), None);
+}
+
+
+#[test]
+fn tst_pop_first_and_last() {
+
+    for balanced in [false, true].iter() {
+
+        let mut map = if *balanced { Tst::new_balanced() } else { Tst::new() };
+
+        for k in RANDOM_VEC_123.iter() {
+
+            map.insert(k, k.len());
+            map.insert(&("http://www.example.org/".to_owned()+k), k.len());
+        }
+
+        //a pop prunes the tree the same way as a remove of the same key
+        let mut removed_map = if *balanced { Tst::new_balanced() } else { Tst::new() };
+
+        for k in RANDOM_VEC_123.iter() {
+
+            removed_map.insert(k, k.len());
+            removed_map.insert(&("http://www.example.org/".to_owned()+k), k.len());
+        }
+
+        let mut keys: Vec<String> = Vec::new();
+        let mut it = map.iter();
+
+        while it.next().is_some() {
+
+            keys.push(it.current_key());
+        }
+
+        let mut front = 0;
+        let mut back = keys.len();
+
+        while front < back {
+
+            let (key, _) = map.pop_first().unwrap();
+            assert_eq!(key, keys[front]);
+            removed_map.remove(&key);
+            front += 1;
+
+            if front < back {
+
+                back -= 1;
+
+                let (key, value) = map.pop_last().unwrap();
+                assert_eq!(key, keys[back]);
+                assert_eq!(value, key.trim_start_matches("http://www.example.org/").len());
+                removed_map.remove(&key);
+            }
+
+            assert_eq!(map.len(), back - front);
+            assert_eq!(map.count_prefix(""), back - front);
+            assert_eq!(map.stat(), removed_map.stat());
+        }
+
+        assert_eq!(map.pop_first(), None);
+        assert_eq!(map.pop_last(), None);
+        assert_eq!(map.stat().count.nodes, 0);
+    }
+}
+
+
+#[test]
+fn tst_retain() {
+
+    for balanced in [false, true].iter() {
+
+        let new_map = || {
+
+            let mut map = if *balanced { Tst::new_balanced() } else { Tst::new() };
+
+            for k in RANDOM_VEC_123.iter() {
+
+                map.insert(k, k.len());
+                map.insert(&("http://www.example.org/".to_owned()+k), k.len());
+            }
+
+            map
+        };
+
+        let mut map = new_map();
+        let mut other = new_map();
+
+        let mut removed = Vec::new();
+        let mut it = other.iter();
+
+        while let Some(value) = it.next() {
+
+            if value % 2 == 0 || it.current_key().ends_with('a') {
+
+                removed.push(it.current_key());
+            }
+        }
+
+        for k in removed.iter() {
+
+            other.remove(k);
+        }
+
+        let mut visited = Vec::new();
+
+        map.retain(|key, value| {
+
+            visited.push(key.to_string());
+            *value *= 10;
+
+            !(*value % 20 == 0 || key.ends_with('a'))
+        });
+
+        let fresh = new_map();
+        let mut keys = Vec::new();
+        let mut it = fresh.iter();
+
+        while it.next().is_some() {
+
+            keys.push(it.current_key());
+        }
+
+        assert_eq!(visited, keys);
+
+        assert_eq!(map.len(), other.len());
+        assert_eq!(map.count_prefix(""), other.len());
+        assert_eq!(map.count_prefix("http://"), other.count_prefix("http://"));
+        assert_eq!(map.stat().count.values, other.len());
+
+        if !*balanced {
+
+            assert_eq!(map.stat().count.nodes, other.stat().count.nodes);
+        }
+
+        for k in keys.iter() {
+
+            assert_eq!(map.get(k).map(|value| value / 10), other.get(k).cloned());
+        }
+
+        map.retain(|_, _| false);
+
+        assert_eq!(map.len(), 0);
+        assert_eq!(map.stat().count.nodes, 0);
+    }
+}
+
+
+#[test]
+fn tst_extract_matching() {
+
+    for balanced in [false, true].iter() {
+
+        let mut map = if *balanced { Tst::new_balanced() } else { Tst::new() };
+
+        for k in RANDOM_VEC_123.iter() {
+
+            map.insert(k, k.len());
+        }
+
+        let len = map.len();
+
+        let extracted = map.extract_matching(|key, _| key.starts_with("b"));
+
+        assert!(!extracted.is_empty());
+        assert_eq!(map.len() + extracted.len(), len);
+        assert_eq!(map.count_prefix("b"), 0);
+        assert_eq!(map.stat().count.values, map.len());
+
+        for (i, &(ref key, value)) in extracted.iter().enumerate() {
+
+            assert!(key.starts_with("b"));
+            assert_eq!(value, key.len());
+            assert_eq!(map.get(key), None);
+
+            if i > 0 {
+
+                assert!(extracted[i-1].0 < *key);
+            }
+        }
+
+        for (key, value) in extracted {
+
+            map.insert(&key, value);
+        }
+
+        assert_eq!(map.len(), len);
+        assert!(map.extract_matching(|_, _| false).is_empty());
+    }
+}
+
+
+#[test]
+fn tst_remove_prefix() {
+
+    for balanced in [false, true].iter() {
+
+        for prefix in ["", "a", "ab", "abc", "b", "http://www.ex", "http://www.example.org/", "http://www.example.org/c", "d", "abz"].iter() {
+
+            let mut map = if *balanced { Tst::new_balanced() } else { Tst::new() };
+
+            for k in RANDOM_VEC_123.iter() {
+
+                map.insert(k, k.len());
+                map.insert(&("http://www.example.org/".to_owned()+k), k.len());
+            }
+
+            let len = map.len();
+            let expected = map.count_prefix(prefix);
+            let own_value = map.get(prefix).cloned();
+
+            let mut keys = Vec::new();
+            let mut it = map.iter_complete(prefix);
+
+            while it.next().is_some() {
+
+                keys.push(it.current_key());
+            }
+
+            let sub = map.remove_prefix(prefix);
+
+            assert_eq!(sub.len(), expected);
+            assert_eq!(sub.stat().count.values, expected);
+            assert_eq!(sub.is_balanced(), *balanced);
+            assert_eq!(map.len(), len - expected);
+            assert_eq!(map.stat().count.values, len - expected);
+            assert_eq!(map.count_prefix(prefix), 0);
+            assert_eq!(map.get(prefix).cloned(), own_value);
+
+            let mut sub_keys = Vec::new();
+            let mut it = sub.iter();
+
+            while let Some(value) = it.next() {
+
+                let key = prefix.to_string() + &it.current_key();
+
+                assert_eq!(*value, key.trim_start_matches("http://www.example.org/").len());
+                sub_keys.push(key);
+            }
+
+            assert_eq!(sub_keys, keys);
+
+            for k in keys.iter() {
+
+                assert_eq!(map.get(k), None);
+            }
+
+            for k in RANDOM_VEC_123.iter() {
+
+                if !k.starts_with(prefix) || *k == *prefix {
+
+                    assert_eq!(map.get(k), Some(&k.len()));
+                }
+            }
+        }
+    }
+}
+
+
+#[test]
+fn tst_remove_prefix_keeping() {
+
+    let mut map = Tst::new();
+
+    for k in RANDOM_VEC_123.iter() {
+
+        map.insert(&("user:42:".to_owned()+k), k.len());
+        map.insert(&("user:7:".to_owned()+k), k.len());
+    }
+
+    let user = map.remove_prefix_keeping("user:42:");
+
+    assert_eq!(user.len(), RANDOM_VEC_123.len());
+    assert_eq!(map.len(), RANDOM_VEC_123.len());
+
+    for k in RANDOM_VEC_123.iter() {
+
+        assert_eq!(user.get(&("user:42:".to_owned()+k)), Some(&k.len()));
+        assert_eq!(user.get(&("user:7:".to_owned()+k)), None);
+        assert_eq!(map.get(&("user:42:".to_owned()+k)), None);
+        assert_eq!(map.get(&("user:7:".to_owned()+k)), Some(&k.len()));
+    }
+
+    assert_eq!(user.count_prefix("user:42:"), user.len());
+
+    assert_eq!(map.remove_prefix_keeping("user:42:").len(), 0);
+}
+
+
+#[test]
+fn tst_split_off() {
+
+    for balanced in [false, true].iter() {
+
+        let new_map = || {
+
+            let mut map = if *balanced { Tst::new_balanced() } else { Tst::new() };
+
+            for k in RANDOM_VEC_123.iter() {
+
+                map.insert(k, k.len());
+                map.insert(&("http://www.example.org/".to_owned()+k), k.len());
+            }
+
+            map
+        };
+
+        let fresh = new_map();
+        let mut keys = Vec::new();
+        let mut it = fresh.iter();
+
+        while it.next().is_some() {
+
+            keys.push(it.current_key());
+        }
+
+        for key in ["", "a", "aa", "ab", "abz", "b", "ba", "bz", "c", "cz", "d", "h", "http://www.example.org/",
+                    "http://www.example.org/b", "http://www.ex", "http://www.fx"].iter() {
+
+            let mut map = new_map();
+            let high = map.split_off(key);
+
+            let mut low_keys = Vec::new();
+            let mut it = map.iter();
+
+            while it.next().is_some() {
+
+                low_keys.push(it.current_key());
+            }
+
+            let mut high_keys = Vec::new();
+            let mut it = high.iter();
+
+            while it.next().is_some() {
+
+                high_keys.push(it.current_key());
+            }
+
+            let expected_low: Vec<String> = keys.iter().filter(|k| k.as_str() < *key).cloned().collect();
+            let expected_high: Vec<String> = keys.iter().filter(|k| k.as_str() >= *key).cloned().collect();
+
+            assert_eq!(low_keys, expected_low);
+            assert_eq!(high_keys, expected_high);
+            assert_eq!(map.len(), expected_low.len());
+            assert_eq!(high.len(), expected_high.len());
+            assert_eq!(map.count_prefix(""), map.len());
+            assert_eq!(high.stat().count.values, high.len());
+            assert_eq!(high.is_balanced(), *balanced);
+
+            for k in high_keys.iter() {
+
+                assert_eq!(high.get(k), fresh.get(k));
+            }
+        }
+    }
+}
+
+
+#[test]
+fn tst_append() {
+
+    for balanced in [false, true].iter() {
+
+        let mut map = if *balanced { Tst::new_balanced() } else { Tst::new() };
+        let mut other = if *balanced { Tst::new_balanced() } else { Tst::new() };
+        let mut all = Tst::new();
+
+        for (i, k) in RANDOM_VEC_123.iter().enumerate() {
+
+            let key = "http://www.example.org/".to_owned()+k;
+
+            if i % 3 == 0 {
+
+                map.insert(k, k.len());
+                all.insert(k, k.len());
+
+            } else {
+
+                other.insert(k, 10*k.len());
+                all.insert(k, 10*k.len());
+            }
+
+            if i % 2 == 0 {
+
+                map.insert(&key, k.len());
+            }
+
+            other.insert(&key, 10*k.len());
+            all.insert(&key, 10*k.len());
+        }
+
+        map.append(&mut other);
+
+        assert_eq!(other.len(), 0);
+        assert_eq!(other.stat().count.nodes, 0);
+        assert_eq!(map.len(), all.len());
+        assert_eq!(map.count_prefix("http://"), all.count_prefix("http://"));
+        assert_eq!(map.stat().count.values, all.len());
+
+        let mut it = all.iter();
+
+        while let Some(value) = it.next() {
+
+            assert_eq!(map.get(&it.current_key()), Some(value));
+        }
+
+        let mut high = map.split_off("b");
+
+        high.append(&mut map);
+
+        assert_eq!(high.len(), all.len());
+        assert_eq!(high.stat().count.values, all.len());
+        assert_eq!(map.len(), 0);
+    }
+}
+
+
+fn get_set_operands(balanced: bool) -> (Tst<usize>, Tst<usize>) {
+
+    let mut map = if balanced { Tst::new_balanced() } else { Tst::new() };
+    let mut other = if balanced { Tst::new_balanced() } else { Tst::new() };
+
+    for (i, k) in RANDOM_VEC_123.iter().enumerate() {
+
+        if i % 3 != 1 {
+
+            map.insert(k, i);
+            map.insert(&("http://www.example.org/".to_owned()+k), i);
+        }
+
+        if i % 3 != 2 {
+
+            other.insert(k, 100+i);
+        }
+
+        other.insert(&("http://www.example.com/".to_owned()+k), 100+i);
+    }
+
+    (map, other)
+}
+
+
+fn get_keys(map: &Tst<usize>) -> Vec<String> {
+
+    let mut keys = Vec::new();
+    let mut it = map.iter();
+
+    while it.next().is_some() {
+
+        keys.push(it.current_key());
+    }
+
+    keys
+}
+
+
+#[test]
+fn tst_union_and_intersection() {
+
+    for balanced in [false, true].iter() {
+
+        let (map, other) = get_set_operands(*balanced);
+        let (map_keys, other_keys) = (get_keys(&map), get_keys(&other));
+
+        let mut all_keys: Vec<String> = map_keys.iter().chain(other_keys.iter()).cloned().collect();
+        all_keys.sort();
+        all_keys.dedup();
+
+        let common_keys: Vec<String> = map_keys.iter().filter(|k| other.get(k).is_some()).cloned().collect();
+
+        let mut combined = Vec::new();
+
+        let union = get_set_operands(*balanced).0.union(get_set_operands(*balanced).1, |key, value, other_value| {
+
+            combined.push(key.to_string());
+            1000*value + other_value
+        });
+
+        assert_eq!(combined, common_keys);
+        assert_eq!(get_keys(&union), all_keys);
+        assert_eq!(union.len(), all_keys.len());
+        assert_eq!(union.stat().count.values, all_keys.len());
+        assert_eq!(union.is_balanced(), *balanced);
+
+        for k in all_keys.iter() {
+
+            let expected = match (map.get(k), other.get(k)) {
+
+                (Some(value), Some(other_value)) => 1000*value + other_value,
+
+                (Some(value), None) => *value,
+
+                (None, Some(other_value)) => *other_value,
+
+                (None, None) => unreachable!()
+            };
+
+            assert_eq!(union.get(k), Some(&expected));
+        }
+
+        let intersection = get_set_operands(*balanced).0.intersection(get_set_operands(*balanced).1, |_, value, other_value| 1000*value + other_value);
+
+        assert_eq!(get_keys(&intersection), common_keys);
+        assert_eq!(intersection.count_prefix(""), common_keys.len());
+        assert_eq!(intersection.stat().count.values, common_keys.len());
+
+        for k in common_keys.iter() {
+
+            assert_eq!(intersection.get(k), Some(&(1000*map.get(k).unwrap() + other.get(k).unwrap())));
+        }
+
+        let empty = get_set_operands(*balanced).0.intersection(Tst::new(), |_, value, _| value);
+
+        assert_eq!(empty.len(), 0);
+        assert_eq!(empty.stat().count.nodes, 0);
+    }
+}
+
+
+#[test]
+fn tst_difference_and_symmetric_difference() {
+
+    for balanced in [false, true].iter() {
+
+        let (map, other) = get_set_operands(*balanced);
+        let (map_keys, other_keys) = (get_keys(&map), get_keys(&other));
+
+        let only_map: Vec<String> = map_keys.iter().filter(|k| other.get(k).is_none()).cloned().collect();
+        let only_other: Vec<String> = other_keys.iter().filter(|k| map.get(k).is_none()).cloned().collect();
+
+        let difference = get_set_operands(*balanced).0.difference(get_set_operands(*balanced).1);
+
+        assert_eq!(get_keys(&difference), only_map);
+        assert_eq!(difference.len(), only_map.len());
+        assert_eq!(difference.stat().count.values, only_map.len());
+
+        for k in only_map.iter() {
+
+            assert_eq!(difference.get(k), map.get(k));
+        }
+
+        let mut either: Vec<String> = only_map.iter().chain(only_other.iter()).cloned().collect();
+        either.sort();
+
+        let symmetric_difference = get_set_operands(*balanced).0.symmetric_difference(get_set_operands(*balanced).1);
+
+        assert_eq!(get_keys(&symmetric_difference), either);
+        assert_eq!(symmetric_difference.len(), either.len());
+        assert_eq!(symmetric_difference.count_prefix("http://www.example.org/"),
+                   only_map.iter().filter(|k| k.starts_with("http://")).count());
+
+        for k in only_other.iter() {
+
+            assert_eq!(symmetric_difference.get(k), other.get(k));
+        }
+
+        assert_eq!(get_set_operands(*balanced).0.difference(get_set_operands(*balanced).0).len(), 0);
+        assert_eq!(get_set_operands(*balanced).0.symmetric_difference(Tst::new()).len(), map.len());
+    }
+}
+
+
+#[test]
+fn tst_diff() {
+
+    for balanced in [false, true].iter() {
+
+        let (old, new) = get_set_operands(*balanced);
+        let (old_keys, new_keys) = (get_keys(&old), get_keys(&new));
+
+        let mut all_keys: Vec<String> = old_keys.iter().chain(new_keys.iter()).cloned().collect();
+        all_keys.sort();
+        all_keys.dedup();
+
+        let mut expected = Vec::new();
+
+        for k in all_keys.iter() {
+
+            match (old.get(k), new.get(k)) {
+
+                (Some(old_value), Some(new_value)) => if old_value != new_value {
+
+                    expected.push(TstDiff::Changed(k.clone(), old_value, new_value));
+                },
+
+                (Some(old_value), None) => expected.push(TstDiff::Removed(k.clone(), old_value)),
+
+                (None, Some(new_value)) => expected.push(TstDiff::Added(k.clone(), new_value)),
+
+                (None, None) => unreachable!()
+            }
+        }
+
+        let diff: Vec<_> = old.diff(&new).collect();
+
+        assert_eq!(diff, expected);
+
+        let back: Vec<_> = new.diff(&old).collect();
+
+        assert_eq!(back.len(), diff.len());
+        assert_eq!(old.diff(&old).next(), None);
+    }
+}
+
+
+#[test]
+fn tst_diff_on_empty_and_changed_values() {
+
+    let empty: Tst<usize> = Tst::new();
+    let mut map = Tst::new();
+
+    for (i, k) in RANDOM_VEC_123.iter().enumerate() {
+
+        map.insert(k, i);
+    }
+
+    let keys = get_keys(&map);
+
+    let added: Vec<String> = empty.diff(&map).map(|diff| match diff {
+
+        TstDiff::Added(key, _) => key,
+
+        _ => panic!()
+    }).collect();
+
+    assert_eq!(added, keys);
+    assert_eq!(map.diff(&empty).count(), keys.len());
+    assert_eq!(empty.diff(&empty).count(), 0);
+
+    let mut other = Tst::new_balanced();
+
+    for (i, k) in RANDOM_VEC_123_BIS.iter().enumerate() {
+
+        other.insert(k, if k.len() == 2 { i + 100 } else { *map.get(k).unwrap() });
+    }
+
+    let changed: Vec<String> = map.diff(&other).map(|diff| match diff {
+
+        TstDiff::Changed(key, old_value, new_value) => {
+
+            assert_eq!(old_value, map.get(&key).unwrap());
+            assert_eq!(*new_value, RANDOM_VEC_123_BIS.iter().position(|k| **k == key).unwrap() + 100);
+            key
+        }
+
+        _ => panic!()
+    }).collect();
+
+    assert_eq!(changed, keys.iter().filter(|k| k.len() == 2).cloned().collect::<Vec<String>>());
+}
+
+
+#[test]
+fn tst_diff_across_runs() {
+
+    // The old tree keeps each long key in a single run, which the new tree splits where keys branch off.
+
+    let mut old = Tst::new();
+
+    old.insert("http://www.example.org/index.html", 1);
+    old.insert("http://www.example.org/", 2);
+    old.insert("ftp://example.org/pub", 3);
+
+    let mut new = Tst::new_balanced();
+
+    for (i, k) in ["http://www.example.org/images/logo.png", "ftp://example.org/pub", "http://www.example.org/",
+                   "http://www.example.org/index.html", "http://www.example.com/", "ftp://example.org/pub/"].iter()
+                   .enumerate() {
+
+        new.insert(k, i);
+    }
+
+    new.insert("http://www.example.org/index.html", 10);
+    new.insert("ftp://example.org/pub", 3);
+
+    assert!(new.stat().count.nodes > old.stat().count.nodes);
+
+    let diff: Vec<_> = old.diff(&new).collect();
+
+    assert_eq!(diff, vec![TstDiff::Added("ftp://example.org/pub/".to_string(), &5),
+                          TstDiff::Added("http://www.example.com/".to_string(), &4),
+                          TstDiff::Added("http://www.example.org/images/logo.png".to_string(), &0),
+                          TstDiff::Changed("http://www.example.org/index.html".to_string(), &1, &10)]);
+
+    new.remove("http://www.example.org/images/logo.png");
+
+    let back: Vec<_> = new.diff(&old).collect();
+
+    assert_eq!(back, vec![TstDiff::Removed("ftp://example.org/pub/".to_string(), &5),
+                          TstDiff::Removed("http://www.example.com/".to_string(), &4),
+                          TstDiff::Changed("http://www.example.org/index.html".to_string(), &10, &1)]);
+}
+
+
+#[test]
+fn tst_rename_prefix() {
+
+    for balanced in [false, true].iter() {
+
+        for &(from, to) in [("http://www.example.org/", "https://example.org/"), ("a", "b"), ("a", "ab"), ("ab", "a"),
+                            ("", "x"), ("c", ""), ("z", "a")].iter() {
+
+            let mut map = if *balanced { Tst::new_balanced() } else { Tst::new() };
+
+            for k in RANDOM_VEC_123.iter() {
+
+                map.insert(k, k.len());
+                map.insert(&("http://www.example.org/".to_owned()+k), 10*k.len());
+            }
+
+            let mut expected = Tst::new();
+            let mut expected_collisions = Vec::new();
+            let mut moved = Vec::new();
+            let mut it = map.iter();
+
+            while let Some(value) = it.next() {
+
+                let key = it.current_key();
+
+                if key.starts_with(from) && key != from {
+
+                    moved.push((to.to_string() + &key[from.len()..], *value));
+
+                } else {
+
+                    expected.insert(&key, *value);
+                }
+            }
+
+            for &(ref key, value) in moved.iter() {
+
+                if let Some(old_value) = expected.insert(key, value) {
+
+                    expected_collisions.push((key.clone(), old_value));
+                }
+            }
+
+            expected_collisions.sort();
+
+            let collisions = map.rename_prefix(from, to);
+
+            assert_eq!(collisions, expected_collisions);
+            assert_eq!(get_keys(&map), get_keys(&expected));
+            assert_eq!(map.len(), expected.len());
+            assert_eq!(map.stat().count.values, expected.len());
+            assert_eq!(map.count_prefix(to), expected.count_prefix(to));
+
+            let mut it = expected.iter();
+
+            while let Some(value) = it.next() {
+
+                assert_eq!(map.get(&it.current_key()), Some(value));
+            }
+        }
+    }
+}
+
+
+#[test]
+fn tst_rename_prefix_with() {
+
+    let mut map = Tst::new();
+
+    for k in RANDOM_VEC_123.iter() {
+
+        map.insert(&("project/old/".to_owned()+k), 1);
+
+        if k.len() == 2 {
+
+            map.insert(&("project/new/".to_owned()+k), 10);
+        }
+    }
+
+    let mut combined = Vec::new();
+
+    map.rename_prefix_with("project/old/", "project/new/", |key, value, moved_value| {
+
+        combined.push(key.to_string());
+        value + moved_value
+    });
+
+    assert_eq!(map.len(), RANDOM_VEC_123.len());
+    assert_eq!(map.count_prefix("project/old/"), 0);
+    assert_eq!(combined.len(), RANDOM_VEC_123.iter().filter(|k| k.len() == 2).count());
+
+    for k in RANDOM_VEC_123.iter() {
+
+        assert_eq!(map.get(&("project/new/".to_owned()+k)), Some(&if k.len() == 2 { 11 } else { 1 }));
+    }
+
+    map.rename_prefix_with("nothing/", "project/", |_, _, _| unreachable!());
+
+    assert_eq!(map.len(), RANDOM_VEC_123.len());
+}